@@ -0,0 +1,201 @@
+//! Throughput of every error-correction layer.
+//!
+//! Run with `cargo bench --bench error_correction`. Classical and bridge
+//! groups report bytes per second over payload size; the surface-code group
+//! reports decodes per second for one patch at each distance.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use elxr::error::bridge::checksum::Checksum;
+use elxr::error::quantum::decoder::{Decoder, GreedyDecoder, MinimumWeightDecoder, UnionFindDecoder};
+use elxr::error::quantum::matching::MatchingGraph;
+use elxr::error::quantum::surface_code::{LogicalState, PauliKind, RotatedSurfaceCode};
+use elxr::error::{
+    BridgeErrorCorrection, ClassicalErrorCorrection, CorrectionConfig, ErrorCorrector, LayerStack,
+};
+
+/// Payload sizes: a telemetry reading and a batched cross-chain message.
+const PAYLOAD_SIZES: [usize; 2] = [256, 16 * 1024];
+
+/// (data, parity) layouts, from the default 4 + 2 up to wide archival layouts.
+const SHARD_CONFIGS: [(usize, usize); 4] = [(4, 2), (10, 4), (16, 8), (32, 16)];
+
+const SURFACE_CODE_DISTANCES: [usize; 4] = [3, 5, 7, 9];
+
+fn payload(len: usize) -> Vec<u8> {
+    let mut rng = ChaCha8Rng::seed_from_u64(len as u64);
+    (0..len).map(|_| rng.gen()).collect()
+}
+
+fn classical(c: &mut Criterion) {
+    let mut group = c.benchmark_group("classical");
+    for &(data_shards, parity_shards) in &SHARD_CONFIGS {
+        let codec = ClassicalErrorCorrection::new(data_shards, parity_shards);
+        let layout = format!("{}+{}", data_shards, parity_shards);
+        for &len in &PAYLOAD_SIZES {
+            let data = payload(len);
+            let shards = codec.encode_bytes(&data);
+            group.throughput(Throughput::Bytes(len as u64));
+
+            group.bench_with_input(
+                BenchmarkId::new(format!("encode/{}", layout), len),
+                &data,
+                |b, data| b.iter(|| codec.encode_bytes(black_box(data))),
+            );
+
+            // Clean shards only pay for the syndrome check
+            group.bench_with_input(
+                BenchmarkId::new(format!("verify/{}", layout), len),
+                &shards,
+                |b, shards| {
+                    b.iter_batched(
+                        || shards.iter().cloned().map(Some).collect::<Vec<_>>(),
+                        |mut received| codec.decode_bytes_correcting(&mut received),
+                        BatchSize::SmallInput,
+                    )
+                },
+            );
+
+            // Every parity shard's worth of data shards lost
+            group.bench_with_input(
+                BenchmarkId::new(format!("reconstruct/{}", layout), len),
+                &shards,
+                |b, shards| {
+                    b.iter_batched(
+                        || {
+                            let mut received: Vec<_> = shards.iter().cloned().map(Some).collect();
+                            received
+                                .iter_mut()
+                                .take(parity_shards)
+                                .for_each(|shard| *shard = None);
+                            received
+                        },
+                        |mut received| codec.decode_bytes(&mut received),
+                        BatchSize::SmallInput,
+                    )
+                },
+            );
+
+            // One corrupted symbol in every codeword, located by the decoder
+            group.bench_with_input(
+                BenchmarkId::new(format!("correct/{}", layout), len),
+                &shards,
+                |b, shards| {
+                    b.iter_batched(
+                        || {
+                            let mut received: Vec<_> = shards.iter().cloned().map(Some).collect();
+                            for (column, byte) in received[0].as_mut().unwrap().iter_mut().enumerate() {
+                                *byte ^= column as u8 | 1;
+                            }
+                            received
+                        },
+                        |mut received| codec.decode_bytes_correcting(&mut received),
+                        BatchSize::SmallInput,
+                    )
+                },
+            );
+        }
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("classical/interleaved");
+    let codec = ClassicalErrorCorrection::new(16, 8);
+    for &len in &PAYLOAD_SIZES {
+        let data = payload(len);
+        let stream = codec.encode_interleaved(&data, 8);
+        group.throughput(Throughput::Bytes(len as u64));
+        group.bench_with_input(BenchmarkId::new("encode", len), &data, |b, data| {
+            b.iter(|| codec.encode_interleaved(black_box(data), 8))
+        });
+        group.bench_with_input(BenchmarkId::new("decode", len), &stream, |b, stream| {
+            b.iter(|| codec.decode_interleaved(black_box(stream), 8, &[]))
+        });
+    }
+    group.finish();
+}
+
+fn bridge(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bridge");
+    for checksum in [Checksum::Crc32, Checksum::Blake2b128] {
+        let bridge = BridgeErrorCorrection::new(3, 1).with_checksum(checksum);
+        for &len in &PAYLOAD_SIZES {
+            let data = payload(len);
+            let frame = bridge.encode(&data);
+            group.throughput(Throughput::Bytes(len as u64));
+            group.bench_with_input(
+                BenchmarkId::new(format!("encode/{:?}", checksum), len),
+                &data,
+                |b, data| b.iter(|| bridge.encode(black_box(data))),
+            );
+            group.bench_with_input(
+                BenchmarkId::new(format!("decode/{:?}", checksum), len),
+                &frame,
+                |b, frame| b.iter(|| bridge.decode(black_box(frame))),
+            );
+        }
+    }
+    group.finish();
+}
+
+fn surface_code(c: &mut Criterion) {
+    let decoders: [(&str, &dyn Decoder); 3] = [
+        ("minimum_weight", &MinimumWeightDecoder),
+        ("union_find", &UnionFindDecoder),
+        ("greedy", &GreedyDecoder),
+    ];
+
+    let mut group = c.benchmark_group("surface_code");
+    for &distance in &SURFACE_CODE_DISTANCES {
+        let code = RotatedSurfaceCode::new(distance);
+        let graph = MatchingGraph::new(&code, PauliKind::Z);
+
+        // A fixed set of 5% bit-flip patterns, so every decoder sees the same syndromes
+        let mut rng = ChaCha8Rng::seed_from_u64(distance as u64);
+        let syndromes: Vec<Vec<bool>> = (0..64)
+            .map(|_| {
+                let mut patch = code.encode(LogicalState::Zero);
+                for qubit in 0..code.data_qubits() {
+                    if rng.gen_bool(0.05) {
+                        patch.apply_x(qubit);
+                    }
+                }
+                code.syndrome(&patch).of(PauliKind::Z).to_vec()
+            })
+            .collect();
+
+        group.throughput(Throughput::Elements(syndromes.len() as u64));
+        for (name, decoder) in decoders {
+            group.bench_with_input(BenchmarkId::new(name, distance), &syndromes, |b, syndromes| {
+                b.iter(|| {
+                    for syndrome in syndromes {
+                        black_box(decoder.correction(&graph, syndrome));
+                    }
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+/// The full three-layer frame, as built for every telemetry packet.
+fn pipeline(c: &mut Criterion) {
+    let stack = LayerStack::from_config(&CorrectionConfig::default());
+    let mut group = c.benchmark_group("pipeline");
+    for &len in &PAYLOAD_SIZES {
+        let data = payload(len);
+        let frame = stack.encode(&data);
+        group.throughput(Throughput::Bytes(len as u64));
+        group.bench_with_input(BenchmarkId::new("encode", len), &data, |b, data| {
+            b.iter(|| stack.encode(black_box(data)))
+        });
+        group.bench_with_input(BenchmarkId::new("decode", len), &frame, |b, frame| {
+            b.iter(|| stack.decode(black_box(frame)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, classical, bridge, surface_code, pipeline);
+criterion_main!(benches);
@@ -1,5 +1,6 @@
 use wasm_bindgen::prelude::*;
 use permaweb_lib::profile::{Profile, Zone, Wallet};
+use elxr::error::LayerStack;
 
 #[wasm_bindgen]
 pub struct KombuchaApi {
@@ -34,31 +35,19 @@ impl KombuchaApi {
         // Implementation for fermentation submission
         Ok(JsValue::from_str("Fermentation data submitted"))
     }
-}
 
-// Error correction implementations
-mod error_correction {
-    // Classical error correction
-    pub mod classical {
-        pub fn correct_errors(data: &[u8]) -> Vec<u8> {
-            // Reed-Solomon implementation
-            data.to_vec()
-        }
-    }
-    
-    // Bridge error correction
-    pub mod bridge {
-        pub fn correct_interface_errors(data: &[u8]) -> Vec<u8> {
-            // Bridge protocol implementation
-            data.to_vec()
-        }
+    /// Wraps a payload in the same error-correction layers the chain uses
+    #[wasm_bindgen]
+    pub fn protect_payload(&self, payload: &[u8]) -> Vec<u8> {
+        LayerStack::default().encode(payload)
     }
-    
-    // Quantum error correction
-    pub mod quantum {
-        pub fn correct_quantum_errors(data: &[u8]) -> Vec<u8> {
-            // Surface code implementation
-            data.to_vec()
-        }
+
+    /// Repairs and unwraps a frame produced by `protect_payload` or the chain
+    #[wasm_bindgen]
+    pub fn recover_payload(&self, frame: &[u8]) -> Result<Vec<u8>, JsValue> {
+        LayerStack::default()
+            .decode(frame)
+            .map(|outcome| outcome.payload)
+            .map_err(|failure| JsValue::from_str(&failure.to_string()))
     }
 }
@@ -0,0 +1,552 @@
+// Adaptive correction strength
+//
+// A `ChannelEstimator` keeps a moving average of the share of received symbols
+// a link needs corrected, and of how often its frames fail outright. A
+// `Ladder` lists the strengths a layer can run at, lightest first, with the
+// error rate each one can absorb. `ChannelQuality` moves a link along its
+// ladder under an `AdaptivePolicy`: one step up as soon as the error rate
+// takes a large share of the current level's capacity or a frame fails, one
+// step down only after a run of frames that the lighter level would have
+// handled easily.
+//
+// Every layer writes its parity or redundancy into its frame header, so the
+// level in use travels with each frame and a receiver decodes mixed-strength
+// traffic without being told about a switch.
+use codec::{Decode, Encode};
+use core::cell::Cell;
+use sp_std::collections::btree_map::BTreeMap;
+use sp_std::prelude::*;
+
+use super::bridge::Checksum;
+use super::{
+    BridgeErrorCorrection, ClassicalErrorCorrection, CorrectionConfig, CorrectionLayer,
+    ErrorCorrectionFailure, ErrorCorrector, LayerOutput,
+};
+
+/// Parts per million, the unit of every rate here.
+pub const PPM: u32 = 1_000_000;
+
+/// Parity shards used by `ParityLadder::default`, lightest first.
+pub const PARITY_LEVELS: [usize; 5] = [2, 4, 8, 12, 16];
+
+/// Copies per packet used by `RedundancyLadder::default`, lightest first.
+pub const REDUNDANCY_LEVELS: [usize; 4] = [1, 3, 5, 7];
+
+/// Each frame moves the averages 1/2^SMOOTHING_SHIFT of the way to its own rates.
+const SMOOTHING_SHIFT: u32 = 3;
+
+/// Moving averages of a link's symbol error and frame failure rates.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Encode, Decode)]
+pub struct ChannelEstimator {
+    error_rate: u32,
+    failure_rate: u32,
+    frames: u32,
+}
+
+impl ChannelEstimator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a frame of `symbols` symbols of which `corrected` needed
+    /// fixing, or that could not be decoded at all.
+    pub fn observe(&mut self, symbols: usize, corrected: usize, failed: bool) {
+        let first = self.frames == 0;
+        self.frames = self.frames.saturating_add(1);
+
+        let failure = if failed { PPM } else { 0 };
+        self.failure_rate = if first {
+            failure
+        } else {
+            smooth(self.failure_rate, failure)
+        };
+        // A failed frame says nothing reliable about how many symbols were bad
+        if !failed {
+            let errors = (corrected as u64 * PPM as u64 / symbols.max(1) as u64).min(PPM as u64) as u32;
+            self.error_rate = if first {
+                errors
+            } else {
+                smooth(self.error_rate, errors)
+            };
+        }
+    }
+
+    /// Symbols per million that needed correcting.
+    pub fn error_rate_ppm(&self) -> u32 {
+        self.error_rate
+    }
+
+    /// Frames per million that could not be decoded.
+    pub fn failure_rate_ppm(&self) -> u32 {
+        self.failure_rate
+    }
+
+    pub fn frames(&self) -> u32 {
+        self.frames
+    }
+}
+
+/// Moves `average` towards `sample`, rounding away from the average so that
+/// a steady input is reached exactly.
+fn smooth(average: u32, sample: u32) -> u32 {
+    let step = |gap: u32| gap.div_ceil(1 << SMOOTHING_SHIFT);
+    if sample >= average {
+        average + step(sample - average)
+    } else {
+        average - step(average - sample)
+    }
+}
+
+/// Strength levels of one layer, lightest first.
+pub trait Ladder {
+    type Corrector: ErrorCorrector;
+
+    /// Number of levels. Always at least one.
+    fn levels(&self) -> usize;
+
+    /// Symbol error rate, in parts per million, that `level` can correct.
+    fn capacity_ppm(&self, level: usize) -> u32;
+
+    /// A corrector that encodes at `level`.
+    fn corrector(&self, level: usize) -> Self::Corrector;
+}
+
+/// Reed-Solomon levels: a fixed number of data shards and a growing number
+/// of parity shards.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParityLadder {
+    data_shards: usize,
+    parity_levels: Vec<usize>,
+}
+
+impl ParityLadder {
+    /// Panics unless `parity_levels` is non-empty, strictly increasing, and
+    /// every level fits in a 255-symbol codeword.
+    pub fn new(data_shards: usize, parity_levels: &[usize]) -> Self {
+        assert!(!parity_levels.is_empty(), "a ladder needs at least one level");
+        assert!(
+            parity_levels.windows(2).all(|pair| pair[0] < pair[1]),
+            "parity levels must be strictly increasing"
+        );
+        assert!(
+            data_shards > 0 && data_shards + parity_levels[parity_levels.len() - 1] <= 255,
+            "Reed-Solomon over GF(256) supports at most 255 shards"
+        );
+        Self {
+            data_shards,
+            parity_levels: parity_levels.to_vec(),
+        }
+    }
+
+    pub fn parity_shards(&self, level: usize) -> usize {
+        self.parity_levels[level]
+    }
+}
+
+impl Default for ParityLadder {
+    fn default() -> Self {
+        Self::new(CorrectionConfig::default().data_shards as usize, &PARITY_LEVELS)
+    }
+}
+
+impl Ladder for ParityLadder {
+    type Corrector = ClassicalErrorCorrection;
+
+    fn levels(&self) -> usize {
+        self.parity_levels.len()
+    }
+
+    /// Each codeword corrects `parity / 2` of its symbols.
+    fn capacity_ppm(&self, level: usize) -> u32 {
+        let parity = self.parity_levels[level];
+        (PPM as usize * (parity / 2) / (self.data_shards + parity)) as u32
+    }
+
+    fn corrector(&self, level: usize) -> ClassicalErrorCorrection {
+        ClassicalErrorCorrection::new(self.data_shards, self.parity_levels[level])
+    }
+}
+
+/// Bridge levels: a growing number of copies per packet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RedundancyLadder {
+    redundancy_levels: Vec<usize>,
+    verification_layers: usize,
+    checksum: Checksum,
+}
+
+impl RedundancyLadder {
+    /// Panics unless `redundancy_levels` is non-empty, strictly increasing and within 1..=255.
+    pub fn new(redundancy_levels: &[usize], verification_layers: usize) -> Self {
+        assert!(!redundancy_levels.is_empty(), "a ladder needs at least one level");
+        assert!(
+            redundancy_levels.windows(2).all(|pair| pair[0] < pair[1]),
+            "redundancy levels must be strictly increasing"
+        );
+        assert!(
+            redundancy_levels[0] >= 1 && redundancy_levels[redundancy_levels.len() - 1] <= 255,
+            "redundancy factor must be between 1 and 255"
+        );
+        Self {
+            redundancy_levels: redundancy_levels.to_vec(),
+            verification_layers,
+            checksum: Checksum::Crc32,
+        }
+    }
+
+    pub fn with_checksum(mut self, checksum: Checksum) -> Self {
+        self.checksum = checksum;
+        self
+    }
+
+    pub fn redundancy_factor(&self, level: usize) -> usize {
+        self.redundancy_levels[level]
+    }
+}
+
+impl Default for RedundancyLadder {
+    fn default() -> Self {
+        Self::new(&REDUNDANCY_LEVELS, 1)
+    }
+}
+
+impl Ladder for RedundancyLadder {
+    type Corrector = BridgeErrorCorrection;
+
+    fn levels(&self) -> usize {
+        self.redundancy_levels.len()
+    }
+
+    /// The vote outweighs up to `(redundancy - 1) / 2` bad copies of each
+    /// byte; a single copy corrects nothing.
+    fn capacity_ppm(&self, level: usize) -> u32 {
+        let redundancy = self.redundancy_levels[level];
+        (PPM as usize * ((redundancy - 1) / 2) / redundancy) as u32
+    }
+
+    fn corrector(&self, level: usize) -> BridgeErrorCorrection {
+        BridgeErrorCorrection::new(self.redundancy_levels[level], self.verification_layers)
+            .with_checksum(self.checksum)
+    }
+}
+
+/// When to change level.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AdaptivePolicy {
+    /// Step up once the error rate passes this percentage of the current
+    /// level's capacity.
+    pub raise_at: u32,
+    /// Step down once the error rate stays under this percentage of the
+    /// lighter level's capacity...
+    pub lower_at: u32,
+    /// ...for this many frames in a row, none of them failed.
+    pub settle_frames: u32,
+}
+
+impl Default for AdaptivePolicy {
+    fn default() -> Self {
+        Self {
+            raise_at: 40,
+            lower_at: 20,
+            settle_frames: 32,
+        }
+    }
+}
+
+/// Where one link stands on its ladder.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChannelQuality {
+    estimator: ChannelEstimator,
+    level: usize,
+    calm_frames: u32,
+}
+
+impl ChannelQuality {
+    pub fn new(level: usize) -> Self {
+        Self {
+            level,
+            ..Self::default()
+        }
+    }
+
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    pub fn estimator(&self) -> &ChannelEstimator {
+        &self.estimator
+    }
+
+    /// Records a frame and moves to the level `policy` recommends. Returns
+    /// the new level when it changed.
+    pub fn observe<L: Ladder>(
+        &mut self,
+        ladder: &L,
+        policy: &AdaptivePolicy,
+        symbols: usize,
+        corrected: usize,
+        failed: bool,
+    ) -> Option<usize> {
+        self.estimator.observe(symbols, corrected, failed);
+        let error_rate = self.estimator.error_rate_ppm() as u64;
+        let share = |level: usize, percent: u32| ladder.capacity_ppm(level) as u64 * percent as u64 / 100;
+
+        let previous = self.level;
+        if failed || error_rate > share(self.level, policy.raise_at) {
+            self.calm_frames = 0;
+            self.level = (self.level + 1).min(ladder.levels() - 1);
+        } else if self.level > 0 && error_rate <= share(self.level - 1, policy.lower_at) {
+            self.calm_frames += 1;
+            if self.calm_frames >= policy.settle_frames {
+                self.calm_frames = 0;
+                self.level -= 1;
+            }
+        } else {
+            self.calm_frames = 0;
+        }
+
+        (self.level != previous).then_some(self.level)
+    }
+}
+
+/// Per-link quality for many devices or bridges sharing one ladder, for a
+/// receiver that recommends levels back to its senders.
+pub struct ChannelMonitor<K, L> {
+    ladder: L,
+    policy: AdaptivePolicy,
+    initial_level: usize,
+    channels: BTreeMap<K, ChannelQuality>,
+}
+
+impl<K: Ord, L: Ladder> ChannelMonitor<K, L> {
+    /// New links start at `initial_level`. Panics if it is not on the ladder.
+    pub fn new(ladder: L, initial_level: usize) -> Self {
+        assert!(
+            initial_level < ladder.levels(),
+            "initial level is not on the ladder"
+        );
+        Self {
+            ladder,
+            policy: AdaptivePolicy::default(),
+            initial_level,
+            channels: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_policy(mut self, policy: AdaptivePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Records the outcome of decoding a `frame_len`-byte frame from `link`.
+    /// Returns the link's new level when it changed.
+    pub fn observe(
+        &mut self,
+        link: K,
+        frame_len: usize,
+        outcome: &Result<LayerOutput, ErrorCorrectionFailure>,
+    ) -> Option<usize> {
+        let (corrected, failed) = match outcome {
+            Ok(output) => (output.report.symbols_corrected as usize, false),
+            Err(_) => (0, true),
+        };
+        let initial_level = self.initial_level;
+        self.channels
+            .entry(link)
+            .or_insert_with(|| ChannelQuality::new(initial_level))
+            .observe(&self.ladder, &self.policy, frame_len, corrected, failed)
+    }
+
+    /// Level recommended for `link`.
+    pub fn level(&self, link: &K) -> usize {
+        self.channels
+            .get(link)
+            .map_or(self.initial_level, ChannelQuality::level)
+    }
+
+    pub fn quality(&self, link: &K) -> Option<&ChannelQuality> {
+        self.channels.get(link)
+    }
+
+    /// A corrector at the level recommended for `link`.
+    pub fn corrector(&self, link: &K) -> L::Corrector {
+        self.ladder.corrector(self.level(link))
+    }
+}
+
+/// A layer that adapts its own strength to what it decodes: every frame it
+/// decodes is observed, and frames it encodes use the resulting level. Peers
+/// that learn a level out of band can apply it with `set_level`.
+pub struct AdaptiveCorrector<L: Ladder> {
+    ladder: L,
+    policy: AdaptivePolicy,
+    correctors: Vec<L::Corrector>,
+    quality: Cell<ChannelQuality>,
+}
+
+impl<L: Ladder> AdaptiveCorrector<L> {
+    /// Starts at `level`. Panics if it is not on the ladder.
+    pub fn new(ladder: L, level: usize) -> Self {
+        assert!(level < ladder.levels(), "initial level is not on the ladder");
+        let correctors = (0..ladder.levels())
+            .map(|level| ladder.corrector(level))
+            .collect();
+        Self {
+            ladder,
+            policy: AdaptivePolicy::default(),
+            correctors,
+            quality: Cell::new(ChannelQuality::new(level)),
+        }
+    }
+
+    pub fn with_policy(mut self, policy: AdaptivePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn level(&self) -> usize {
+        self.quality.get().level()
+    }
+
+    /// Moves to `level`, clamped to the ladder, keeping the channel estimate.
+    pub fn set_level(&self, level: usize) {
+        let mut quality = self.quality.get();
+        quality.level = level.min(self.ladder.levels() - 1);
+        quality.calm_frames = 0;
+        self.quality.set(quality);
+    }
+
+    pub fn quality(&self) -> ChannelQuality {
+        self.quality.get()
+    }
+}
+
+impl<L: Ladder> ErrorCorrector for AdaptiveCorrector<L> {
+    fn layer(&self) -> CorrectionLayer {
+        self.correctors[0].layer()
+    }
+
+    fn encode(&self, data: &[u8]) -> Vec<u8> {
+        self.correctors[self.level()].encode(data)
+    }
+
+    /// Decodes a frame of any level and adapts to how much it needed fixing.
+    fn decode(&self, frame: &[u8]) -> Result<LayerOutput, ErrorCorrectionFailure> {
+        let outcome = self.correctors[self.level()].decode(frame);
+        let (corrected, failed) = match &outcome {
+            Ok(output) => (output.report.symbols_corrected as usize, false),
+            Err(_) => (0, true),
+        };
+        let mut quality = self.quality.get();
+        quality.observe(&self.ladder, &self.policy, frame.len(), corrected, failed);
+        self.quality.set(quality);
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::HEADER_COPIES;
+
+    /// Flips every `stride`-th byte of the frame body.
+    fn corrupt(frame: &mut [u8], header_fields: usize, stride: usize) {
+        let body = header_fields * HEADER_COPIES;
+        for byte in frame[body..].iter_mut().step_by(stride) {
+            *byte ^= 0x55;
+        }
+    }
+
+    #[test]
+    fn estimator_tracks_steady_rates_exactly() {
+        let mut estimator = ChannelEstimator::new();
+        for _ in 0..100 {
+            estimator.observe(1000, 50, false);
+        }
+        assert_eq!(estimator.error_rate_ppm(), 50_000);
+        assert_eq!(estimator.failure_rate_ppm(), 0);
+
+        estimator.observe(1000, 0, true);
+        assert_eq!(estimator.error_rate_ppm(), 50_000);
+        assert_eq!(estimator.failure_rate_ppm(), PPM / 8);
+
+        for _ in 0..200 {
+            estimator.observe(1000, 0, false);
+        }
+        assert_eq!(estimator.error_rate_ppm(), 0);
+        assert_eq!(estimator.failure_rate_ppm(), 0);
+        assert_eq!(estimator.frames(), 301);
+    }
+
+    #[test]
+    fn noisy_links_step_up_and_clean_links_settle_back_down() {
+        let sender = AdaptiveCorrector::new(ParityLadder::default(), 1);
+        let receiver = AdaptiveCorrector::new(ParityLadder::default(), 1);
+        let data = vec![0x42u8; 96];
+
+        // One byte in twelve corrupted is beyond half of the 8+4 budget
+        for _ in 0..4 {
+            let mut frame = sender.encode(&data);
+            corrupt(&mut frame, 2, 12);
+            assert_eq!(receiver.decode(&frame).unwrap().data, data);
+        }
+        assert!(receiver.level() > 1);
+
+        // The receiver's recommendation reaches the sender, and frames of
+        // the old and new strength decode side by side
+        let old_frame = sender.encode(&data);
+        sender.set_level(receiver.level());
+        let new_frame = sender.encode(&data);
+        assert!(new_frame.len() > old_frame.len());
+        assert_eq!(receiver.decode(&old_frame).unwrap().data, data);
+        assert_eq!(receiver.decode(&new_frame).unwrap().data, data);
+
+        let noisy = receiver.level();
+        for _ in 0..AdaptivePolicy::default().settle_frames * 8 {
+            receiver.decode(&sender.encode(&data)).unwrap();
+        }
+        assert!(receiver.level() < noisy);
+        assert_eq!(receiver.level(), 0);
+    }
+
+    #[test]
+    fn failures_raise_redundancy_even_without_correction_capacity() {
+        let mut quality = ChannelQuality::new(0);
+        let ladder = RedundancyLadder::default();
+        assert_eq!(ladder.capacity_ppm(0), 0);
+
+        let policy = AdaptivePolicy::default();
+        assert_eq!(quality.observe(&ladder, &policy, 64, 0, false), None);
+        assert_eq!(quality.observe(&ladder, &policy, 64, 0, true), Some(1));
+        assert_eq!(ladder.corrector(quality.level()).redundancy_factor(), 3);
+    }
+
+    #[test]
+    fn monitor_keeps_links_apart() {
+        let mut monitor = ChannelMonitor::new(ParityLadder::default(), 1);
+        let codec = monitor.corrector(&"pool-a");
+        let data = vec![7u8; 64];
+        let clean = ErrorCorrector::encode(&codec, &data);
+        let mut noisy = clean.clone();
+        corrupt(&mut noisy, 2, 10);
+
+        for _ in 0..3 {
+            let outcome = codec.decode(&noisy);
+            monitor.observe("pool-a", noisy.len(), &outcome);
+            let outcome = codec.decode(&clean);
+            monitor.observe("pool-b", clean.len(), &outcome);
+        }
+
+        assert!(monitor.level(&"pool-a") > 1);
+        assert_eq!(monitor.level(&"pool-b"), 1);
+        assert_eq!(monitor.level(&"pool-c"), 1);
+        assert_eq!(
+            monitor.corrector(&"pool-a").parity_shards(),
+            ParityLadder::default().parity_shards(monitor.level(&"pool-a"))
+        );
+        assert_eq!(
+            monitor.quality(&"pool-b").unwrap().estimator().error_rate_ppm(),
+            0
+        );
+    }
+}
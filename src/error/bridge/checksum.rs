@@ -0,0 +1,92 @@
+// Packet checksums for the bridge framing protocol
+use blake2::digest::consts::U16;
+use blake2::{Blake2b, Digest};
+use sp_std::prelude::*;
+
+/// Checksum appended to every bridge packet, identified on the wire by its
+/// discriminant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Checksum {
+    /// CRC-32 (IEEE 802.3), 4 bytes. Catches transmission errors cheaply.
+    Crc32 = 0,
+    /// BLAKE2b truncated to 16 bytes, for links where a forged or
+    /// accidentally colliding CRC is a concern.
+    Blake2b128 = 1,
+}
+
+impl Checksum {
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Checksum::Crc32),
+            1 => Some(Checksum::Blake2b128),
+            _ => None,
+        }
+    }
+
+    pub fn id(self) -> u8 {
+        self as u8
+    }
+
+    /// Length of the tag in bytes.
+    pub fn tag_len(self) -> usize {
+        match self {
+            Checksum::Crc32 => 4,
+            Checksum::Blake2b128 => 16,
+        }
+    }
+
+    pub fn tag(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            Checksum::Crc32 => crc32(data).to_le_bytes().to_vec(),
+            Checksum::Blake2b128 => Blake2b::<U16>::digest(data).to_vec(),
+        }
+    }
+}
+
+const CRC32_TABLE: [u32; 256] = crc32_table();
+
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+pub fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &byte| {
+        CRC32_TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32_matches_reference_value() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn ids_roundtrip() {
+        for checksum in [Checksum::Crc32, Checksum::Blake2b128] {
+            assert_eq!(Checksum::from_id(checksum.id()), Some(checksum));
+            assert_eq!(checksum.tag(b"frame").len(), checksum.tag_len());
+        }
+        assert_eq!(Checksum::from_id(7), None);
+    }
+}
@@ -0,0 +1,210 @@
+// Receiving side of the bridge framing protocol
+//
+// Tracks the next expected sequence number, delivers recovered packets once,
+// and answers every gap or unrecoverable frame with `Nack`s for the sender.
+use sp_std::collections::vec_deque::VecDeque;
+use sp_std::prelude::*;
+
+use super::{BridgeErrorCorrection, Nack};
+use crate::error::FailureReason;
+
+/// Most sequence numbers NACKed for a single gap, and most kept outstanding.
+pub const MAX_OUTSTANDING_NACKS: usize = 64;
+
+/// What became of one frame handed to a `BridgeReceiver`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Delivery {
+    /// A new packet, or the retransmission of one that was NACKed
+    Payload {
+        sequence: u32,
+        data: Vec<u8>,
+        corrected: usize,
+    },
+    /// A packet that was already delivered
+    Duplicate { sequence: u32 },
+    /// The frame could not be recovered and has been NACKed
+    Lost,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Receipt {
+    pub delivery: Delivery,
+    /// Retransmit requests to send back to the sender
+    pub nacks: Vec<Nack>,
+}
+
+pub struct BridgeReceiver {
+    bridge: BridgeErrorCorrection,
+    expected: u32,
+    /// NACKed sequence numbers still awaiting retransmission, oldest first
+    missing: VecDeque<u32>,
+}
+
+impl BridgeReceiver {
+    /// Receives frames produced by `encode` on a bridge whose first packet is `first_sequence`.
+    pub fn new(bridge: BridgeErrorCorrection, first_sequence: u32) -> Self {
+        Self {
+            bridge,
+            expected: first_sequence,
+            missing: VecDeque::new(),
+        }
+    }
+
+    /// Sequence number of the next new packet.
+    pub fn expected(&self) -> u32 {
+        self.expected
+    }
+
+    /// Sequence numbers NACKed and not yet retransmitted.
+    pub fn missing(&self) -> impl Iterator<Item = u32> + '_ {
+        self.missing.iter().copied()
+    }
+
+    pub fn receive(&mut self, frame: &[u8]) -> Receipt {
+        let (bridge, copies) = match self.bridge.split_frame(frame) {
+            Ok(split) => split,
+            Err(failure) => return self.lost(None, failure.reason),
+        };
+        let recovered = match bridge.as_ref().unwrap_or(&self.bridge).recover(&copies) {
+            Ok(recovered) => recovered,
+            Err(nack) => return self.lost(Some(nack.sequence), nack.reason),
+        };
+
+        let sequence = recovered.sequence;
+        let ahead = sequence.wrapping_sub(self.expected);
+        let mut nacks = Vec::new();
+        let retransmitted = self.missing.iter().position(|&missing| missing == sequence);
+        if let Some(position) = retransmitted {
+            self.missing.remove(position);
+        } else if ahead > u32::MAX / 2 {
+            return Receipt {
+                delivery: Delivery::Duplicate { sequence },
+                nacks,
+            };
+        }
+
+        if ahead <= u32::MAX / 2 {
+            // Everything between the expected packet and this one was skipped
+            let first_skipped = self.expected;
+            for skipped in (0..ahead.min(MAX_OUTSTANDING_NACKS as u32)).map(|i| first_skipped.wrapping_add(i))
+            {
+                if !self.missing.contains(&skipped) {
+                    self.mark_missing(skipped);
+                    nacks.push(Nack {
+                        sequence: skipped,
+                        reason: FailureReason::Missing,
+                    });
+                }
+            }
+            self.expected = sequence.wrapping_add(1);
+        }
+
+        Receipt {
+            delivery: Delivery::Payload {
+                sequence,
+                data: recovered.payload,
+                corrected: recovered.corrected,
+            },
+            nacks,
+        }
+    }
+
+    /// NACKs an unrecoverable frame under its voted sequence number when
+    /// that is plausible, and otherwise under the next expected one.
+    fn lost(&mut self, guess: Option<u32>, reason: FailureReason) -> Receipt {
+        let plausible = |guess: &u32| {
+            self.missing.contains(guess)
+                || (guess.wrapping_sub(self.expected) as usize) < MAX_OUTSTANDING_NACKS
+        };
+        let sequence = guess.filter(plausible).unwrap_or(self.expected);
+        if !self.missing.contains(&sequence) {
+            self.mark_missing(sequence);
+        }
+
+        Receipt {
+            delivery: Delivery::Lost,
+            nacks: vec![Nack { sequence, reason }],
+        }
+    }
+
+    fn mark_missing(&mut self, sequence: u32) {
+        if self.missing.len() == MAX_OUTSTANDING_NACKS {
+            self.missing.pop_front();
+        }
+        self.missing.push_back(sequence);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorCorrector;
+
+    fn corrupt_every_copy(frame: &mut [u8]) {
+        // 3 header copies of 3 fields, then three 13-byte packets for a 1-byte payload
+        for copy in 0..3 {
+            frame[9 + copy * 13 + 8] = copy as u8 + 0x10;
+        }
+    }
+
+    #[test]
+    fn delivers_in_order_and_drops_duplicates() {
+        let sender = BridgeErrorCorrection::new(3, 1);
+        let mut receiver = BridgeReceiver::new(BridgeErrorCorrection::new(3, 1), 0);
+
+        let first = sender.encode(b"a");
+        let receipt = receiver.receive(&first);
+        assert!(matches!(receipt.delivery, Delivery::Payload { sequence: 0, .. }));
+        assert!(receipt.nacks.is_empty());
+
+        assert_eq!(
+            receiver.receive(&first).delivery,
+            Delivery::Duplicate { sequence: 0 }
+        );
+        assert_eq!(receiver.expected(), 1);
+    }
+
+    #[test]
+    fn gaps_and_damaged_frames_are_nacked_until_retransmitted() {
+        let sender = BridgeErrorCorrection::new(3, 1);
+        let mut receiver = BridgeReceiver::new(BridgeErrorCorrection::new(3, 1), 0);
+        let frames: Vec<Vec<u8>> = [b"a", b"b", b"c"]
+            .iter()
+            .map(|data| sender.encode(*data))
+            .collect();
+
+        // Frame 0 arrives damaged beyond repair, frame 1 never arrives
+        let mut damaged = frames[0].clone();
+        corrupt_every_copy(&mut damaged);
+        let receipt = receiver.receive(&damaged);
+        assert_eq!(receipt.delivery, Delivery::Lost);
+        assert_eq!(
+            receipt.nacks,
+            vec![Nack {
+                sequence: 0,
+                reason: FailureReason::NoConsensus
+            }]
+        );
+
+        let receipt = receiver.receive(&frames[2]);
+        assert!(matches!(receipt.delivery, Delivery::Payload { sequence: 2, .. }));
+        assert_eq!(
+            receipt.nacks,
+            vec![Nack {
+                sequence: 1,
+                reason: FailureReason::Missing
+            }]
+        );
+        assert_eq!(receiver.missing().collect::<Vec<_>>(), vec![0, 1]);
+
+        // Retransmissions are delivered even though they are behind
+        for frame in &frames[..2] {
+            assert!(matches!(
+                receiver.receive(frame).delivery,
+                Delivery::Payload { .. }
+            ));
+        }
+        assert_eq!(receiver.missing().count(), 0);
+        assert_eq!(receiver.expected(), 3);
+    }
+}
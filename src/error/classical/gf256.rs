@@ -0,0 +1,257 @@
+// GF(2^8) arithmetic for the Reed-Solomon codec
+// Field generated by the primitive polynomial x^8 + x^4 + x^3 + x^2 + 1 (0x11d)
+// with generator alpha = 2.
+
+const PRIMITIVE_POLY: u16 = 0x11d;
+
+/// alpha^i for i in 0..512, doubled so products of two logs never need a modulo.
+pub const EXP: [u8; 512] = build_exp();
+
+/// log_alpha(x) for x in 1..256. `LOG[0]` is unused.
+pub const LOG: [u8; 256] = build_log();
+
+const fn build_exp() -> [u8; 512] {
+    let mut table = [0u8; 512];
+    let mut x: u16 = 1;
+    let mut i = 0;
+    while i < 255 {
+        table[i] = x as u8;
+        table[i + 255] = x as u8;
+        x <<= 1;
+        if x & 0x100 != 0 {
+            x ^= PRIMITIVE_POLY;
+        }
+        i += 1;
+    }
+    table[510] = table[0];
+    table[511] = table[1];
+    table
+}
+
+/// Products of every field element with the low and high nibbles of a byte:
+/// `c * x == MUL_LOW[c][x & 15] ^ MUL_HIGH[c][x >> 4]`, since multiplication
+/// by `c` is linear over GF(2). Sixteen-entry rows fit a `pshufb` lookup.
+#[cfg(feature = "alloc")]
+pub const MUL_LOW: [[u8; 16]; 256] = build_nibble_table(0);
+#[cfg(feature = "alloc")]
+pub const MUL_HIGH: [[u8; 16]; 256] = build_nibble_table(4);
+
+const fn build_log() -> [u8; 256] {
+    let exp = build_exp();
+    let mut table = [0u8; 256];
+    let mut i = 0;
+    while i < 255 {
+        table[exp[i] as usize] = i as u8;
+        i += 1;
+    }
+    table
+}
+
+#[cfg(feature = "alloc")]
+const fn build_nibble_table(shift: u32) -> [[u8; 16]; 256] {
+    let exp = build_exp();
+    let log = build_log();
+    let mut table = [[0u8; 16]; 256];
+    let mut c = 1;
+    while c < 256 {
+        let mut nibble = 1;
+        while nibble < 16 {
+            let x = nibble << shift;
+            table[c][nibble] = exp[log[c] as usize + log[x] as usize];
+            nibble += 1;
+        }
+        c += 1;
+    }
+    table
+}
+
+#[inline]
+pub fn add(a: u8, b: u8) -> u8 {
+    a ^ b
+}
+
+#[inline]
+pub fn mul(a: u8, b: u8) -> u8 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    EXP[LOG[a as usize] as usize + LOG[b as usize] as usize]
+}
+
+/// Divides `a` by `b`. Panics if `b` is zero.
+#[inline]
+pub fn div(a: u8, b: u8) -> u8 {
+    assert!(b != 0, "division by zero in GF(256)");
+    if a == 0 {
+        return 0;
+    }
+    EXP[LOG[a as usize] as usize + 255 - LOG[b as usize] as usize]
+}
+
+/// Multiplicative inverse of `a`. Panics if `a` is zero.
+#[inline]
+pub fn inv(a: u8) -> u8 {
+    div(1, a)
+}
+
+/// alpha^power, for any non-negative power.
+#[inline]
+pub fn pow_alpha(power: usize) -> u8 {
+    EXP[power % 255]
+}
+
+// Slice kernels. These carry the bulk of encoding and syndrome computation,
+// where one coefficient multiplies a whole shard, so they come with the
+// shard API and its `alloc` feature.
+
+/// `dst[i] ^= coef * src[i]` for every byte. Uses SSSE3 shuffles when the CPU
+/// has them and the nibble tables otherwise. Panics if the lengths differ.
+#[cfg(feature = "alloc")]
+pub fn mul_slice_xor(coef: u8, src: &[u8], dst: &mut [u8]) {
+    assert_eq!(src.len(), dst.len(), "slices must have equal length");
+    match coef {
+        0 => {}
+        1 => dst.iter_mut().zip(src).for_each(|(d, &s)| *d ^= s),
+        _ => {
+            #[cfg(all(feature = "std", target_arch = "x86_64"))]
+            {
+                if std::arch::is_x86_feature_detected!("ssse3") {
+                    let done = src.len() - src.len() % 16;
+                    // SAFETY: SSSE3 support was just checked
+                    unsafe { simd::mul_slice_xor(coef, &src[..done], &mut dst[..done]) };
+                    mul_slice_xor_tables(coef, &src[done..], &mut dst[done..]);
+                    return;
+                }
+            }
+            mul_slice_xor_tables(coef, src, dst);
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+fn mul_slice_xor_tables(coef: u8, src: &[u8], dst: &mut [u8]) {
+    let low = &MUL_LOW[coef as usize];
+    let high = &MUL_HIGH[coef as usize];
+    for (d, &s) in dst.iter_mut().zip(src) {
+        *d ^= low[(s & 0x0f) as usize] ^ high[(s >> 4) as usize];
+    }
+}
+
+#[cfg(all(feature = "std", target_arch = "x86_64"))]
+mod simd {
+    use std::arch::x86_64::*;
+
+    /// Sixteen bytes at a time: each nibble indexes its product table with
+    /// `pshufb`. `src.len()` must be a multiple of 16.
+    #[target_feature(enable = "ssse3")]
+    pub unsafe fn mul_slice_xor(coef: u8, src: &[u8], dst: &mut [u8]) {
+        let low = _mm_loadu_si128(super::MUL_LOW[coef as usize].as_ptr() as *const __m128i);
+        let high = _mm_loadu_si128(super::MUL_HIGH[coef as usize].as_ptr() as *const __m128i);
+        let mask = _mm_set1_epi8(0x0f);
+        for (s, d) in src.chunks_exact(16).zip(dst.chunks_exact_mut(16)) {
+            let x = _mm_loadu_si128(s.as_ptr() as *const __m128i);
+            let lo = _mm_shuffle_epi8(low, _mm_and_si128(x, mask));
+            let hi = _mm_shuffle_epi8(high, _mm_and_si128(_mm_srli_epi64(x, 4), mask));
+            let acc = _mm_loadu_si128(d.as_ptr() as *const __m128i);
+            let out = _mm_xor_si128(acc, _mm_xor_si128(lo, hi));
+            _mm_storeu_si128(d.as_mut_ptr() as *mut __m128i, out);
+        }
+    }
+}
+
+// Polynomial helpers. Polynomials are stored lowest degree first, so
+// `p[i]` is the coefficient of x^i. They live in fixed-capacity `Poly`
+// buffers: a codeword has at most 255 symbols, so no locator, syndrome or
+// evaluator needs more than 256 coefficients and decoding never allocates.
+
+/// Most coefficients any codec polynomial holds.
+pub const MAX_POLY_LEN: usize = 256;
+
+pub type Poly = heapless::Vec<u8, MAX_POLY_LEN>;
+
+/// Copies `p` into a `Poly`. Panics if it has more than `MAX_POLY_LEN` coefficients.
+pub fn poly(p: &[u8]) -> Poly {
+    Poly::from_slice(p).expect("polynomial exceeds MAX_POLY_LEN coefficients")
+}
+
+/// Evaluates `p` at `x` using Horner's rule.
+pub fn poly_eval(p: &[u8], x: u8) -> u8 {
+    let mut y = 0u8;
+    for &coef in p.iter().rev() {
+        y = mul(y, x) ^ coef;
+    }
+    y
+}
+
+/// `p * q`, keeping only the coefficients of x^0 to x^(max_len - 1).
+pub fn poly_mul(p: &[u8], q: &[u8], max_len: usize) -> Poly {
+    let mut out = Poly::new();
+    if p.is_empty() || q.is_empty() {
+        return out;
+    }
+    let len = (p.len() + q.len() - 1).min(max_len).min(MAX_POLY_LEN);
+    out.resize(len, 0).expect("length capped at capacity");
+    for (i, &a) in p.iter().enumerate().take(len) {
+        if a == 0 {
+            continue;
+        }
+        for (j, &b) in q.iter().enumerate().take(len - i) {
+            out[i + j] ^= mul(a, b);
+        }
+    }
+    out
+}
+
+pub fn poly_scale(p: &[u8], factor: u8) -> Poly {
+    p.iter().map(|&c| mul(c, factor)).collect()
+}
+
+pub fn poly_add(p: &[u8], q: &[u8]) -> Poly {
+    let mut out = poly(if p.len() >= q.len() { p } else { q });
+    let shorter = if p.len() >= q.len() { q } else { p };
+    for (i, &c) in shorter.iter().enumerate() {
+        out[i] = add(out[i], c);
+    }
+    out
+}
+
+/// Formal derivative. In characteristic 2 the even-power terms vanish.
+pub fn poly_derivative(p: &[u8]) -> Poly {
+    p.iter()
+        .enumerate()
+        .skip(1)
+        .map(|(i, &c)| if i % 2 == 1 { c } else { 0 })
+        .collect()
+}
+
+/// Degree of `p` ignoring trailing zero coefficients, or `None` for the zero polynomial.
+pub fn poly_degree(p: &[u8]) -> Option<usize> {
+    p.iter().rposition(|&c| c != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nibble_tables_agree_with_log_tables() {
+        for c in 0..=255u8 {
+            for x in 0..=255u8 {
+                let product = MUL_LOW[c as usize][(x & 0x0f) as usize] ^ MUL_HIGH[c as usize][(x >> 4) as usize];
+                assert_eq!(product, mul(c, x));
+            }
+        }
+    }
+
+    #[test]
+    fn slice_kernel_matches_scalar_multiplication() {
+        // 37 bytes exercises both the vector body and the scalar tail
+        let src: Vec<u8> = (0..37u8).map(|i| i.wrapping_mul(73) ^ 0x5a).collect();
+        for coef in [0u8, 1, 2, 0x1d, 0xff] {
+            let mut dst: Vec<u8> = (0..37u8).collect();
+            let expected: Vec<u8> = dst.iter().zip(&src).map(|(&d, &s)| d ^ mul(coef, s)).collect();
+            mul_slice_xor(coef, &src, &mut dst);
+            assert_eq!(dst, expected);
+        }
+    }
+}
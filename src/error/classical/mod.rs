@@ -1,21 +1,1053 @@
 // Classical Error Correction using Reed-Solomon codes
-use reed_solomon_erasure::ReedSolomon;
+//
+// Systematic RS(n, k) over GF(256). A byte stream is split into `data_shards`
+// equally sized shards and `parity_shards` parity shards are appended; byte `j`
+// of every shard forms one codeword, so any `parity_shards` missing shards can
+// be rebuilt from the rest. When the damaged positions are unknown, each
+// codeword can locate and fix up to `parity_shards / 2` corrupted symbols.
+// Serial links use `encode_interleaved` instead, which sends whole codewords
+// through a `BlockInterleaver` so that bursts are spread across them.
+//
+// The codec itself never allocates: polynomials live in fixed-capacity
+// buffers, so the `_heapless` variants of the interleaved framing run on
+// devices without a heap, and the pallet decodes their frames with the
+// same code. The shard API needs the `alloc` feature.
+use core::fmt;
+#[cfg(feature = "alloc")]
+use sp_std::prelude::*;
+
+use super::interleaver::BlockInterleaver;
+#[cfg(feature = "alloc")]
+use super::{
+    read_header, write_header, CorrectionLayer, ErrorCorrectionFailure, ErrorCorrector, FailureReason,
+    LayerOutput, LayerReport,
+};
+
+mod gf256;
+
+use gf256::Poly;
+
+/// Codeword indices, at most one per symbol of a 255-symbol codeword.
+type Positions = heapless::Vec<usize, 255>;
+
+/// Bytes used by the length header prepended by `encode_bytes`.
+pub const LENGTH_HEADER_LEN: usize = 4;
+
+/// Layout of the telemetry firmware's serial frames: RS(24, 16) codewords
+/// interleaved 8 deep, which corrects a 32-byte burst in every block.
+pub const SERIAL_DATA_SHARDS: usize = 16;
+pub const SERIAL_PARITY_SHARDS: usize = 8;
+pub const SERIAL_INTERLEAVE_DEPTH: usize = 8;
+
+/// Failures reported by the classical codec.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClassicalError {
+    /// The shard slice does not match the codec layout.
+    ShardCountMismatch { expected: usize, actual: usize },
+    /// Present shards differ in length.
+    ShardLengthMismatch { expected: usize, actual: usize },
+    /// Every shard is missing.
+    NoShardsPresent,
+    /// More shards are missing than there are parity shards.
+    TooManyErasures { missing: usize, max: usize },
+    /// A codeword carries more damage than the parity can repair. `column`
+    /// is its index among the shard columns or stream codewords, and `None`
+    /// for a lone codeword.
+    Uncorrectable { column: Option<usize> },
+    /// The decoded length header points past the end of the data shards.
+    InvalidLengthHeader { declared: usize, available: usize },
+    /// A codeword stream is not a whole number of codewords.
+    TruncatedStream { codeword_len: usize, actual: usize },
+    /// A fixed-capacity buffer is too small for the output.
+    CapacityExceeded { needed: usize, capacity: usize },
+}
+
+impl fmt::Display for ClassicalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClassicalError::ShardCountMismatch { expected, actual } => {
+                write!(f, "expected {} shards, got {}", expected, actual)
+            }
+            ClassicalError::ShardLengthMismatch { expected, actual } => {
+                write!(f, "shard length mismatch: expected {}, got {}", expected, actual)
+            }
+            ClassicalError::NoShardsPresent => write!(f, "no shards present"),
+            ClassicalError::TooManyErasures { missing, max } => write!(
+                f,
+                "{} shards missing, at most {} can be recovered",
+                missing, max
+            ),
+            ClassicalError::Uncorrectable { column: Some(column) } => {
+                write!(f, "codeword {} has more errors than the parity can correct", column)
+            }
+            ClassicalError::Uncorrectable { column: None } => {
+                write!(f, "codeword has more errors than the parity can correct")
+            }
+            ClassicalError::InvalidLengthHeader { declared, available } => write!(
+                f,
+                "length header {} exceeds the {} payload bytes available",
+                declared, available
+            ),
+            ClassicalError::TruncatedStream { codeword_len, actual } => write!(
+                f,
+                "stream of {} bytes is not a whole number of {}-byte codewords",
+                actual, codeword_len
+            ),
+            ClassicalError::CapacityExceeded { needed, capacity } => write!(
+                f,
+                "output needs {} bytes but the buffer holds {}",
+                needed, capacity
+            ),
+        }
+    }
+}
+
+impl ClassicalError {
+    /// How the failure is reported when the codec runs as a correction layer.
+    #[cfg(feature = "alloc")]
+    pub fn reason(&self) -> FailureReason {
+        match self {
+            ClassicalError::InvalidLengthHeader { .. }
+            | ClassicalError::TruncatedStream { .. }
+            | ClassicalError::CapacityExceeded { .. } => FailureReason::LengthMismatch,
+            ClassicalError::ShardCountMismatch { .. } | ClassicalError::ShardLengthMismatch { .. } => {
+                FailureReason::MalformedHeader
+            }
+            ClassicalError::NoShardsPresent
+            | ClassicalError::TooManyErasures { .. }
+            | ClassicalError::Uncorrectable { .. } => FailureReason::Uncorrectable,
+        }
+    }
+}
+
+/// What an error-locating decode had to repair.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CorrectionReport {
+    /// Symbols found corrupted and fixed without being flagged.
+    pub symbols_corrected: usize,
+    /// Symbols rebuilt because their shard was missing.
+    pub erasures_filled: usize,
+    /// Codewords (shard columns) that needed any repair.
+    pub codewords_repaired: usize,
+}
 
 pub struct ClassicalErrorCorrection {
-    rs: ReedSolomon,
+    data_shards: usize,
+    parity_shards: usize,
+    /// Monic generator polynomial prod(x - alpha^i), highest degree first.
+    generator: Poly,
 }
 
 impl ClassicalErrorCorrection {
+    /// Creates a codec with the given shard layout.
+    ///
+    /// Panics if `data_shards` is zero or the total number of shards exceeds
+    /// 255, the codeword length limit for GF(256).
     pub fn new(data_shards: usize, parity_shards: usize) -> Self {
-        let rs = ReedSolomon::new(data_shards, parity_shards).unwrap();
-        Self { rs }
+        assert!(data_shards > 0, "Reed-Solomon needs at least one data shard");
+        assert!(
+            data_shards + parity_shards <= 255,
+            "Reed-Solomon over GF(256) supports at most 255 shards"
+        );
+
+        let mut generator = gf256::poly(&[1]);
+        for i in 0..parity_shards {
+            generator = gf256::poly_mul(&generator, &[gf256::pow_alpha(i), 1], gf256::MAX_POLY_LEN);
+        }
+        generator.reverse();
+
+        Self {
+            data_shards,
+            parity_shards,
+            generator,
+        }
+    }
+
+    pub fn data_shards(&self) -> usize {
+        self.data_shards
+    }
+
+    pub fn parity_shards(&self) -> usize {
+        self.parity_shards
+    }
+
+    pub fn total_shards(&self) -> usize {
+        self.data_shards + self.parity_shards
+    }
+
+    /// Fills the parity shards from the data shards. `data` must hold
+    /// `total_shards()` shards of equal length.
+    #[cfg(feature = "alloc")]
+    pub fn encode(&self, data: &mut [Vec<u8>]) -> Result<(), ClassicalError> {
+        self.check_shards(data.iter().map(|s| Some(s.len())))?;
+
+        let (data_rows, parity_rows) = data.split_at_mut(self.data_shards);
+        for row in parity_rows.iter_mut() {
+            row.fill(0);
+        }
+        // Encoding is linear, so each data shard adds a multiple of itself to
+        // every parity shard. The multipliers are the parity of a message with
+        // a single one in that shard's position, x^(data_shards - 1 - i) times
+        // x^parity_shards mod the generator, so walking the shards backwards
+        // each set is one encoder step on from the last.
+        let mut coefficients = gf256::poly(&self.generator[1..]);
+        for data_row in data_rows.iter().rev() {
+            for (parity_row, &coef) in parity_rows.iter_mut().zip(coefficients.iter()) {
+                gf256::mul_slice_xor(coef, data_row, parity_row);
+            }
+            self.shift_parity(&mut coefficients, 0);
+        }
+
+        Ok(())
+    }
+
+    /// Rebuilds every `None` shard in place, as long as no more than
+    /// `parity_shards` shards are missing. Present shards are trusted.
+    #[cfg(feature = "alloc")]
+    pub fn reconstruct(&self, data: &mut [Option<Vec<u8>>]) -> Result<(), ClassicalError> {
+        self.repair(data, false).map(|_| ())
+    }
+
+    /// Rebuilds missing shards and locates and fixes corrupted symbols in the
+    /// present ones. Each codeword survives `2 * errors + erasures <= parity_shards`.
+    #[cfg(feature = "alloc")]
+    pub fn correct(&self, data: &mut [Option<Vec<u8>>]) -> Result<CorrectionReport, ClassicalError> {
+        self.repair(data, true)
+    }
+
+    /// Encodes one `data_shards`-long message into a `total_shards()`-long codeword.
+    #[cfg(feature = "alloc")]
+    pub fn encode_codeword(&self, message: &[u8]) -> Vec<u8> {
+        assert_eq!(message.len(), self.data_shards, "message length must equal data_shards");
+        let mut codeword = message.to_vec();
+        codeword.extend_from_slice(&self.parity_for(message));
+        codeword
+    }
+
+    /// Corrects one codeword in place, given the indices already known to be
+    /// bad. Returns the number of symbols fixed outside of `erasures`.
+    pub fn correct_codeword(&self, codeword: &mut [u8], erasures: &[usize]) -> Result<usize, ClassicalError> {
+        if codeword.len() != self.total_shards() {
+            return Err(ClassicalError::ShardCountMismatch {
+                expected: self.total_shards(),
+                actual: codeword.len(),
+            });
+        }
+        if erasures.len() > self.parity_shards {
+            return Err(ClassicalError::TooManyErasures {
+                missing: erasures.len(),
+                max: self.parity_shards,
+            });
+        }
+        self.correct_errata(codeword, erasures, true)
+            .map_err(|()| ClassicalError::Uncorrectable { column: None })
+    }
+
+    /// Splits `data` into `total_shards()` shards: a little-endian `u32`
+    /// length header plus the payload, zero-padded across the data shards,
+    /// followed by the parity shards.
+    #[cfg(feature = "alloc")]
+    pub fn encode_bytes(&self, data: &[u8]) -> Vec<Vec<u8>> {
+        assert!(
+            data.len() <= u32::MAX as usize,
+            "payload too large for the length header"
+        );
+
+        let framed_len = LENGTH_HEADER_LEN + data.len();
+        let shard_len = framed_len.div_ceil(self.data_shards);
+
+        let mut framed = Vec::with_capacity(shard_len * self.data_shards);
+        framed.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        framed.extend_from_slice(data);
+        framed.resize(shard_len * self.data_shards, 0);
+
+        let mut shards: Vec<Vec<u8>> = framed.chunks(shard_len).map(<[u8]>::to_vec).collect();
+        shards.resize(self.total_shards(), vec![0u8; shard_len]);
+
+        self.encode(&mut shards)
+            .expect("shards built by encode_bytes always have a valid layout");
+        shards
+    }
+
+    /// Reverses `encode_bytes`, rebuilding missing shards first.
+    #[cfg(feature = "alloc")]
+    pub fn decode_bytes(&self, shards: &mut [Option<Vec<u8>>]) -> Result<Vec<u8>, ClassicalError> {
+        self.reconstruct(shards)?;
+        self.unframe(shards)
+    }
+
+    /// Reverses `encode_bytes`, rebuilding missing shards and fixing
+    /// corrupted symbols in the present ones.
+    #[cfg(feature = "alloc")]
+    pub fn decode_bytes_correcting(
+        &self,
+        shards: &mut [Option<Vec<u8>>],
+    ) -> Result<(Vec<u8>, CorrectionReport), ClassicalError> {
+        let report = self.correct(shards)?;
+        Ok((self.unframe(shards)?, report))
+    }
+
+    /// Encodes `data` as whole codewords sent one after another, each
+    /// `total_shards()` bytes, and interleaves them `depth` deep. The messages
+    /// carry the same length header and zero padding as `encode_bytes`.
+    ///
+    /// A burst of up to `depth * parity_shards / 2` corrupted bytes, or
+    /// `depth * parity_shards` bytes known to be lost, is recoverable as long
+    /// as it falls within whole interleaver blocks.
+    #[cfg(feature = "alloc")]
+    pub fn encode_interleaved(&self, data: &[u8], depth: usize) -> Vec<u8> {
+        let mut stream = vec![0u8; self.interleaved_len(data.len())];
+        self.write_interleaved(data, depth, &mut stream);
+        stream
+    }
+
+    /// `encode_interleaved` into a fixed-capacity buffer, without allocating.
+    pub fn encode_interleaved_heapless<const N: usize>(
+        &self,
+        data: &[u8],
+        depth: usize,
+    ) -> Result<heapless::Vec<u8, N>, ClassicalError> {
+        let needed = self.interleaved_len(data.len());
+        let mut stream = heapless::Vec::new();
+        stream
+            .resize(needed, 0)
+            .map_err(|()| ClassicalError::CapacityExceeded { needed, capacity: N })?;
+        self.write_interleaved(data, depth, &mut stream);
+        Ok(stream)
+    }
+
+    /// Length of the `encode_interleaved` stream for a `payload_len`-byte payload.
+    pub fn interleaved_len(&self, payload_len: usize) -> usize {
+        (LENGTH_HEADER_LEN + payload_len).div_ceil(self.data_shards) * self.total_shards()
+    }
+
+    /// Reverses `encode_interleaved`. `lost` lists stream positions known to
+    /// be bad, such as bytes flagged by the UART; they are decoded as erasures.
+    #[cfg(feature = "alloc")]
+    pub fn decode_interleaved(
+        &self,
+        stream: &[u8],
+        depth: usize,
+        lost: &[usize],
+    ) -> Result<(Vec<u8>, CorrectionReport), ClassicalError> {
+        let mut framed = Vec::with_capacity(stream.len() / self.total_shards() * self.data_shards);
+        let report = self.read_interleaved(stream, depth, lost, |data| {
+            framed.extend_from_slice(data);
+            Ok(())
+        })?;
+        Ok((strip_length_header(&framed)?.to_vec(), report))
+    }
+
+    /// `decode_interleaved` into a fixed-capacity buffer, without allocating.
+    pub fn decode_interleaved_heapless<const N: usize>(
+        &self,
+        stream: &[u8],
+        depth: usize,
+        lost: &[usize],
+    ) -> Result<(heapless::Vec<u8, N>, CorrectionReport), ClassicalError> {
+        let mut header = [0u8; LENGTH_HEADER_LEN];
+        let mut received = 0usize;
+        let mut payload = heapless::Vec::new();
+        let report = self.read_interleaved(stream, depth, lost, |data| {
+            for &byte in data {
+                match received.checked_sub(LENGTH_HEADER_LEN) {
+                    None => header[received] = byte,
+                    Some(offset) if offset < (u32::from_le_bytes(header) as usize).min(N) => {
+                        payload.push(byte).expect("bounded by the capacity");
+                    }
+                    Some(_) => {}
+                }
+                received += 1;
+            }
+            Ok(())
+        })?;
+
+        let declared = u32::from_le_bytes(header) as usize;
+        let available = received.saturating_sub(LENGTH_HEADER_LEN);
+        if received < LENGTH_HEADER_LEN || declared > available {
+            return Err(ClassicalError::InvalidLengthHeader {
+                declared: if received < LENGTH_HEADER_LEN { 0 } else { declared },
+                available,
+            });
+        }
+        if declared > N {
+            return Err(ClassicalError::CapacityExceeded {
+                needed: declared,
+                capacity: N,
+            });
+        }
+        Ok((payload, report))
+    }
+
+    /// Encodes the framed `data` one codeword at a time, writing each symbol
+    /// straight to its interleaved position in `stream`.
+    fn write_interleaved(&self, data: &[u8], depth: usize, stream: &mut [u8]) {
+        assert!(
+            data.len() <= u32::MAX as usize,
+            "payload too large for the length header"
+        );
+
+        let n = self.total_shards();
+        let interleaver = BlockInterleaver::new(depth, n);
+        let header = (data.len() as u32).to_le_bytes();
+        let mut framed = header.iter().chain(data).copied();
+        let mut buffer = [0u8; 255];
+        let codeword = &mut buffer[..n];
+        for index in 0..stream.len() / n {
+            let (message, parity) = codeword.split_at_mut(self.data_shards);
+            for symbol in message.iter_mut() {
+                *symbol = framed.next().unwrap_or(0);
+            }
+            parity.copy_from_slice(&self.parity_for(message));
+            for (offset, &symbol) in codeword.iter().enumerate() {
+                stream[interleaver.position(stream.len(), index * n + offset)] = symbol;
+            }
+        }
     }
-    
-    pub fn encode(&self, data: &mut [Vec<u8>]) -> Result<(), String> {
-        self.rs.encode(data).map_err(|e| format!("Encoding error: {:?}", e))
+
+    /// Gathers, corrects and hands the data symbols of every codeword in an
+    /// interleaved stream to `emit`, in order.
+    fn read_interleaved<F>(
+        &self,
+        stream: &[u8],
+        depth: usize,
+        lost: &[usize],
+        mut emit: F,
+    ) -> Result<CorrectionReport, ClassicalError>
+    where
+        F: FnMut(&[u8]) -> Result<(), ClassicalError>,
+    {
+        let n = self.total_shards();
+        if stream.is_empty() || !stream.len().is_multiple_of(n) {
+            return Err(ClassicalError::TruncatedStream {
+                codeword_len: n,
+                actual: stream.len(),
+            });
+        }
+
+        let interleaver = BlockInterleaver::new(depth, n);
+        let mut report = CorrectionReport::default();
+        let mut buffer = [0u8; 255];
+        let codeword = &mut buffer[..n];
+        for column in 0..stream.len() / n {
+            for (offset, symbol) in codeword.iter_mut().enumerate() {
+                *symbol = stream[interleaver.position(stream.len(), column * n + offset)];
+            }
+            let mut erased = Positions::new();
+            for &position in lost.iter().filter(|&&position| position < stream.len()) {
+                let index = interleaver.source(stream.len(), position);
+                if index / n == column && !erased.contains(&(index % n)) {
+                    erased.push(index % n).expect("one entry per codeword symbol");
+                }
+            }
+
+            let corrected = self
+                .correct_codeword(codeword, &erased)
+                .map_err(|e| match e {
+                    ClassicalError::Uncorrectable { .. } => {
+                        ClassicalError::Uncorrectable { column: Some(column) }
+                    }
+                    other => other,
+                })?;
+            if corrected + erased.len() > 0 {
+                report.codewords_repaired += 1;
+            }
+            report.symbols_corrected += corrected;
+            report.erasures_filled += erased.len();
+            emit(&codeword[..self.data_shards])?;
+        }
+
+        Ok(report)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn unframe(&self, shards: &[Option<Vec<u8>>]) -> Result<Vec<u8>, ClassicalError> {
+        let mut framed = Vec::new();
+        for shard in shards.iter().take(self.data_shards) {
+            framed.extend_from_slice(shard.as_ref().expect("repaired before unframing"));
+        }
+        strip_length_header(&framed).map(<[u8]>::to_vec)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn repair(&self, data: &mut [Option<Vec<u8>>], locate_errors: bool) -> Result<CorrectionReport, ClassicalError> {
+        let shard_len = self.check_shards(data.iter().map(|s| s.as_ref().map(Vec::len)))?;
+
+        let erasures: Vec<usize> = data
+            .iter()
+            .enumerate()
+            .filter(|(_, shard)| shard.is_none())
+            .map(|(index, _)| index)
+            .collect();
+        if erasures.len() > self.parity_shards {
+            return Err(ClassicalError::TooManyErasures {
+                missing: erasures.len(),
+                max: self.parity_shards,
+            });
+        }
+        if erasures.is_empty() && !locate_errors {
+            return Ok(CorrectionReport::default());
+        }
+
+        let mut report = CorrectionReport {
+            erasures_filled: erasures.len() * shard_len,
+            ..CorrectionReport::default()
+        };
+        // Repairs happen on a copy, so the caller's shards are left as they
+        // were if a later column turns out to be uncorrectable.
+        let mut shards: Vec<Vec<u8>> = data
+            .iter()
+            .map(|shard| shard.clone().unwrap_or_else(|| vec![0u8; shard_len]))
+            .collect();
+
+        // Syndromes of every column at once; columns where they all vanish are
+        // already valid codewords and skip the per-column decoder.
+        let rows: Vec<&[u8]> = shards.iter().map(Vec::as_slice).collect();
+        let syndromes = self.shard_syndromes(&rows, shard_len);
+
+        let mut codeword = vec![0u8; self.total_shards()];
+        for column in 0..shard_len {
+            if syndromes.iter().all(|syndrome| syndrome[column] == 0) {
+                if !erasures.is_empty() {
+                    report.codewords_repaired += 1;
+                }
+                continue;
+            }
+            for (byte, shard) in codeword.iter_mut().zip(shards.iter()) {
+                *byte = shard[column];
+            }
+            let before = codeword.clone();
+            let corrected = self
+                .correct_errata(&mut codeword, &erasures, locate_errors)
+                .map_err(|()| ClassicalError::Uncorrectable { column: Some(column) })?;
+            if codeword != before || !erasures.is_empty() {
+                report.codewords_repaired += 1;
+            }
+            report.symbols_corrected += corrected;
+            for (byte, shard) in codeword.iter().zip(shards.iter_mut()) {
+                shard[column] = *byte;
+            }
+        }
+
+        for (slot, shard) in data.iter_mut().zip(shards) {
+            *slot = Some(shard);
+        }
+        Ok(report)
+    }
+
+    /// Checks the shard count and that all present shards share one length,
+    /// returning that length.
+    #[cfg(feature = "alloc")]
+    fn check_shards<I>(&self, lengths: I) -> Result<usize, ClassicalError>
+    where
+        I: ExactSizeIterator<Item = Option<usize>>,
+    {
+        if lengths.len() != self.total_shards() {
+            return Err(ClassicalError::ShardCountMismatch {
+                expected: self.total_shards(),
+                actual: lengths.len(),
+            });
+        }
+
+        let mut shard_len = None;
+        for len in lengths.flatten() {
+            match shard_len {
+                None => shard_len = Some(len),
+                Some(expected) if expected != len => {
+                    return Err(ClassicalError::ShardLengthMismatch {
+                        expected,
+                        actual: len,
+                    });
+                }
+                Some(_) => {}
+            }
+        }
+
+        shard_len.ok_or(ClassicalError::NoShardsPresent)
     }
-    
-    pub fn reconstruct(&self, data: &mut [Vec<u8>]) -> Result<(), String> {
-        self.rs.reconstruct(data).map_err(|e| format!("Reconstruction error: {:?}", e))
+
+    /// Parity symbols for one `data_shards`-long message (systematic encoding).
+    fn parity_for(&self, message: &[u8]) -> Poly {
+        let mut parity = Poly::new();
+        parity
+            .resize(self.parity_shards, 0)
+            .expect("at most 254 parity shards");
+        for &symbol in message {
+            self.shift_parity(&mut parity, symbol);
+        }
+        parity
+    }
+
+    /// One step of the encoder's division by the generator: feeds `symbol`
+    /// into the remainder `parity`, stored highest degree first.
+    fn shift_parity(&self, parity: &mut [u8], symbol: u8) {
+        let Some(&first) = parity.first() else {
+            return;
+        };
+        let feedback = symbol ^ first;
+        parity.rotate_left(1);
+        parity[parity.len() - 1] = 0;
+        for (remainder, &g) in parity.iter_mut().zip(&self.generator[1..]) {
+            *remainder ^= gf256::mul(g, feedback);
+        }
+    }
+
+    /// Syndromes S_i = c(alpha^i) for i in 0..parity_shards.
+    fn syndromes(&self, codeword: &[u8]) -> Poly {
+        (0..self.parity_shards)
+            .map(|i| {
+                let x = gf256::pow_alpha(i);
+                codeword.iter().fold(0u8, |acc, &c| gf256::mul(acc, x) ^ c)
+            })
+            .collect()
+    }
+
+    /// `syndromes` for every column of `shards`: entry `i` holds S_i of each
+    /// column, with S_i = sum of c_p * alpha^(i * (n - 1 - p)).
+    #[cfg(feature = "alloc")]
+    fn shard_syndromes(&self, shards: &[&[u8]], shard_len: usize) -> Vec<Vec<u8>> {
+        let n = self.total_shards();
+        let mut syndromes = vec![vec![0u8; shard_len]; self.parity_shards];
+        for (position, shard) in shards.iter().enumerate() {
+            for (i, syndrome) in syndromes.iter_mut().enumerate() {
+                gf256::mul_slice_xor(gf256::pow_alpha(i * (n - 1 - position)), shard, syndrome);
+            }
+        }
+        syndromes
+    }
+
+    /// Error locator X for codeword index `position` (first symbol is the
+    /// highest-degree coefficient).
+    fn locator(&self, position: usize) -> u8 {
+        gf256::pow_alpha(self.total_shards() - 1 - position)
+    }
+
+    /// Berlekamp-Massey seeded with the erasure locator, so the result locates
+    /// both the known erasures and any unknown errors. `None` if the damage is
+    /// too heavy for the locator to fit in a `Poly`.
+    fn berlekamp_massey(
+        &self,
+        syndromes: &[u8],
+        erasure_locator: &[u8],
+        erasure_count: usize,
+    ) -> Option<Poly> {
+        let mut lambda = gf256::poly(erasure_locator);
+        let mut previous = gf256::poly(erasure_locator);
+        let mut length = erasure_count;
+
+        for r in erasure_count..self.parity_shards {
+            let mut discrepancy = 0u8;
+            for (j, &coef) in lambda.iter().enumerate().take(r + 1) {
+                discrepancy ^= gf256::mul(coef, syndromes[r - j]);
+            }
+
+            // Multiply by x. Trailing zeros are dropped first, which keeps
+            // the degree, and with it the length, bounded by the parity.
+            while previous.last() == Some(&0) {
+                previous.pop();
+            }
+            previous.insert(0, 0).ok()?;
+            if discrepancy == 0 {
+                continue;
+            }
+
+            let updated = gf256::poly_add(&lambda, &gf256::poly_scale(&previous, discrepancy));
+            if 2 * length <= r + erasure_count {
+                previous = gf256::poly_scale(&lambda, gf256::inv(discrepancy));
+                length = r + 1 + erasure_count - length;
+            }
+            lambda = updated;
+        }
+
+        Some(lambda)
+    }
+
+    /// Corrects one codeword. With `locate_errors` unset only the erasures are
+    /// solved for. Returns the number of non-erased symbols changed, or `Err`
+    /// when the damage is beyond repair.
+    fn correct_errata(&self, codeword: &mut [u8], erasures: &[usize], locate_errors: bool) -> Result<usize, ()> {
+        let syndromes = self.syndromes(codeword);
+        if syndromes.iter().all(|&s| s == 0) {
+            return Ok(0);
+        }
+
+        // Erasure locator Gamma(x) = prod(1 + X_j x)
+        let mut erasure_locator = gf256::poly(&[1]);
+        for &position in erasures {
+            let factor = [1, self.locator(position)];
+            erasure_locator = gf256::poly_mul(&erasure_locator, &factor, gf256::MAX_POLY_LEN);
+        }
+
+        let (errata_locator, positions) = if locate_errors {
+            let lambda = self
+                .berlekamp_massey(&syndromes, &erasure_locator, erasures.len())
+                .ok_or(())?;
+            let degree = gf256::poly_degree(&lambda).unwrap_or(0);
+            if 2 * degree > self.parity_shards + erasures.len() {
+                return Err(());
+            }
+
+            // Chien search over every codeword position
+            let positions: Positions = (0..self.total_shards())
+                .filter(|&p| gf256::poly_eval(&lambda, gf256::inv(self.locator(p))) == 0)
+                .collect();
+            if positions.len() != degree {
+                return Err(());
+            }
+            (lambda, positions)
+        } else {
+            (erasure_locator, Positions::from_slice(erasures)?)
+        };
+
+        // Error evaluator Omega(x) = S(x) * Lambda(x) mod x^parity_shards
+        let evaluator = gf256::poly_mul(&syndromes, &errata_locator, self.parity_shards);
+        let derivative = gf256::poly_derivative(&errata_locator);
+
+        let mut corrected = 0;
+        for &position in &positions {
+            let x = self.locator(position);
+            let x_inv = gf256::inv(x);
+            let denominator = gf256::poly_eval(&derivative, x_inv);
+            if denominator == 0 {
+                return Err(());
+            }
+            let magnitude = gf256::mul(x, gf256::div(gf256::poly_eval(&evaluator, x_inv), denominator));
+            codeword[position] ^= magnitude;
+            if magnitude != 0 && !erasures.contains(&position) {
+                corrected += 1;
+            }
+        }
+
+        if locate_errors && self.syndromes(codeword).iter().any(|&s| s != 0) {
+            return Err(());
+        }
+
+        Ok(corrected)
+    }
+}
+
+/// Splits the little-endian length header off `framed` and returns the payload it describes.
+#[cfg(feature = "alloc")]
+fn strip_length_header(framed: &[u8]) -> Result<&[u8], ClassicalError> {
+    if framed.len() < LENGTH_HEADER_LEN {
+        return Err(ClassicalError::InvalidLengthHeader {
+            declared: 0,
+            available: 0,
+        });
+    }
+    let mut header = [0u8; LENGTH_HEADER_LEN];
+    header.copy_from_slice(&framed[..LENGTH_HEADER_LEN]);
+    let len = u32::from_le_bytes(header) as usize;
+    let available = framed.len() - LENGTH_HEADER_LEN;
+    if len > available {
+        return Err(ClassicalError::InvalidLengthHeader {
+            declared: len,
+            available,
+        });
+    }
+
+    Ok(&framed[LENGTH_HEADER_LEN..LENGTH_HEADER_LEN + len])
+}
+
+#[cfg(feature = "alloc")]
+impl ErrorCorrector for ClassicalErrorCorrection {
+    fn layer(&self) -> CorrectionLayer {
+        CorrectionLayer::Classical
+    }
+
+    /// Frame layout: `[data_shards, parity_shards]` header, then the shards back to back.
+    fn encode(&self, data: &[u8]) -> Vec<u8> {
+        let mut frame = write_header(&[self.data_shards as u8, self.parity_shards as u8]);
+        for shard in self.encode_bytes(data) {
+            frame.extend_from_slice(&shard);
+        }
+        frame
+    }
+
+    fn decode(&self, frame: &[u8]) -> Result<LayerOutput, ErrorCorrectionFailure> {
+        let layer = CorrectionLayer::Classical;
+        let (header, body) = read_header(frame, 2, layer)?;
+        let (data_shards, parity_shards) = (header[0] as usize, header[1] as usize);
+        let total_shards = data_shards + parity_shards;
+        if data_shards == 0 || total_shards > 255 || body.is_empty() || body.len() % total_shards != 0 {
+            return Err(ErrorCorrectionFailure::new(layer, 0, FailureReason::MalformedHeader));
+        }
+
+        let codec = if (data_shards, parity_shards) == (self.data_shards, self.parity_shards) {
+            None
+        } else {
+            Some(ClassicalErrorCorrection::new(data_shards, parity_shards))
+        };
+        let mut shards: Vec<Option<Vec<u8>>> = body
+            .chunks(body.len() / total_shards)
+            .map(|shard| Some(shard.to_vec()))
+            .collect();
+
+        let (data, report) = codec
+            .as_ref()
+            .unwrap_or(self)
+            .decode_bytes_correcting(&mut shards)
+            .map_err(|e| ErrorCorrectionFailure::new(layer, 0, e.reason()))?;
+
+        Ok(LayerOutput {
+            data,
+            report: LayerReport {
+                layer,
+                symbols_corrected: report.symbols_corrected as u32,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn roundtrip_without_loss() {
+        let codec = ClassicalErrorCorrection::new(4, 2);
+        let data = b"ELXR fermentation telemetry".to_vec();
+
+        let mut shards: Vec<Option<Vec<u8>>> =
+            codec.encode_bytes(&data).into_iter().map(Some).collect();
+        assert_eq!(shards.len(), 6);
+        assert_eq!(codec.decode_bytes(&mut shards).unwrap(), data);
+    }
+
+    #[test]
+    fn empty_payload_roundtrips() {
+        let codec = ClassicalErrorCorrection::new(3, 3);
+        let mut shards: Vec<Option<Vec<u8>>> =
+            codec.encode_bytes(&[]).into_iter().map(Some).collect();
+        shards[0] = None;
+        assert_eq!(codec.decode_bytes(&mut shards).unwrap(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn too_many_missing_shards_is_an_error() {
+        let codec = ClassicalErrorCorrection::new(4, 2);
+        let mut shards: Vec<Option<Vec<u8>>> =
+            codec.encode_bytes(b"kombucha").into_iter().map(Some).collect();
+        shards[0] = None;
+        shards[3] = None;
+        shards[5] = None;
+        assert_eq!(
+            codec.decode_bytes(&mut shards),
+            Err(ClassicalError::TooManyErasures { missing: 3, max: 2 })
+        );
+    }
+
+    #[test]
+    fn shard_count_is_checked() {
+        let codec = ClassicalErrorCorrection::new(4, 2);
+        let mut shards = vec![vec![0u8; 8]; 5];
+        assert_eq!(
+            codec.encode(&mut shards),
+            Err(ClassicalError::ShardCountMismatch { expected: 6, actual: 5 })
+        );
+    }
+
+    #[test]
+    fn locates_and_reports_corrupted_symbols() {
+        let codec = ClassicalErrorCorrection::new(8, 4);
+        let data = b"{\"ph\":3.21,\"temp\":24.50,\"co2\":12.0}".to_vec();
+        let mut shards: Vec<Option<Vec<u8>>> =
+            codec.encode_bytes(&data).into_iter().map(Some).collect();
+
+        shards[1].as_mut().unwrap()[0] ^= 0x5a;
+        shards[9].as_mut().unwrap()[0] ^= 0x01;
+        shards[4].as_mut().unwrap()[2] ^= 0xff;
+
+        let (decoded, report) = codec.decode_bytes_correcting(&mut shards).unwrap();
+        assert_eq!(decoded, data);
+        assert_eq!(report.symbols_corrected, 3);
+        assert_eq!(report.codewords_repaired, 2);
+        assert_eq!(report.erasures_filled, 0);
+    }
+
+    #[test]
+    fn damage_beyond_repair_is_reported() {
+        let codec = ClassicalErrorCorrection::new(4, 2);
+        let mut codeword = codec.encode_codeword(&[1, 2, 3, 4]);
+        codeword[0] ^= 0x10;
+        codeword[2] ^= 0x22;
+
+        assert_eq!(
+            codec.correct_codeword(&mut codeword, &[]),
+            Err(ClassicalError::Uncorrectable { column: None })
+        );
+    }
+
+    #[test]
+    fn failed_repairs_leave_the_shards_untouched() {
+        let codec = ClassicalErrorCorrection::new(4, 2);
+        let mut shards: Vec<Option<Vec<u8>>> =
+            codec.encode_bytes(b"kombucha batch 7").into_iter().map(Some).collect();
+        // Column 0 only misses its erased symbol; column 1 also has two errors
+        shards[5] = None;
+        shards[0].as_mut().unwrap()[1] ^= 0x10;
+        shards[2].as_mut().unwrap()[1] ^= 0x22;
+        let damaged = shards.clone();
+
+        assert_eq!(
+            codec.correct(&mut shards),
+            Err(ClassicalError::Uncorrectable { column: Some(1) })
+        );
+        assert_eq!(shards, damaged);
+    }
+
+    #[test]
+    fn interleaving_survives_bursts_up_to_depth_times_correctable() {
+        let codec = ClassicalErrorCorrection::new(8, 4);
+        let data: Vec<u8> = (0..200u8).collect();
+        let depth = 6;
+        let stream = codec.encode_interleaved(&data, depth);
+
+        // Every burst of depth * t bytes inside the full interleaver blocks
+        let full_blocks = stream.len() / (depth * codec.total_shards()) * depth * codec.total_shards();
+        let burst = depth * codec.parity_shards() / 2;
+        for start in 0..=full_blocks - burst {
+            let mut damaged = stream.clone();
+            for byte in &mut damaged[start..start + burst] {
+                *byte ^= 0xa5;
+            }
+            let (decoded, report) = codec.decode_interleaved(&damaged, depth, &[]).unwrap();
+            assert_eq!(decoded, data);
+            assert_eq!(report.symbols_corrected, burst);
+        }
+    }
+
+    #[test]
+    fn interleaving_fills_lost_runs_as_erasures() {
+        let codec = ClassicalErrorCorrection::new(8, 4);
+        let data = b"{\"ph\":3.21,\"temp\":24.50,\"co2\":12.0,\"batch\":\"K-2207\"}".to_vec();
+        let depth = 4;
+        let mut stream = codec.encode_interleaved(&data, depth);
+
+        let lost: Vec<usize> = (5..5 + depth * codec.parity_shards()).collect();
+        for &position in &lost {
+            stream[position] = 0;
+        }
+        let (decoded, report) = codec.decode_interleaved(&stream, depth, &lost).unwrap();
+        assert_eq!(decoded, data);
+        assert_eq!(report.erasures_filled, lost.len());
+    }
+
+    #[test]
+    fn heapless_framing_matches_the_allocating_api() {
+        let codec = ClassicalErrorCorrection::new(SERIAL_DATA_SHARDS, SERIAL_PARITY_SHARDS);
+        let data = br#"{"device_id":"ELXR-SPIRULINA-POOL-A24","ph":9.12}"#;
+
+        let stream: heapless::Vec<u8, 128> = codec
+            .encode_interleaved_heapless(data, SERIAL_INTERLEAVE_DEPTH)
+            .unwrap();
+        assert_eq!(&stream[..], &codec.encode_interleaved(data, SERIAL_INTERLEAVE_DEPTH)[..]);
+
+        let mut damaged = stream.clone();
+        damaged[10..20].iter_mut().for_each(|byte| *byte ^= 0x5a);
+        let (decoded, report) = codec
+            .decode_interleaved_heapless::<64>(&damaged, SERIAL_INTERLEAVE_DEPTH, &[])
+            .unwrap();
+        assert_eq!(&decoded[..], &data[..]);
+        assert_eq!(report.symbols_corrected, 10);
+
+        assert_eq!(
+            codec.encode_interleaved_heapless::<64>(data, SERIAL_INTERLEAVE_DEPTH),
+            Err(ClassicalError::CapacityExceeded {
+                needed: codec.interleaved_len(data.len()),
+                capacity: 64
+            })
+        );
+        assert_eq!(
+            codec.decode_interleaved_heapless::<16>(&stream, SERIAL_INTERLEAVE_DEPTH, &[]),
+            Err(ClassicalError::CapacityExceeded {
+                needed: data.len(),
+                capacity: 16
+            })
+        );
+    }
+
+    #[test]
+    fn bursts_break_codewords_without_interleaving() {
+        let codec = ClassicalErrorCorrection::new(8, 4);
+        let mut stream = codec.encode_interleaved(b"scoby culture", 1);
+        let n = codec.total_shards();
+        for byte in &mut stream[n..n + 3] {
+            *byte ^= 0xa5;
+        }
+        assert_eq!(
+            codec.decode_interleaved(&stream, 1, &[]),
+            Err(ClassicalError::Uncorrectable { column: Some(1) })
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn recovers_from_random_erasures(
+            data in proptest::collection::vec(any::<u8>(), 0..1024),
+            data_shards in 1usize..16,
+            parity_shards in 1usize..8,
+            erase_order in Just((0..24usize).collect::<Vec<_>>()).prop_shuffle(),
+            erase_count in 0usize..8,
+        ) {
+            let codec = ClassicalErrorCorrection::new(data_shards, parity_shards);
+            let mut shards: Vec<Option<Vec<u8>>> =
+                codec.encode_bytes(&data).into_iter().map(Some).collect();
+
+            let erase_count = erase_count.min(parity_shards);
+            for index in erase_order
+                .into_iter()
+                .filter(|&i| i < codec.total_shards())
+                .take(erase_count)
+            {
+                shards[index] = None;
+            }
+
+            prop_assert_eq!(codec.decode_bytes(&mut shards).unwrap(), data);
+        }
+
+        #[test]
+        fn reconstruct_restores_original_shards(
+            data in proptest::collection::vec(any::<u8>(), 1..256),
+            erase_order in Just((0..10usize).collect::<Vec<_>>()).prop_shuffle(),
+        ) {
+            let codec = ClassicalErrorCorrection::new(6, 4);
+            let original = codec.encode_bytes(&data);
+            let mut shards: Vec<Option<Vec<u8>>> = original.iter().cloned().map(Some).collect();
+            for &index in erase_order.iter().take(4) {
+                shards[index] = None;
+            }
+
+            codec.reconstruct(&mut shards).unwrap();
+            let rebuilt: Vec<Vec<u8>> = shards.into_iter().map(Option::unwrap).collect();
+            prop_assert_eq!(rebuilt, original);
+        }
+
+        #[test]
+        fn corrects_unknown_errors_and_erasures(
+            message in proptest::collection::vec(any::<u8>(), 10),
+            order in Just((0..16usize).collect::<Vec<_>>()).prop_shuffle(),
+            errors in 0usize..=3,
+            erasures in 0usize..=6,
+            flips in proptest::collection::vec(1u8..=255, 16),
+        ) {
+            let codec = ClassicalErrorCorrection::new(10, 6);
+            let original = codec.encode_codeword(&message);
+            let erasures = erasures.min(6 - 2 * errors);
+
+            let mut codeword = original.clone();
+            let (erased, corrupted) = order.split_at(erasures);
+            for &position in erased {
+                codeword[position] = 0;
+            }
+            for &position in corrupted.iter().take(errors) {
+                codeword[position] ^= flips[position];
+            }
+
+            let corrected = codec.correct_codeword(&mut codeword, erased).unwrap();
+            prop_assert_eq!(corrected, errors);
+            prop_assert_eq!(codeword, original);
+        }
     }
 }
@@ -0,0 +1,102 @@
+// Block interleaving for bursty links
+//
+// Reed-Solomon codewords sent back to back die when a burst lands inside one
+// of them. The interleaver writes `depth` codewords as the rows of a block and
+// transmits the block column by column, so a burst of `depth * t` consecutive
+// bytes touches each codeword at most `t` times. A trailing block with fewer
+// than `depth` rows is interleaved over the rows it has, which makes it
+// proportionally less tolerant.
+#[cfg(feature = "alloc")]
+use sp_std::prelude::*;
+
+pub struct BlockInterleaver {
+    depth: usize,
+    width: usize,
+}
+
+impl BlockInterleaver {
+    /// Interleaves `depth` rows of `width` bytes at a time. Panics if either is zero.
+    pub fn new(depth: usize, width: usize) -> Self {
+        assert!(depth > 0 && width > 0, "interleaver depth and width must be non-zero");
+        Self { depth, width }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Reorders whole rows for transmission. Panics unless `data` is a whole
+    /// number of rows.
+    #[cfg(feature = "alloc")]
+    pub fn interleave(&self, data: &[u8]) -> Vec<u8> {
+        self.check_len(data.len());
+        (0..data.len()).map(|position| data[self.source(data.len(), position)]).collect()
+    }
+
+    /// Reverses `interleave`.
+    #[cfg(feature = "alloc")]
+    pub fn deinterleave(&self, data: &[u8]) -> Vec<u8> {
+        self.check_len(data.len());
+        let mut rows = vec![0u8; data.len()];
+        for (position, &byte) in data.iter().enumerate() {
+            rows[self.source(data.len(), position)] = byte;
+        }
+        rows
+    }
+
+    /// Row-major index of the byte sent at `position` in a stream of `len` bytes.
+    pub fn source(&self, len: usize, position: usize) -> usize {
+        let block_len = self.depth * self.width;
+        let base = position - position % block_len;
+        let rows = self.depth.min((len - base) / self.width);
+        let offset = position - base;
+        base + (offset % rows) * self.width + offset / rows
+    }
+
+    /// Stream position at which the byte with row-major index `index` is sent;
+    /// the inverse of `source`.
+    pub fn position(&self, len: usize, index: usize) -> usize {
+        let block_len = self.depth * self.width;
+        let base = index - index % block_len;
+        let rows = self.depth.min((len - base) / self.width);
+        let offset = index - base;
+        base + (offset % self.width) * rows + offset / self.width
+    }
+
+    #[cfg(feature = "alloc")]
+    fn check_len(&self, len: usize) {
+        assert!(
+            len.is_multiple_of(self.width),
+            "interleaved data must be a whole number of {}-byte rows",
+            self.width
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrips_including_partial_blocks() {
+        let interleaver = BlockInterleaver::new(4, 3);
+        for rows in 0..11 {
+            let data: Vec<u8> = (0..rows * 3).map(|i| i as u8).collect();
+            let sent = interleaver.interleave(&data);
+            assert_eq!(interleaver.deinterleave(&sent), data);
+            for index in 0..data.len() {
+                assert_eq!(interleaver.source(data.len(), interleaver.position(data.len(), index)), index);
+            }
+        }
+    }
+
+    #[test]
+    fn sends_columns_of_a_block() {
+        let interleaver = BlockInterleaver::new(2, 3);
+        assert_eq!(interleaver.interleave(&[1, 2, 3, 4, 5, 6]), vec![1, 4, 2, 5, 3, 6]);
+    }
+}
@@ -0,0 +1,33 @@
+// Error correction shared by every Matrix-Magiq component
+//
+// Each layer implements `ErrorCorrector`, and `LayerStack` chains them in the
+// order a frame is peeled on receipt: classical, then bridge, then quantum.
+// Pallets, the parachain integration and the frontends all go through this
+// module, so a fix in one layer reaches every consumer.
+//
+// Without the `alloc` feature only the classical codec's heapless framing and
+// the interleaver's index mapping are built, which is all firmware needs.
+pub mod classical;
+pub mod interleaver;
+
+#[cfg(feature = "alloc")]
+pub mod adaptive;
+#[cfg(feature = "alloc")]
+pub mod bridge;
+#[cfg(feature = "alloc")]
+pub mod quantum;
+#[cfg(feature = "alloc")]
+pub mod stats;
+#[cfg(feature = "alloc")]
+mod stack;
+
+pub use classical::ClassicalErrorCorrection;
+pub use interleaver::BlockInterleaver;
+
+#[cfg(feature = "alloc")]
+pub use self::{
+    adaptive::AdaptiveCorrector, bridge::BridgeErrorCorrection, quantum::QuantumErrorCorrection,
+    stats::CorrectionStats,
+};
+#[cfg(feature = "alloc")]
+pub use stack::*;
@@ -0,0 +1,64 @@
+// Surface-code decoders
+//
+// A decoder turns the syndrome of one stabilizer kind into a Pauli correction
+// on the data qubits. The correction is applied with the Pauli those
+// stabilizers detect, i.e. Z for the X graph and X for the Z graph.
+use sp_std::prelude::*;
+
+use super::matching::MatchingGraph;
+
+pub trait Decoder {
+    /// Data qubits to flip so that `syndrome` on `graph` is cleared.
+    fn correction(&self, graph: &MatchingGraph, syndrome: &[bool]) -> Vec<bool>;
+
+    /// Like `correction`, with `erased` marking data qubits known to have been
+    /// lost. Decoders that cannot use the locations ignore them.
+    fn correction_with_erasures(
+        &self,
+        graph: &MatchingGraph,
+        syndrome: &[bool],
+        erased: &[bool],
+    ) -> Vec<bool> {
+        let _ = erased;
+        self.correction(graph, syndrome)
+    }
+}
+
+impl<D: Decoder + ?Sized> Decoder for &D {
+    fn correction(&self, graph: &MatchingGraph, syndrome: &[bool]) -> Vec<bool> {
+        (**self).correction(graph, syndrome)
+    }
+
+    fn correction_with_erasures(
+        &self,
+        graph: &MatchingGraph,
+        syndrome: &[bool],
+        erased: &[bool],
+    ) -> Vec<bool> {
+        (**self).correction_with_erasures(graph, syndrome, erased)
+    }
+}
+
+/// Minimum-weight perfect matching over the defects and the boundary.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MinimumWeightDecoder;
+
+impl Decoder for MinimumWeightDecoder {
+    fn correction(&self, graph: &MatchingGraph, syndrome: &[bool]) -> Vec<bool> {
+        let pairs = graph.minimum_weight_matching(&MatchingGraph::defects(syndrome));
+        graph.correction(&pairs, graph.qubits())
+    }
+}
+
+/// Pairs the closest defects first.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GreedyDecoder;
+
+impl Decoder for GreedyDecoder {
+    fn correction(&self, graph: &MatchingGraph, syndrome: &[bool]) -> Vec<bool> {
+        let pairs = graph.greedy_matching(&MatchingGraph::defects(syndrome));
+        graph.correction(&pairs, graph.qubits())
+    }
+}
+
+pub use super::union_find::UnionFindDecoder;
@@ -0,0 +1,305 @@
+// Matching graph for surface-code decoding
+//
+// One graph per stabilizer kind. Its nodes are the stabilizers of that kind
+// plus a single virtual boundary node. Each data qubit is an edge that joins
+// the stabilizers it touches, or joins its only stabilizer to the boundary.
+// An error chain lights up the stabilizers at its ends, so a correction is a
+// set of paths that pair every lit stabilizer with another one or with the
+// boundary.
+use sp_std::collections::vec_deque::VecDeque;
+use sp_std::prelude::*;
+
+use super::surface_code::{PauliKind, RotatedSurfaceCode};
+
+/// Defect count up to which `minimum_weight_matching` is solved exactly.
+pub const EXACT_MATCHING_LIMIT: usize = 18;
+
+pub struct MatchingGraph {
+    kind: PauliKind,
+    /// Endpoints of the edge for every data qubit.
+    edges: Vec<(usize, usize)>,
+    /// `(neighbour, qubit)` pairs for every node.
+    adjacency: Vec<Vec<(usize, usize)>>,
+    /// Hop count between every pair of nodes.
+    distances: Vec<Vec<usize>>,
+    /// `parents[source][node]` is the step back towards `source` on a
+    /// shortest path, as `(previous node, qubit)`.
+    parents: Vec<Vec<Option<(usize, usize)>>>,
+}
+
+impl MatchingGraph {
+    /// Builds the graph over the `kind` stabilizers of `code`.
+    pub fn new(code: &RotatedSurfaceCode, kind: PauliKind) -> Self {
+        let stabilizers = code.stabilizers(kind);
+        let boundary = stabilizers.len();
+        let mut adjacency = vec![Vec::new(); boundary + 1];
+        let mut edges = Vec::with_capacity(code.data_qubits());
+
+        for qubit in 0..code.data_qubits() {
+            let checks: Vec<usize> = stabilizers
+                .iter()
+                .enumerate()
+                .filter(|(_, stabilizer)| stabilizer.qubits.contains(&qubit))
+                .map(|(index, _)| index)
+                .collect();
+            let (a, b) = match checks[..] {
+                [a] => (a, boundary),
+                [a, b] => (a, b),
+                _ => unreachable!("every data qubit touches one or two stabilizers of each kind"),
+            };
+            edges.push((a, b));
+            adjacency[a].push((b, qubit));
+            adjacency[b].push((a, qubit));
+        }
+
+        let mut distances = Vec::with_capacity(adjacency.len());
+        let mut parents = Vec::with_capacity(adjacency.len());
+        for source in 0..adjacency.len() {
+            let (distance, parent) = breadth_first(&adjacency, source);
+            distances.push(distance);
+            parents.push(parent);
+        }
+
+        Self {
+            kind,
+            edges,
+            adjacency,
+            distances,
+            parents,
+        }
+    }
+
+    /// Stabilizer kind the graph is built over.
+    pub fn kind(&self) -> PauliKind {
+        self.kind
+    }
+
+    /// Number of nodes, including the boundary.
+    pub fn nodes(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    /// Number of edges, one per data qubit.
+    pub fn qubits(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Nodes joined by the edge of `qubit`.
+    pub fn edge(&self, qubit: usize) -> (usize, usize) {
+        self.edges[qubit]
+    }
+
+    /// Index of the virtual boundary node.
+    pub fn boundary(&self) -> usize {
+        self.adjacency.len() - 1
+    }
+
+    pub fn distance(&self, a: usize, b: usize) -> usize {
+        self.distances[a][b]
+    }
+
+    /// Qubits on a shortest path between two nodes.
+    pub fn path(&self, a: usize, b: usize) -> Vec<usize> {
+        let mut qubits = Vec::with_capacity(self.distances[a][b]);
+        let mut node = b;
+        while let Some((previous, qubit)) = self.parents[a][node] {
+            qubits.push(qubit);
+            node = previous;
+        }
+        qubits
+    }
+
+    /// Indices of the lit stabilizers in a syndrome.
+    pub fn defects(syndrome: &[bool]) -> Vec<usize> {
+        syndrome
+            .iter()
+            .enumerate()
+            .filter(|(_, &flag)| flag)
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Flips the qubits of every matched path into a correction mask.
+    pub fn correction(&self, pairs: &[(usize, usize)], data_qubits: usize) -> Vec<bool> {
+        let mut mask = vec![false; data_qubits];
+        for &(a, b) in pairs {
+            for qubit in self.path(a, b) {
+                mask[qubit] ^= true;
+            }
+        }
+        mask
+    }
+
+    /// Pairs every defect with another defect or the boundary so that the
+    /// total path length is minimal. Solved exactly by dynamic programming
+    /// over subsets of defects; above `EXACT_MATCHING_LIMIT` defects it falls
+    /// back to `greedy_matching`.
+    pub fn minimum_weight_matching(&self, defects: &[usize]) -> Vec<(usize, usize)> {
+        let n = defects.len();
+        if n > EXACT_MATCHING_LIMIT {
+            return self.greedy_matching(defects);
+        }
+
+        let boundary = self.boundary();
+        let full = (1usize << n) - 1;
+        // cost[mask] is the cheapest way to match the defects in `mask`;
+        // choice[mask] records the partner of its lowest defect (n for the boundary)
+        let mut cost = vec![usize::MAX; full + 1];
+        let mut choice = vec![0u8; full + 1];
+        cost[0] = 0;
+        for mask in 1..=full {
+            let i = mask.trailing_zeros() as usize;
+            let rest = mask & !(1 << i);
+            let mut best = cost[rest].saturating_add(self.distance(defects[i], boundary));
+            let mut partner = n;
+            let mut others = rest;
+            while others != 0 {
+                let j = others.trailing_zeros() as usize;
+                others &= others - 1;
+                let candidate = cost[rest & !(1 << j)].saturating_add(self.distance(defects[i], defects[j]));
+                if candidate < best {
+                    best = candidate;
+                    partner = j;
+                }
+            }
+            cost[mask] = best;
+            choice[mask] = partner as u8;
+        }
+
+        let mut pairs = Vec::with_capacity(n);
+        let mut mask = full;
+        while mask != 0 {
+            let i = mask.trailing_zeros() as usize;
+            let partner = choice[mask] as usize;
+            mask &= !(1 << i);
+            if partner == n {
+                pairs.push((defects[i], boundary));
+            } else {
+                pairs.push((defects[i], defects[partner]));
+                mask &= !(1 << partner);
+            }
+        }
+        pairs
+    }
+
+    /// Repeatedly pairs the two closest defects, or a defect with the
+    /// boundary when that is closer. Fast but not minimum weight.
+    pub fn greedy_matching(&self, defects: &[usize]) -> Vec<(usize, usize)> {
+        let boundary = self.boundary();
+        let mut open = defects.to_vec();
+        let mut pairs = Vec::with_capacity(open.len());
+
+        while !open.is_empty() {
+            let mut best = (usize::MAX, 0, None);
+            for (i, &a) in open.iter().enumerate() {
+                for (j, &b) in open.iter().enumerate().skip(i + 1) {
+                    if self.distance(a, b) < best.0 {
+                        best = (self.distance(a, b), i, Some(j));
+                    }
+                }
+            }
+            // Ties go to the pair, so a single bulk error is not split into two boundary chains
+            for (i, &a) in open.iter().enumerate() {
+                if self.distance(a, boundary) < best.0 {
+                    best = (self.distance(a, boundary), i, None);
+                }
+            }
+
+            let (_, i, partner) = best;
+            match partner {
+                Some(j) => {
+                    pairs.push((open[i], open[j]));
+                    open.remove(j);
+                    open.remove(i);
+                }
+                None => {
+                    pairs.push((open[i], boundary));
+                    open.remove(i);
+                }
+            }
+        }
+        pairs
+    }
+}
+
+fn breadth_first(
+    adjacency: &[Vec<(usize, usize)>],
+    source: usize,
+) -> (Vec<usize>, Vec<Option<(usize, usize)>>) {
+    let mut distance = vec![usize::MAX; adjacency.len()];
+    let mut parent = vec![None; adjacency.len()];
+    let mut queue = VecDeque::new();
+    distance[source] = 0;
+    queue.push_back(source);
+
+    while let Some(node) = queue.pop_front() {
+        for &(neighbour, qubit) in &adjacency[node] {
+            if distance[neighbour] == usize::MAX {
+                distance[neighbour] = distance[node] + 1;
+                parent[neighbour] = Some((node, qubit));
+                queue.push_back(neighbour);
+            }
+        }
+    }
+    (distance, parent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::quantum::surface_code::LogicalState;
+
+    #[test]
+    fn greedy_matching_clears_the_syndrome() {
+        let code = RotatedSurfaceCode::new(5);
+        let graph = MatchingGraph::new(&code, PauliKind::Z);
+        let mut encoded = code.encode(LogicalState::Zero);
+        for qubit in [0, 7, 8, 19] {
+            encoded.apply_x(qubit);
+        }
+
+        let defects = MatchingGraph::defects(&code.syndrome(&encoded).z);
+        let mask = graph.correction(&graph.greedy_matching(&defects), code.data_qubits());
+        for (qubit, flip) in mask.into_iter().enumerate() {
+            if flip {
+                encoded.apply_x(qubit);
+            }
+        }
+        assert!(code.syndrome(&encoded).is_trivial());
+    }
+
+    #[test]
+    fn minimum_weight_beats_greedy() {
+        let code = RotatedSurfaceCode::new(7);
+        let graph = MatchingGraph::new(&code, PauliKind::Z);
+        let weight =
+            |pairs: &[(usize, usize)]| pairs.iter().map(|&(a, b)| graph.distance(a, b)).sum::<usize>();
+
+        for seed in 0..32usize {
+            let defects: Vec<usize> = (0..graph.boundary())
+                .filter(|n| (n * (seed + 1) * 31 + seed) % 4 == 0)
+                .collect();
+            let optimal = graph.minimum_weight_matching(&defects);
+            assert!(weight(&optimal) <= weight(&graph.greedy_matching(&defects)));
+
+            let mut matched: Vec<usize> = optimal
+                .iter()
+                .flat_map(|&(a, b)| [a, b])
+                .filter(|&node| node != graph.boundary())
+                .collect();
+            matched.sort_unstable();
+            assert_eq!(matched, defects);
+        }
+    }
+
+    #[test]
+    fn paths_have_shortest_length() {
+        let code = RotatedSurfaceCode::new(3);
+        let graph = MatchingGraph::new(&code, PauliKind::X);
+        for a in 0..=graph.boundary() {
+            for b in 0..=graph.boundary() {
+                assert_eq!(graph.path(a, b).len(), graph.distance(a, b));
+            }
+        }
+    }
+}
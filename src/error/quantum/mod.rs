@@ -1,33 +1,239 @@
 // Quantum Error Correction using Surface codes
+use alloc::format;
+use alloc::string::{String, ToString};
+use sp_std::prelude::*;
+
+use super::{
+    read_header, write_header, CorrectionLayer, ErrorCorrectionFailure, ErrorCorrector, FailureReason,
+    LayerOutput, LayerReport,
+};
+
+pub mod decoder;
+pub mod matching;
+pub mod monte_carlo;
+pub mod noise;
+pub mod surface_code;
+mod union_find;
+
+use decoder::{Decoder, MinimumWeightDecoder};
+use matching::MatchingGraph;
+use surface_code::{EncodedQubit, LogicalState, PauliKind, RotatedSurfaceCode};
+
+/// Largest code distance accepted, which bounds the decoding work a frame
+/// header can ask for.
+pub const MAX_CODE_DISTANCE: usize = 15;
+
+/// Every logical bit is carried by one rotated surface-code patch. The
+/// physical bits are the Z-basis readout of the data qubits, so a flipped
+/// bit is an X error; decoding matches the Z stabilizer syndrome and then
+/// reads the logical Z operator.
 pub struct QuantumErrorCorrection {
     code_distance: usize,
+    lattice: Lattice,
+    decoder: Box<dyn Decoder>,
+}
+
+struct Lattice {
+    code: RotatedSurfaceCode,
+    graph: MatchingGraph,
+}
+
+impl Lattice {
+    fn new(code_distance: usize) -> Self {
+        let code = RotatedSurfaceCode::new(code_distance);
+        let graph = MatchingGraph::new(&code, PauliKind::Z);
+        Self { code, graph }
+    }
 }
 
 impl QuantumErrorCorrection {
+    /// Decodes with minimum-weight matching. Panics unless `code_distance`
+    /// is odd and between 3 and `MAX_CODE_DISTANCE`.
     pub fn new(code_distance: usize) -> Self {
-        Self { code_distance }
+        assert!(code_distance <= MAX_CODE_DISTANCE, "code distance above MAX_CODE_DISTANCE");
+        Self {
+            code_distance,
+            lattice: Lattice::new(code_distance),
+            decoder: Box::new(MinimumWeightDecoder),
+        }
     }
-    
+
+    /// Replaces the syndrome decoder.
+    pub fn with_decoder<D>(mut self, decoder: D) -> Self
+    where
+        D: Decoder + 'static,
+    {
+        self.decoder = Box::new(decoder);
+        self
+    }
+
+    pub fn code_distance(&self) -> usize {
+        self.code_distance
+    }
+
     pub fn encode_qubit(&self, qubit_value: bool) -> Vec<bool> {
-        // Implement surface code encoding
-        let mut encoded = Vec::with_capacity(self.code_distance * self.code_distance);
-        for _ in 0..(self.code_distance * self.code_distance) {
-            encoded.push(qubit_value);
-        }
-        encoded
+        let state = if qubit_value { LogicalState::One } else { LogicalState::Zero };
+        self.lattice.code.encode(state).x_frame
     }
-    
+
     pub fn correct_and_decode(&self, encoded_qubits: &[bool]) -> Result<bool, String> {
-        // Implement surface code decoding and correction
-        if encoded_qubits.is_empty() {
-            return Err("Empty encoded qubits".to_string());
+        self.correct_and_decode_counting(encoded_qubits).map(|(bit, _)| bit)
+    }
+
+    /// Like `correct_and_decode`, also returning how many qubits were flipped.
+    pub fn correct_and_decode_counting(&self, encoded_qubits: &[bool]) -> Result<(bool, usize), String> {
+        correct_block(&self.lattice, self.decoder.as_ref(), encoded_qubits)
+    }
+}
+
+fn correct_block(
+    lattice: &Lattice,
+    decoder: &dyn Decoder,
+    encoded_qubits: &[bool],
+) -> Result<(bool, usize), String> {
+    if encoded_qubits.is_empty() {
+        return Err("Empty encoded qubits".to_string());
+    }
+    if encoded_qubits.len() != lattice.code.data_qubits() {
+        return Err(format!(
+            "Expected {} encoded qubits, got {}",
+            lattice.code.data_qubits(),
+            encoded_qubits.len()
+        ));
+    }
+
+    let mut patch = EncodedQubit {
+        basis: PauliKind::Z,
+        x_frame: encoded_qubits.to_vec(),
+        z_frame: vec![false; encoded_qubits.len()],
+    };
+    let syndrome = lattice.code.syndrome(&patch);
+    let correction = decoder.correction(&lattice.graph, &syndrome.z);
+    let mut flipped = 0;
+    for (qubit, _) in correction.iter().enumerate().filter(|(_, &flip)| flip) {
+        patch.apply_x(qubit);
+        flipped += 1;
+    }
+
+    Ok((lattice.code.measure_logical(&patch), flipped))
+}
+
+impl ErrorCorrector for QuantumErrorCorrection {
+    fn layer(&self) -> CorrectionLayer {
+        CorrectionLayer::Quantum
+    }
+
+    /// Frame layout: `[code_distance]` header, then every payload bit encoded
+    /// as a block of physical qubits, packed most significant bit first.
+    fn encode(&self, data: &[u8]) -> Vec<u8> {
+        let qubits: Vec<bool> = unpack_bits(data)
+            .into_iter()
+            .flat_map(|bit| self.encode_qubit(bit))
+            .collect();
+        let mut frame = write_header(&[self.code_distance as u8]);
+        frame.extend_from_slice(&pack_bits(&qubits));
+        frame
+    }
+
+    fn decode(&self, frame: &[u8]) -> Result<LayerOutput, ErrorCorrectionFailure> {
+        let layer = CorrectionLayer::Quantum;
+        let (header, body) = read_header(frame, 1, layer)?;
+        let code_distance = header[0] as usize;
+        if code_distance < 3 || code_distance.is_multiple_of(2) || code_distance > MAX_CODE_DISTANCE {
+            return Err(ErrorCorrectionFailure::new(layer, 0, FailureReason::MalformedHeader));
         }
-        
-        // Count ones and zeros
-        let ones = encoded_qubits.iter().filter(|&&x| x).count();
-        let zeros = encoded_qubits.len() - ones;
-        
-        // Majority vote
-        Ok(ones > zeros)
+        let block = code_distance * code_distance;
+        if (body.len() * 8) % block != 0 {
+            return Err(ErrorCorrectionFailure::new(layer, 0, FailureReason::MalformedHeader));
+        }
+
+        let other;
+        let lattice = if code_distance == self.code_distance {
+            &self.lattice
+        } else {
+            other = Lattice::new(code_distance);
+            &other
+        };
+        let qubits = unpack_bits(body);
+        let logical_bits = qubits.len() / block;
+        if !logical_bits.is_multiple_of(8) {
+            return Err(ErrorCorrectionFailure::new(layer, 0, FailureReason::LengthMismatch));
+        }
+
+        let mut corrected = 0u32;
+        let mut decoded = Vec::with_capacity(logical_bits);
+        for encoded in qubits.chunks_exact(block) {
+            let (bit, flipped) = correct_block(lattice, self.decoder.as_ref(), encoded)
+                .map_err(|_| ErrorCorrectionFailure::new(layer, corrected, FailureReason::Uncorrectable))?;
+            corrected = corrected.saturating_add(flipped as u32);
+            decoded.push(bit);
+        }
+
+        Ok(LayerOutput {
+            data: pack_bits(&decoded),
+            report: LayerReport {
+                layer,
+                symbols_corrected: corrected,
+            },
+        })
+    }
+}
+
+/// Most significant bit first.
+fn unpack_bits(bytes: &[u8]) -> Vec<bool> {
+    bytes
+        .iter()
+        .flat_map(|byte| (0..8).rev().map(move |i| byte & (1 << i) != 0))
+        .collect()
+}
+
+/// Inverse of `unpack_bits`; a trailing partial byte is zero-padded.
+fn pack_bits(bits: &[bool]) -> Vec<u8> {
+    bits.chunks(8)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .enumerate()
+                .fold(0u8, |byte, (i, &bit)| if bit { byte | (0x80 >> i) } else { byte })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::HEADER_COPIES;
+    use decoder::UnionFindDecoder;
+
+    #[test]
+    fn corrects_up_to_half_the_distance() {
+        let qec = QuantumErrorCorrection::new(5);
+        for value in [false, true] {
+            let mut block = qec.encode_qubit(value);
+            block[6] ^= true;
+            block[18] ^= true;
+            assert_eq!(qec.correct_and_decode_counting(&block).unwrap(), (value, 2));
+        }
+    }
+
+    #[test]
+    fn frames_decode_with_any_decoder() {
+        let frame = QuantumErrorCorrection::new(7).encode(b"pH 3.1");
+        let mut damaged = frame.clone();
+        damaged[HEADER_COPIES + 4] ^= 0b0010_0100;
+
+        // A decoder configured for another distance follows the frame header
+        let qec = QuantumErrorCorrection::new(3).with_decoder(UnionFindDecoder);
+        let output = qec.decode(&damaged).unwrap();
+        assert_eq!(output.data, b"pH 3.1".to_vec());
+        assert_eq!(output.report.symbols_corrected, 2);
+    }
+
+    #[test]
+    fn rejects_invalid_distance_in_header() {
+        let mut frame = write_header(&[4]);
+        frame.extend_from_slice(&[0; 16]);
+        let failure = QuantumErrorCorrection::new(3).decode(&frame).unwrap_err();
+        assert_eq!(failure.reason, FailureReason::MalformedHeader);
     }
 }
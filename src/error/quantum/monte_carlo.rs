@@ -0,0 +1,105 @@
+// Monte Carlo estimate of the logical error rate
+//
+// Each trial prepares logical |0>, flips every data qubit independently with
+// the physical error rate (code-capacity bit-flip noise), decodes the Z
+// stabilizer syndrome and counts a logical error when the readout comes back
+// as 1. Below threshold the logical error rate should fall as the distance
+// grows, which is what makes the quantum layer worth having.
+use sp_std::prelude::*;
+
+use super::decoder::Decoder;
+use super::noise::{NoiseChannel, NoiseModel, Simulator};
+use super::surface_code::LogicalState;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MonteCarloResult {
+    pub distance: usize,
+    pub physical_error_rate: f64,
+    pub trials: usize,
+    pub logical_errors: usize,
+}
+
+impl MonteCarloResult {
+    pub fn logical_error_rate(&self) -> f64 {
+        if self.trials == 0 {
+            return 0.0;
+        }
+        self.logical_errors as f64 / self.trials as f64
+    }
+}
+
+/// Runs `trials` noisy memory experiments at one distance and error rate.
+/// The same `seed` always gives the same result.
+pub fn estimate_logical_error_rate<D: Decoder>(
+    distance: usize,
+    physical_error_rate: f64,
+    trials: usize,
+    decoder: &D,
+    seed: u64,
+) -> MonteCarloResult {
+    let model = NoiseModel::new().with_channel(NoiseChannel::BitFlip(physical_error_rate));
+    let summary = Simulator::new(distance, model, decoder, seed).run(LogicalState::Zero, trials);
+
+    MonteCarloResult {
+        distance,
+        physical_error_rate,
+        trials,
+        logical_errors: summary.logical_errors,
+    }
+}
+
+/// Estimates every combination of distance and physical error rate, in
+/// distance-major order. Each point gets its own seed derived from `seed`.
+pub fn sweep<D: Decoder>(
+    distances: &[usize],
+    physical_error_rates: &[f64],
+    trials: usize,
+    decoder: &D,
+    seed: u64,
+) -> Vec<MonteCarloResult> {
+    let mut results = Vec::with_capacity(distances.len() * physical_error_rates.len());
+    for &distance in distances {
+        for (index, &rate) in physical_error_rates.iter().enumerate() {
+            let point_seed = seed ^ ((distance as u64) << 32) ^ index as u64;
+            results.push(estimate_logical_error_rate(
+                distance, rate, trials, decoder, point_seed,
+            ));
+        }
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::quantum::decoder::{MinimumWeightDecoder, UnionFindDecoder};
+
+    fn assert_suppresses_errors<D: Decoder>(decoder: &D) {
+        let results = sweep(&[3, 5, 7], &[0.05], 4000, decoder, 7);
+        let rates: Vec<f64> = results.iter().map(|result| result.logical_error_rate()).collect();
+        assert!(
+            rates[0] < 0.05,
+            "d=3 logical error rate {} above physical",
+            rates[0]
+        );
+        assert!(rates[1] < rates[0], "d=5 not better than d=3: {:?}", rates);
+        assert!(rates[2] < rates[1], "d=7 not better than d=5: {:?}", rates);
+    }
+
+    #[test]
+    fn minimum_weight_matching_suppresses_errors() {
+        assert_suppresses_errors(&MinimumWeightDecoder);
+    }
+
+    #[test]
+    fn union_find_suppresses_errors() {
+        assert_suppresses_errors(&UnionFindDecoder);
+    }
+
+    #[test]
+    fn estimates_are_reproducible() {
+        let first = estimate_logical_error_rate(3, 0.1, 500, &MinimumWeightDecoder, 42);
+        let second = estimate_logical_error_rate(3, 0.1, 500, &MinimumWeightDecoder, 42);
+        assert_eq!(first, second);
+    }
+}
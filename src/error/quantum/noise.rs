@@ -0,0 +1,373 @@
+// Noise models for exercising the quantum layer
+//
+// A `NoiseModel` is a list of channels. Data channels act on every data qubit
+// of a patch; the measurement channel flips stabilizer outcomes as they are
+// read. All randomness comes from a caller-supplied RNG, so a seeded
+// `Simulator` replays exactly the same errors on every run.
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use sp_std::prelude::*;
+
+use super::decoder::Decoder;
+use super::matching::MatchingGraph;
+use super::surface_code::{EncodedQubit, LogicalState, PauliKind, RotatedSurfaceCode, Syndrome};
+
+/// A single noise channel and its probability per qubit or per outcome.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NoiseChannel {
+    /// X on each data qubit
+    BitFlip(f64),
+    /// Z on each data qubit
+    PhaseFlip(f64),
+    /// X, Y or Z, chosen uniformly, on each data qubit
+    Depolarizing(f64),
+    /// Lost data qubit, replaced by a maximally mixed one; the location is known
+    Erasure(f64),
+    /// Flipped stabilizer measurement outcome
+    MeasurementError(f64),
+}
+
+/// Errors one application of a noise model left behind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoiseEvents {
+    /// Pauli errors applied to data qubits, including erasures
+    pub data_errors: usize,
+    /// Heralded erasure locations
+    pub erased: Vec<bool>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NoiseModel {
+    channels: Vec<NoiseChannel>,
+}
+
+impl NoiseModel {
+    /// A noiseless model.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `channel`; channels act in the order they were added.
+    pub fn with_channel(mut self, channel: NoiseChannel) -> Self {
+        self.channels.push(channel);
+        self
+    }
+
+    pub fn channels(&self) -> &[NoiseChannel] {
+        &self.channels
+    }
+
+    /// Applies the data channels to every qubit of `patch`.
+    pub fn apply<R: RngCore>(&self, patch: &mut EncodedQubit, rng: &mut R) -> NoiseEvents {
+        let qubits = patch.x_frame.len();
+        let mut events = NoiseEvents {
+            data_errors: 0,
+            erased: vec![false; qubits],
+        };
+
+        for channel in &self.channels {
+            for qubit in 0..qubits {
+                let (x, z) = match *channel {
+                    NoiseChannel::BitFlip(p) if rng.gen_bool(p) => (true, false),
+                    NoiseChannel::PhaseFlip(p) if rng.gen_bool(p) => (false, true),
+                    NoiseChannel::Depolarizing(p) if rng.gen_bool(p) => match rng.gen_range(0..3) {
+                        0 => (true, false),
+                        1 => (true, true),
+                        _ => (false, true),
+                    },
+                    NoiseChannel::Erasure(p) if rng.gen_bool(p) => {
+                        events.erased[qubit] = true;
+                        (rng.gen_bool(0.5), rng.gen_bool(0.5))
+                    }
+                    _ => (false, false),
+                };
+                if x {
+                    patch.apply_x(qubit);
+                }
+                if z {
+                    patch.apply_z(qubit);
+                }
+                if x || z {
+                    events.data_errors += 1;
+                }
+            }
+        }
+        events
+    }
+
+    /// Applies the data channels to Z-basis readout bits, where only the X
+    /// part of an error is visible. Returns the number of flipped bits.
+    pub fn apply_to_bits<R: RngCore>(&self, bits: &mut [bool], rng: &mut R) -> usize {
+        let mut patch = EncodedQubit {
+            basis: PauliKind::Z,
+            x_frame: bits.to_vec(),
+            z_frame: vec![false; bits.len()],
+        };
+        self.apply(&mut patch, rng);
+
+        let mut flipped = 0;
+        for (bit, noisy) in bits.iter_mut().zip(patch.x_frame) {
+            if *bit != noisy {
+                *bit = noisy;
+                flipped += 1;
+            }
+        }
+        flipped
+    }
+
+    /// Measures the stabilizers of `patch`, flipping outcomes through the
+    /// measurement channels. Returns the syndrome and the number of flips.
+    pub fn measure<R: RngCore>(
+        &self,
+        code: &RotatedSurfaceCode,
+        patch: &EncodedQubit,
+        rng: &mut R,
+    ) -> (Syndrome, usize) {
+        let mut syndrome = code.syndrome(patch);
+        let mut flipped = 0;
+        for channel in &self.channels {
+            if let NoiseChannel::MeasurementError(p) = *channel {
+                for outcome in syndrome.x.iter_mut().chain(syndrome.z.iter_mut()) {
+                    if rng.gen_bool(p) {
+                        *outcome ^= true;
+                        flipped += 1;
+                    }
+                }
+            }
+        }
+        (syndrome, flipped)
+    }
+}
+
+/// What happened to one logical qubit in a simulated memory experiment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrialOutcome {
+    pub logical_error: bool,
+    pub data_errors: usize,
+    pub erasures: usize,
+    pub measurement_errors: usize,
+    /// Pauli flips applied by the decoder
+    pub corrections: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimulationSummary {
+    pub trials: usize,
+    pub logical_errors: usize,
+}
+
+impl SimulationSummary {
+    pub fn logical_error_rate(&self) -> f64 {
+        if self.trials == 0 {
+            return 0.0;
+        }
+        self.logical_errors as f64 / self.trials as f64
+    }
+}
+
+/// Encode -> noise -> syndrome -> decode on one surface-code patch.
+///
+/// Each trial prepares a logical state, applies the model's data channels
+/// once, then measures the stabilizers `syndrome_rounds` times through the
+/// measurement channel and decodes the per-stabilizer majority. The patch is
+/// finally read out by measuring the data qubits directly, which gives an
+/// exact syndrome for one last decoding pass, as in a memory experiment.
+pub struct Simulator<D: Decoder> {
+    code: RotatedSurfaceCode,
+    x_graph: MatchingGraph,
+    z_graph: MatchingGraph,
+    model: NoiseModel,
+    decoder: D,
+    rng: ChaCha8Rng,
+    syndrome_rounds: usize,
+}
+
+impl<D: Decoder> Simulator<D> {
+    pub fn new(distance: usize, model: NoiseModel, decoder: D, seed: u64) -> Self {
+        let code = RotatedSurfaceCode::new(distance);
+        let x_graph = MatchingGraph::new(&code, PauliKind::X);
+        let z_graph = MatchingGraph::new(&code, PauliKind::Z);
+        Self {
+            code,
+            x_graph,
+            z_graph,
+            model,
+            decoder,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            syndrome_rounds: 1,
+        }
+    }
+
+    /// Repeats every syndrome measurement; `rounds` must be odd.
+    pub fn with_syndrome_rounds(mut self, rounds: usize) -> Self {
+        assert!(rounds % 2 == 1, "syndrome rounds must be odd to take a majority");
+        self.syndrome_rounds = rounds;
+        self
+    }
+
+    pub fn code(&self) -> &RotatedSurfaceCode {
+        &self.code
+    }
+
+    pub fn run_trial(&mut self, state: LogicalState) -> TrialOutcome {
+        let mut patch = self.code.encode(state);
+        let events = self.model.apply(&mut patch, &mut self.rng);
+
+        let mut measurement_errors = 0;
+        let mut votes = Syndrome {
+            x: vec![false; self.code.stabilizers(PauliKind::X).len()],
+            z: vec![false; self.code.stabilizers(PauliKind::Z).len()],
+        };
+        let mut tallies = (vec![0usize; votes.x.len()], vec![0usize; votes.z.len()]);
+        for _ in 0..self.syndrome_rounds {
+            let (round, flipped) = self.model.measure(&self.code, &patch, &mut self.rng);
+            measurement_errors += flipped;
+            for (tally, &outcome) in tallies.0.iter_mut().zip(&round.x) {
+                *tally += outcome as usize;
+            }
+            for (tally, &outcome) in tallies.1.iter_mut().zip(&round.z) {
+                *tally += outcome as usize;
+            }
+        }
+        for (vote, &tally) in votes.x.iter_mut().zip(&tallies.0) {
+            *vote = tally * 2 > self.syndrome_rounds;
+        }
+        for (vote, &tally) in votes.z.iter_mut().zip(&tallies.1) {
+            *vote = tally * 2 > self.syndrome_rounds;
+        }
+
+        let mut corrections = self.correct(&mut patch, &votes, &events.erased);
+        let exact = self.code.syndrome(&patch);
+        corrections += self.correct(&mut patch, &exact, &events.erased);
+
+        TrialOutcome {
+            logical_error: self.code.measure_logical(&patch) != state.value(),
+            data_errors: events.data_errors,
+            erasures: events.erased.iter().filter(|&&erased| erased).count(),
+            measurement_errors,
+            corrections,
+        }
+    }
+
+    /// Runs `trials` trials of `state` and counts the logical errors.
+    pub fn run(&mut self, state: LogicalState, trials: usize) -> SimulationSummary {
+        let logical_errors = (0..trials)
+            .filter(|_| self.run_trial(state).logical_error)
+            .count();
+        SimulationSummary {
+            trials,
+            logical_errors,
+        }
+    }
+
+    fn correct(&self, patch: &mut EncodedQubit, syndrome: &Syndrome, erased: &[bool]) -> usize {
+        let mut applied = 0;
+        for graph in [&self.x_graph, &self.z_graph] {
+            let mask = self
+                .decoder
+                .correction_with_erasures(graph, syndrome.of(graph.kind()), erased);
+            for (qubit, _) in mask.iter().enumerate().filter(|(_, &flip)| flip) {
+                patch.apply(graph.kind().detects(), qubit);
+                applied += 1;
+            }
+        }
+        applied
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::quantum::decoder::{MinimumWeightDecoder, UnionFindDecoder};
+    use crate::error::QuantumErrorCorrection;
+
+    const STATES: [LogicalState; 4] = [
+        LogicalState::Zero,
+        LogicalState::One,
+        LogicalState::Plus,
+        LogicalState::Minus,
+    ];
+
+    #[test]
+    fn noiseless_model_never_fails() {
+        let mut simulator = Simulator::new(3, NoiseModel::new(), MinimumWeightDecoder, 1);
+        for state in STATES {
+            let outcome = simulator.run_trial(state);
+            assert!(!outcome.logical_error);
+            assert_eq!(outcome.corrections, 0);
+        }
+    }
+
+    #[test]
+    fn depolarizing_noise_is_suppressed_in_every_basis() {
+        let model = NoiseModel::new().with_channel(NoiseChannel::Depolarizing(0.03));
+        for state in STATES {
+            let mut simulator = Simulator::new(5, model.clone(), MinimumWeightDecoder, 11);
+            let summary = simulator.run(state, 2000);
+            assert!(
+                summary.logical_error_rate() < 0.03,
+                "{:?}: {}",
+                state,
+                summary.logical_error_rate()
+            );
+        }
+    }
+
+    #[test]
+    fn union_find_uses_erasure_locations() {
+        // Erasures are cheap to fix once their locations are used
+        let model = NoiseModel::new().with_channel(NoiseChannel::Erasure(0.15));
+        let aware = Simulator::new(5, model.clone(), UnionFindDecoder, 3).run(LogicalState::Zero, 2000);
+        let blind = Simulator::new(5, model, MinimumWeightDecoder, 3).run(LogicalState::Zero, 2000);
+        assert!(
+            aware.logical_errors < blind.logical_errors,
+            "{:?} vs {:?}",
+            aware,
+            blind
+        );
+    }
+
+    #[test]
+    fn repeated_rounds_outvote_measurement_errors() {
+        let model = NoiseModel::new()
+            .with_channel(NoiseChannel::BitFlip(0.01))
+            .with_channel(NoiseChannel::MeasurementError(0.05));
+        let single = Simulator::new(5, model.clone(), MinimumWeightDecoder, 5).run(LogicalState::One, 2000);
+        let repeated = Simulator::new(5, model, MinimumWeightDecoder, 5)
+            .with_syndrome_rounds(5)
+            .run(LogicalState::One, 2000);
+        assert!(repeated.logical_errors < single.logical_errors);
+        assert!(repeated.logical_error_rate() < 0.01);
+    }
+
+    #[test]
+    fn same_seed_replays_the_same_errors() {
+        let model = NoiseModel::new()
+            .with_channel(NoiseChannel::PhaseFlip(0.05))
+            .with_channel(NoiseChannel::MeasurementError(0.02));
+        let first: Vec<TrialOutcome> = {
+            let mut simulator = Simulator::new(3, model.clone(), UnionFindDecoder, 99);
+            (0..50).map(|_| simulator.run_trial(LogicalState::Plus)).collect()
+        };
+        let mut simulator = Simulator::new(3, model, UnionFindDecoder, 99);
+        let second: Vec<TrialOutcome> = (0..50).map(|_| simulator.run_trial(LogicalState::Plus)).collect();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn quantum_layer_blocks_survive_bit_flips() {
+        let qec = QuantumErrorCorrection::new(5);
+        let model = NoiseModel::new().with_channel(NoiseChannel::BitFlip(0.02));
+        let mut rng = ChaCha8Rng::seed_from_u64(2024);
+        let mut failures = 0;
+        for trial in 0..1000 {
+            let value = trial % 2 == 0;
+            let mut block = qec.encode_qubit(value);
+            model.apply_to_bits(&mut block, &mut rng);
+            if qec.correct_and_decode(&block).unwrap() != value {
+                failures += 1;
+            }
+        }
+        assert!(failures < 20, "{} failures", failures);
+    }
+}
@@ -0,0 +1,355 @@
+// Rotated surface code lattice
+//
+// Classical stabilizer simulation of a distance-d rotated surface code. Data
+// qubit (r, c) sits at index r * d + c. Plaquette (i, j), for 0 <= i, j <= d,
+// touches the data qubits at (i-1, j-1), (i-1, j), (i, j-1) and (i, j) that
+// exist. Plaquettes with i + j even are X stabilizers and the rest are Z
+// stabilizers; the weight-two X stabilizers run along the top and bottom edges
+// and the weight-two Z stabilizers along the left and right edges.
+//
+// A state is tracked as a logical basis state plus a Pauli frame: the X and Z
+// flips accumulated on each data qubit by encoding, noise and correction.
+use sp_std::prelude::*;
+
+/// Pauli operator type of a stabilizer or error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauliKind {
+    X,
+    Z,
+}
+
+impl PauliKind {
+    /// Stabilizers of this kind detect errors of the returned kind.
+    pub fn detects(self) -> PauliKind {
+        match self {
+            PauliKind::X => PauliKind::Z,
+            PauliKind::Z => PauliKind::X,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stabilizer {
+    pub kind: PauliKind,
+    /// Plaquette coordinates (i, j).
+    pub position: (usize, usize),
+    /// Data qubit indices in the support.
+    pub qubits: Vec<usize>,
+}
+
+/// Logical states the code can prepare.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogicalState {
+    Zero,
+    One,
+    Plus,
+    Minus,
+}
+
+impl LogicalState {
+    /// Basis the state is prepared and read out in.
+    pub fn basis(self) -> PauliKind {
+        match self {
+            LogicalState::Zero | LogicalState::One => PauliKind::Z,
+            LogicalState::Plus | LogicalState::Minus => PauliKind::X,
+        }
+    }
+
+    /// Eigenvalue bit: `false` for |0> and |+>, `true` for |1> and |->.
+    pub fn value(self) -> bool {
+        matches!(self, LogicalState::One | LogicalState::Minus)
+    }
+}
+
+/// An encoded logical qubit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodedQubit {
+    /// Basis the logical qubit was prepared in.
+    pub basis: PauliKind,
+    /// X flips on each data qubit.
+    pub x_frame: Vec<bool>,
+    /// Z flips on each data qubit.
+    pub z_frame: Vec<bool>,
+}
+
+impl EncodedQubit {
+    pub fn apply_x(&mut self, qubit: usize) {
+        self.x_frame[qubit] ^= true;
+    }
+
+    pub fn apply_z(&mut self, qubit: usize) {
+        self.z_frame[qubit] ^= true;
+    }
+
+    pub fn apply_y(&mut self, qubit: usize) {
+        self.apply_x(qubit);
+        self.apply_z(qubit);
+    }
+
+    pub fn apply(&mut self, kind: PauliKind, qubit: usize) {
+        match kind {
+            PauliKind::X => self.apply_x(qubit),
+            PauliKind::Z => self.apply_z(qubit),
+        }
+    }
+
+    pub fn frame(&self, kind: PauliKind) -> &[bool] {
+        match kind {
+            PauliKind::X => &self.x_frame,
+            PauliKind::Z => &self.z_frame,
+        }
+    }
+}
+
+/// Outcomes of one round of stabilizer measurements; `true` marks a -1 outcome.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Syndrome {
+    /// X stabilizer outcomes, in `RotatedSurfaceCode::stabilizers(PauliKind::X)` order.
+    pub x: Vec<bool>,
+    /// Z stabilizer outcomes, in `RotatedSurfaceCode::stabilizers(PauliKind::Z)` order.
+    pub z: Vec<bool>,
+}
+
+impl Syndrome {
+    pub fn of(&self, kind: PauliKind) -> &[bool] {
+        match kind {
+            PauliKind::X => &self.x,
+            PauliKind::Z => &self.z,
+        }
+    }
+
+    pub fn is_trivial(&self) -> bool {
+        self.x.iter().chain(self.z.iter()).all(|&flag| !flag)
+    }
+}
+
+pub struct RotatedSurfaceCode {
+    distance: usize,
+    x_stabilizers: Vec<Stabilizer>,
+    z_stabilizers: Vec<Stabilizer>,
+    /// Support of the logical X operator (left column).
+    logical_x: Vec<usize>,
+    /// Support of the logical Z operator (top row).
+    logical_z: Vec<usize>,
+}
+
+impl RotatedSurfaceCode {
+    /// Builds the lattice. Panics unless `distance` is odd and at least 3.
+    pub fn new(distance: usize) -> Self {
+        assert!(
+            distance >= 3 && distance % 2 == 1,
+            "rotated surface code distance must be odd and at least 3"
+        );
+
+        let d = distance;
+        let mut x_stabilizers = Vec::new();
+        let mut z_stabilizers = Vec::new();
+        for i in 0..=d {
+            for j in 0..=d {
+                let kind = if (i + j) % 2 == 0 {
+                    PauliKind::X
+                } else {
+                    PauliKind::Z
+                };
+                let vertical_edge = i == 0 || i == d;
+                let horizontal_edge = j == 0 || j == d;
+                let keep = match (vertical_edge, horizontal_edge) {
+                    (false, false) => true,
+                    (true, false) => kind == PauliKind::X,
+                    (false, true) => kind == PauliKind::Z,
+                    (true, true) => false,
+                };
+                if !keep {
+                    continue;
+                }
+
+                let qubits = [
+                    (i.wrapping_sub(1), j.wrapping_sub(1)),
+                    (i.wrapping_sub(1), j),
+                    (i, j.wrapping_sub(1)),
+                    (i, j),
+                ]
+                .iter()
+                .filter(|&&(r, c)| r < d && c < d)
+                .map(|&(r, c)| r * d + c)
+                .collect();
+                let stabilizer = Stabilizer {
+                    kind,
+                    position: (i, j),
+                    qubits,
+                };
+                match kind {
+                    PauliKind::X => x_stabilizers.push(stabilizer),
+                    PauliKind::Z => z_stabilizers.push(stabilizer),
+                }
+            }
+        }
+
+        Self {
+            distance,
+            x_stabilizers,
+            z_stabilizers,
+            logical_x: (0..d).map(|r| r * d).collect(),
+            logical_z: (0..d).collect(),
+        }
+    }
+
+    pub fn distance(&self) -> usize {
+        self.distance
+    }
+
+    pub fn data_qubits(&self) -> usize {
+        self.distance * self.distance
+    }
+
+    pub fn stabilizers(&self, kind: PauliKind) -> &[Stabilizer] {
+        match kind {
+            PauliKind::X => &self.x_stabilizers,
+            PauliKind::Z => &self.z_stabilizers,
+        }
+    }
+
+    /// Support of the logical operator of the given kind.
+    pub fn logical(&self, kind: PauliKind) -> &[usize] {
+        match kind {
+            PauliKind::X => &self.logical_x,
+            PauliKind::Z => &self.logical_z,
+        }
+    }
+
+    /// Prepares a logical state. |0> and |+> start with an empty Pauli frame;
+    /// |1> and |-> apply the logical X or Z operator to it.
+    pub fn encode(&self, state: LogicalState) -> EncodedQubit {
+        let mut encoded = EncodedQubit {
+            basis: state.basis(),
+            x_frame: vec![false; self.data_qubits()],
+            z_frame: vec![false; self.data_qubits()],
+        };
+        if state.value() {
+            let flip = state.basis().detects();
+            for &qubit in self.logical(flip) {
+                encoded.apply(flip, qubit);
+            }
+        }
+        encoded
+    }
+
+    /// Measures every stabilizer against the data qubits. An X stabilizer
+    /// flags an odd number of Z flips on its support and vice versa.
+    pub fn syndrome(&self, encoded: &EncodedQubit) -> Syndrome {
+        let measure = |kind: PauliKind| -> Vec<bool> {
+            let frame = encoded.frame(kind.detects());
+            self.stabilizers(kind)
+                .iter()
+                .map(|stabilizer| {
+                    stabilizer
+                        .qubits
+                        .iter()
+                        .fold(false, |parity, &q| parity ^ frame[q])
+                })
+                .collect()
+        };
+
+        Syndrome {
+            x: measure(PauliKind::X),
+            z: measure(PauliKind::Z),
+        }
+    }
+
+    /// Destructively reads out the logical qubit in its preparation basis.
+    /// Errors that form a logical operator flip the result.
+    pub fn measure_logical(&self, encoded: &EncodedQubit) -> bool {
+        let frame = encoded.frame(encoded.basis.detects());
+        self.logical(encoded.basis)
+            .iter()
+            .fold(false, |parity, &q| parity ^ frame[q])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overlap(a: &[usize], b: &[usize]) -> usize {
+        a.iter().filter(|q| b.contains(q)).count()
+    }
+
+    #[test]
+    fn lattice_has_expected_stabilizers() {
+        for d in [3, 5, 7] {
+            let code = RotatedSurfaceCode::new(d);
+            let x = code.stabilizers(PauliKind::X);
+            let z = code.stabilizers(PauliKind::Z);
+            assert_eq!(x.len(), (d * d - 1) / 2);
+            assert_eq!(z.len(), (d * d - 1) / 2);
+
+            // Every data qubit is checked by one or two stabilizers of each kind
+            for q in 0..code.data_qubits() {
+                for kind in [PauliKind::X, PauliKind::Z] {
+                    let checks = code
+                        .stabilizers(kind)
+                        .iter()
+                        .filter(|s| s.qubits.contains(&q))
+                        .count();
+                    assert!(
+                        checks == 1 || checks == 2,
+                        "qubit {} has {} {:?} checks",
+                        q,
+                        checks,
+                        kind
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn stabilizers_and_logicals_commute_as_required() {
+        let code = RotatedSurfaceCode::new(5);
+        for sx in code.stabilizers(PauliKind::X) {
+            for sz in code.stabilizers(PauliKind::Z) {
+                assert_eq!(overlap(&sx.qubits, &sz.qubits) % 2, 0);
+            }
+            assert_eq!(overlap(&sx.qubits, code.logical(PauliKind::Z)) % 2, 0);
+        }
+        for sz in code.stabilizers(PauliKind::Z) {
+            assert_eq!(overlap(&sz.qubits, code.logical(PauliKind::X)) % 2, 0);
+        }
+        assert_eq!(
+            overlap(code.logical(PauliKind::X), code.logical(PauliKind::Z)) % 2,
+            1
+        );
+    }
+
+    #[test]
+    fn encoded_states_have_trivial_syndrome_and_read_out_correctly() {
+        let code = RotatedSurfaceCode::new(3);
+        for state in [
+            LogicalState::Zero,
+            LogicalState::One,
+            LogicalState::Plus,
+            LogicalState::Minus,
+        ] {
+            let encoded = code.encode(state);
+            assert!(code.syndrome(&encoded).is_trivial());
+            assert_eq!(code.measure_logical(&encoded), state.value());
+        }
+    }
+
+    #[test]
+    fn single_errors_light_up_neighbouring_stabilizers() {
+        let code = RotatedSurfaceCode::new(3);
+        let mut encoded = code.encode(LogicalState::Zero);
+        encoded.apply_x(4);
+
+        let syndrome = code.syndrome(&encoded);
+        assert!(syndrome.x.iter().all(|&flag| !flag));
+        assert_eq!(syndrome.z.iter().filter(|&&flag| flag).count(), 2);
+
+        encoded.apply_x(4);
+        encoded.apply_z(4);
+        let syndrome = code.syndrome(&encoded);
+        assert!(syndrome.z.iter().all(|&flag| !flag));
+        assert_eq!(syndrome.x.iter().filter(|&&flag| flag).count(), 2);
+    }
+}
@@ -0,0 +1,188 @@
+// Union-find decoder
+//
+// Clusters start at the defects and grow by half an edge per round while they
+// hold an odd number of defects and have not reached the boundary. Edges that
+// become fully grown merge the clusters at their ends. Once every cluster is
+// neutral, a spanning forest of the grown edges is peeled from the leaves
+// inwards to find the correction. Runs in almost linear time, at the cost of
+// occasionally choosing a heavier correction than minimum-weight matching.
+// Erased qubits are known locations, so their edges start out fully grown.
+use sp_std::prelude::*;
+
+use super::decoder::Decoder;
+use super::matching::MatchingGraph;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UnionFindDecoder;
+
+impl Decoder for UnionFindDecoder {
+    fn correction(&self, graph: &MatchingGraph, syndrome: &[bool]) -> Vec<bool> {
+        self.correction_with_erasures(graph, syndrome, &vec![false; graph.qubits()])
+    }
+
+    fn correction_with_erasures(
+        &self,
+        graph: &MatchingGraph,
+        syndrome: &[bool],
+        erased: &[bool],
+    ) -> Vec<bool> {
+        let grown = grow_clusters(graph, syndrome, erased);
+        peel(graph, syndrome, &grown)
+    }
+}
+
+struct Clusters {
+    parent: Vec<usize>,
+    /// Odd number of defects, tracked on the root
+    odd: Vec<bool>,
+    /// Contains the boundary node, tracked on the root
+    boundary: Vec<bool>,
+}
+
+impl Clusters {
+    fn find(&mut self, node: usize) -> usize {
+        let mut root = node;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        let mut node = node;
+        while self.parent[node] != root {
+            let next = self.parent[node];
+            self.parent[node] = root;
+            node = next;
+        }
+        root
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parent[b] = a;
+            self.odd[a] ^= self.odd[b];
+            self.boundary[a] |= self.boundary[b];
+        }
+    }
+
+    fn active(&mut self, node: usize) -> bool {
+        let root = self.find(node);
+        self.odd[root] && !self.boundary[root]
+    }
+}
+
+/// Grows clusters until each is neutral and returns the fully grown edges.
+fn grow_clusters(graph: &MatchingGraph, syndrome: &[bool], erased: &[bool]) -> Vec<bool> {
+    let boundary = graph.boundary();
+    let mut clusters = Clusters {
+        parent: (0..graph.nodes()).collect(),
+        odd: (0..graph.nodes())
+            .map(|node| node != boundary && syndrome[node])
+            .collect(),
+        boundary: (0..graph.nodes()).map(|node| node == boundary).collect(),
+    };
+    let mut support = vec![0u8; graph.qubits()];
+    for qubit in (0..graph.qubits()).filter(|&qubit| erased[qubit]) {
+        support[qubit] = 2;
+        let (a, b) = graph.edge(qubit);
+        clusters.union(a, b);
+    }
+
+    loop {
+        let mut growth = Vec::new();
+        for (qubit, &grown) in support.iter().enumerate() {
+            if grown < 2 {
+                let (a, b) = graph.edge(qubit);
+                let halves = clusters.active(a) as u8 + clusters.active(b) as u8;
+                if halves > 0 {
+                    growth.push((qubit, halves));
+                }
+            }
+        }
+        if growth.is_empty() {
+            break;
+        }
+
+        for (qubit, halves) in growth {
+            support[qubit] = (support[qubit] + halves).min(2);
+            if support[qubit] == 2 {
+                let (a, b) = graph.edge(qubit);
+                clusters.union(a, b);
+            }
+        }
+    }
+
+    support.into_iter().map(|grown| grown == 2).collect()
+}
+
+/// Peels a spanning forest of the grown edges, rooted at the boundary where
+/// a tree reaches it, flipping the edge above every leaf that holds a defect.
+fn peel(graph: &MatchingGraph, syndrome: &[bool], grown: &[bool]) -> Vec<bool> {
+    let boundary = graph.boundary();
+    let mut adjacency = vec![Vec::new(); graph.nodes()];
+    for qubit in (0..graph.qubits()).filter(|&qubit| grown[qubit]) {
+        let (a, b) = graph.edge(qubit);
+        adjacency[a].push((b, qubit));
+        adjacency[b].push((a, qubit));
+    }
+
+    let mut visited = vec![false; graph.nodes()];
+    let mut parent = vec![None; graph.nodes()];
+    let mut order = Vec::with_capacity(graph.nodes());
+    let roots = core::iter::once(boundary).chain(0..boundary);
+    for root in roots {
+        if visited[root] {
+            continue;
+        }
+        visited[root] = true;
+        let start = order.len();
+        order.push(root);
+        let mut next = start;
+        while next < order.len() {
+            let node = order[next];
+            next += 1;
+            for &(neighbour, qubit) in &adjacency[node] {
+                if !visited[neighbour] {
+                    visited[neighbour] = true;
+                    parent[neighbour] = Some((node, qubit));
+                    order.push(neighbour);
+                }
+            }
+        }
+    }
+
+    let mut defect: Vec<bool> = (0..graph.nodes())
+        .map(|node| node != boundary && syndrome[node])
+        .collect();
+    let mut correction = vec![false; graph.qubits()];
+    for &node in order.iter().rev() {
+        if let (true, Some((up, qubit))) = (defect[node], parent[node]) {
+            correction[qubit] ^= true;
+            defect[node] = false;
+            defect[up] ^= true;
+        }
+    }
+    correction
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::quantum::surface_code::{LogicalState, PauliKind, RotatedSurfaceCode};
+
+    #[test]
+    fn corrects_every_single_error() {
+        let code = RotatedSurfaceCode::new(5);
+        let graph = MatchingGraph::new(&code, PauliKind::Z);
+        for qubit in 0..code.data_qubits() {
+            let mut encoded = code.encode(LogicalState::One);
+            encoded.apply_x(qubit);
+
+            let correction = UnionFindDecoder.correction(&graph, &code.syndrome(&encoded).z);
+            assert_eq!(correction.iter().filter(|&&flip| flip).count(), 1);
+            for (qubit, _) in correction.iter().enumerate().filter(|(_, &flip)| flip) {
+                encoded.apply_x(qubit);
+            }
+            assert!(code.syndrome(&encoded).is_trivial());
+            assert!(code.measure_logical(&encoded));
+        }
+    }
+}
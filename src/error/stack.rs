@@ -0,0 +1,342 @@
+// Layers and the stack that chains them
+//
+// Everything here allocates, so it is only built with the `alloc` feature.
+use codec::{Decode, Encode, MaxEncodedLen};
+use core::fmt;
+use scale_info::TypeInfo;
+use sp_std::prelude::*;
+
+use super::{BridgeErrorCorrection, ClassicalErrorCorrection, QuantumErrorCorrection};
+
+/// Copies of each layer header kept for majority voting.
+pub(crate) const HEADER_COPIES: usize = 3;
+
+/// One error-correction layer.
+///
+/// `encode` produces a self-describing frame: the layer parameters travel in a
+/// small header, so `decode` works on any frame of this layer regardless of
+/// how the decoding instance was configured.
+pub trait ErrorCorrector {
+    /// Layer named in reports and failures.
+    fn layer(&self) -> CorrectionLayer;
+
+    /// Protects `data` and returns the frame to transmit.
+    fn encode(&self, data: &[u8]) -> Vec<u8>;
+
+    /// Repairs a frame produced by `encode` and returns the inner data.
+    fn decode(&self, frame: &[u8]) -> Result<LayerOutput, ErrorCorrectionFailure>;
+
+    /// Whether `frame` decodes without needing any correction.
+    fn verify(&self, frame: &[u8]) -> bool {
+        matches!(self.decode(frame), Ok(output) if output.report.symbols_corrected == 0)
+    }
+}
+
+/// Parameters for the standard three-layer stack.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct CorrectionConfig {
+    /// Reed-Solomon data shards
+    pub data_shards: u8,
+    /// Reed-Solomon parity shards
+    pub parity_shards: u8,
+    /// Copies sent across the bridge
+    pub redundancy_factor: u8,
+    /// Code distance of the quantum layer, odd and at most `quantum::MAX_CODE_DISTANCE`
+    pub code_distance: u8,
+}
+
+impl Default for CorrectionConfig {
+    fn default() -> Self {
+        Self {
+            data_shards: 8,
+            parity_shards: 4,
+            redundancy_factor: 3,
+            code_distance: 3,
+        }
+    }
+}
+
+/// Ordered set of layers, outermost first.
+pub struct LayerStack {
+    layers: Vec<Box<dyn ErrorCorrector>>,
+}
+
+impl LayerStack {
+    /// An empty stack, which passes data through unchanged.
+    pub fn new() -> Self {
+        Self { layers: Vec::new() }
+    }
+
+    /// Classical, bridge and quantum layers built from `config`.
+    pub fn from_config(config: &CorrectionConfig) -> Self {
+        Self::new()
+            .with_layer(ClassicalErrorCorrection::new(
+                config.data_shards as usize,
+                config.parity_shards as usize,
+            ))
+            .with_layer(BridgeErrorCorrection::new(config.redundancy_factor as usize, 1))
+            .with_layer(QuantumErrorCorrection::new(config.code_distance as usize))
+    }
+
+    /// Adds `layer` inside the ones already on the stack.
+    pub fn with_layer<L>(mut self, layer: L) -> Self
+    where
+        L: ErrorCorrector + 'static,
+    {
+        self.layers.push(Box::new(layer));
+        self
+    }
+
+    pub fn layers(&self) -> impl Iterator<Item = CorrectionLayer> + '_ {
+        self.layers.iter().map(|layer| layer.layer())
+    }
+
+    /// Applies the layers innermost first, so the outermost wraps the frame.
+    pub fn encode(&self, payload: &[u8]) -> Vec<u8> {
+        self.layers
+            .iter()
+            .rev()
+            .fold(payload.to_vec(), |data, layer| layer.encode(&data))
+    }
+
+    /// Peels the layers outermost first, stopping at the first failure.
+    pub fn decode(&self, frame: &[u8]) -> Result<CorrectionOutcome, ErrorCorrectionFailure> {
+        self.decode_observed(frame, |_| {})
+    }
+
+    /// Like `decode`, also handing each layer's report, or the failure that
+    /// stopped the stack, to `observe` as the layers are peeled.
+    pub fn decode_observed<F>(
+        &self,
+        frame: &[u8],
+        mut observe: F,
+    ) -> Result<CorrectionOutcome, ErrorCorrectionFailure>
+    where
+        F: FnMut(Result<&LayerReport, &ErrorCorrectionFailure>),
+    {
+        let mut data = frame.to_vec();
+        let mut reports = Vec::with_capacity(self.layers.len());
+        for layer in &self.layers {
+            let output = layer.decode(&data).inspect_err(|failure| observe(Err(failure)))?;
+            observe(Ok(&output.report));
+            data = output.data;
+            reports.push(output.report);
+        }
+
+        Ok(CorrectionOutcome {
+            payload: data,
+            reports,
+        })
+    }
+
+    /// Whether `frame` decodes through every layer without any correction.
+    pub fn verify(&self, frame: &[u8]) -> bool {
+        matches!(self.decode(frame), Ok(outcome) if outcome.symbols_corrected() == 0)
+    }
+}
+
+impl Default for LayerStack {
+    fn default() -> Self {
+        Self::from_config(&CorrectionConfig::default())
+    }
+}
+
+/// Output of a single layer: the repaired inner data and what was fixed.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct LayerOutput {
+    pub data: Vec<u8>,
+    pub report: LayerReport,
+}
+
+/// Corrections made by one layer.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct LayerReport {
+    pub layer: CorrectionLayer,
+    pub symbols_corrected: u32,
+}
+
+/// Result of decoding a frame through a `LayerStack`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CorrectionOutcome {
+    pub payload: Vec<u8>,
+    /// One report per layer, outermost first.
+    pub reports: Vec<LayerReport>,
+}
+
+impl CorrectionOutcome {
+    /// Report for `layer`, if the stack contains it.
+    pub fn report(&self, layer: CorrectionLayer) -> Option<LayerReport> {
+        self.reports.iter().copied().find(|report| report.layer == layer)
+    }
+
+    /// Total symbols corrected across all layers.
+    pub fn symbols_corrected(&self) -> u32 {
+        self.reports
+            .iter()
+            .fold(0u32, |total, report| total.saturating_add(report.symbols_corrected))
+    }
+}
+
+/// The layer an error correction failure came from
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Encode, Decode, MaxEncodedLen, TypeInfo)]
+pub enum CorrectionLayer {
+    Classical,
+    Bridge,
+    Quantum,
+}
+
+impl fmt::Display for CorrectionLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CorrectionLayer::Classical => write!(f, "classical"),
+            CorrectionLayer::Bridge => write!(f, "bridge"),
+            CorrectionLayer::Quantum => write!(f, "quantum"),
+        }
+    }
+}
+
+/// Why a layer gave up on a frame
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Encode, Decode, MaxEncodedLen, TypeInfo)]
+pub enum FailureReason {
+    /// The frame was empty
+    EmptyFrame,
+    /// The layer header or frame layout is invalid
+    MalformedHeader,
+    /// The decoded length does not match the data available
+    LengthMismatch,
+    /// More damage than the layer can repair
+    Uncorrectable,
+    /// Redundant copies did not agree
+    NoConsensus,
+    /// The frame never arrived
+    Missing,
+}
+
+impl fmt::Display for FailureReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FailureReason::EmptyFrame => write!(f, "empty frame"),
+            FailureReason::MalformedHeader => write!(f, "malformed header"),
+            FailureReason::LengthMismatch => write!(f, "length mismatch"),
+            FailureReason::Uncorrectable => write!(f, "damage beyond repair"),
+            FailureReason::NoConsensus => write!(f, "no consensus across redundant copies"),
+            FailureReason::Missing => write!(f, "frame missing"),
+        }
+    }
+}
+
+/// Error correction failure details
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct ErrorCorrectionFailure {
+    /// Layer that failed
+    pub layer: CorrectionLayer,
+    /// Symbols the failing layer had already corrected
+    pub symbols_corrected: u32,
+    /// Why the layer failed
+    pub reason: FailureReason,
+}
+
+impl ErrorCorrectionFailure {
+    pub fn new(layer: CorrectionLayer, symbols_corrected: u32, reason: FailureReason) -> Self {
+        Self {
+            layer,
+            symbols_corrected,
+            reason,
+        }
+    }
+}
+
+impl fmt::Display for ErrorCorrectionFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} error correction failed after {} corrected symbols: {}",
+            self.layer, self.symbols_corrected, self.reason
+        )
+    }
+}
+
+/// Writes `HEADER_COPIES` back-to-back copies of `fields`.
+pub(crate) fn write_header(fields: &[u8]) -> Vec<u8> {
+    let mut header = Vec::with_capacity(fields.len() * HEADER_COPIES);
+    for _ in 0..HEADER_COPIES {
+        header.extend_from_slice(fields);
+    }
+    header
+}
+
+/// Majority-votes a `len`-byte header and returns it along with the frame body.
+pub(crate) fn read_header(
+    frame: &[u8],
+    len: usize,
+    layer: CorrectionLayer,
+) -> Result<(Vec<u8>, &[u8]), ErrorCorrectionFailure> {
+    if frame.is_empty() {
+        return Err(ErrorCorrectionFailure::new(layer, 0, FailureReason::EmptyFrame));
+    }
+    if frame.len() < len * HEADER_COPIES {
+        return Err(ErrorCorrectionFailure::new(layer, 0, FailureReason::MalformedHeader));
+    }
+
+    let mut header = Vec::with_capacity(len);
+    for field in 0..len {
+        let votes: Vec<u8> = (0..HEADER_COPIES).map(|copy| frame[copy * len + field]).collect();
+        let winner = votes
+            .iter()
+            .copied()
+            .find(|candidate| votes.iter().filter(|vote| *vote == candidate).count() * 2 > HEADER_COPIES)
+            .ok_or_else(|| ErrorCorrectionFailure::new(layer, 0, FailureReason::MalformedHeader))?;
+        header.push(winner);
+    }
+
+    Ok((header, &frame[len * HEADER_COPIES..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_stack_roundtrips() {
+        let stack = LayerStack::default();
+        assert_eq!(
+            stack.layers().collect::<Vec<_>>(),
+            vec![CorrectionLayer::Classical, CorrectionLayer::Bridge, CorrectionLayer::Quantum]
+        );
+
+        let frame = stack.encode(b"SCOBY-7 gen 12");
+        assert!(stack.verify(&frame));
+        let outcome = stack.decode(&frame).unwrap();
+        assert_eq!(outcome.payload, b"SCOBY-7 gen 12".to_vec());
+        assert_eq!(outcome.reports.len(), 3);
+    }
+
+    #[test]
+    fn custom_stack_uses_only_its_layers() {
+        let stack = LayerStack::new().with_layer(ClassicalErrorCorrection::new(4, 2));
+        let mut frame = stack.encode(b"batch");
+        frame[HEADER_COPIES * 2] ^= 0x80;
+
+        assert!(!stack.verify(&frame));
+        let outcome = stack.decode(&frame).unwrap();
+        assert_eq!(outcome.payload, b"batch".to_vec());
+        assert_eq!(outcome.report(CorrectionLayer::Classical).unwrap().symbols_corrected, 1);
+        assert_eq!(outcome.report(CorrectionLayer::Quantum), None);
+    }
+
+    #[test]
+    fn empty_stack_is_identity() {
+        let stack = LayerStack::new();
+        assert_eq!(stack.encode(b"raw"), b"raw".to_vec());
+        assert_eq!(stack.decode(b"raw").unwrap().payload, b"raw".to_vec());
+    }
+
+    #[test]
+    fn header_survives_one_corrupted_copy() {
+        let mut frame = write_header(&[8, 4]);
+        frame[2] = 0;
+        let (header, body) = read_header(&frame, 2, CorrectionLayer::Classical).unwrap();
+        assert_eq!(header, vec![8, 4]);
+        assert!(body.is_empty());
+    }
+}
@@ -0,0 +1,190 @@
+// Per-source error-correction counters
+//
+// `CorrectionStats` counts, for every source and layer, the frames that
+// reached the layer, the symbols it corrected and the frames it could not
+// recover. A source is whatever the caller tells links apart by, such as a
+// device ID or a chain. Feed it from `LayerStack::decode_observed`, or go
+// through `CorrectionStats::decode`, so that the outer layers of a frame that
+// fails further in are still counted.
+use codec::{Decode, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
+use sp_std::collections::btree_map::BTreeMap;
+use sp_std::prelude::*;
+
+use super::adaptive::PPM;
+use super::{CorrectionLayer, CorrectionOutcome, ErrorCorrectionFailure, LayerReport, LayerStack};
+
+/// What one layer has seen from one source.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Encode, Decode, MaxEncodedLen, TypeInfo)]
+pub struct LayerCounters {
+    /// Frames that reached the layer
+    pub frames: u64,
+    /// Symbols the layer corrected, including in frames it then gave up on
+    pub symbols_corrected: u64,
+    /// Frames the layer could not recover
+    pub unrecoverable: u64,
+}
+
+impl LayerCounters {
+    /// Counts one frame's result at this layer.
+    pub fn record(&mut self, result: Result<&LayerReport, &ErrorCorrectionFailure>) {
+        let corrected = match result {
+            Ok(report) => report.symbols_corrected,
+            Err(failure) => {
+                self.unrecoverable = self.unrecoverable.saturating_add(1);
+                failure.symbols_corrected
+            }
+        };
+        self.frames = self.frames.saturating_add(1);
+        self.symbols_corrected = self.symbols_corrected.saturating_add(corrected as u64);
+    }
+
+    /// Adds another set of counters to these.
+    pub fn merge(&mut self, other: &LayerCounters) {
+        self.frames = self.frames.saturating_add(other.frames);
+        self.symbols_corrected = self.symbols_corrected.saturating_add(other.symbols_corrected);
+        self.unrecoverable = self.unrecoverable.saturating_add(other.unrecoverable);
+    }
+
+    /// Frames per million that the layer could not recover.
+    pub fn failure_rate_ppm(&self) -> u32 {
+        (self.unrecoverable as u128 * PPM as u128 / self.frames.max(1) as u128) as u32
+    }
+}
+
+/// Counters for every layer, broken down by source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CorrectionStats<S> {
+    counters: BTreeMap<(S, CorrectionLayer), LayerCounters>,
+}
+
+impl<S: Ord + Clone> CorrectionStats<S> {
+    pub fn new() -> Self {
+        Self {
+            counters: BTreeMap::new(),
+        }
+    }
+
+    /// Counts one layer's result for a frame from `source`.
+    pub fn record(&mut self, source: &S, result: Result<&LayerReport, &ErrorCorrectionFailure>) {
+        let layer = match result {
+            Ok(report) => report.layer,
+            Err(failure) => failure.layer,
+        };
+        self.counters
+            .entry((source.clone(), layer))
+            .or_default()
+            .record(result);
+    }
+
+    /// Decodes `frame` through `stack`, counting every layer it reaches.
+    pub fn decode(
+        &mut self,
+        stack: &LayerStack,
+        source: &S,
+        frame: &[u8],
+    ) -> Result<CorrectionOutcome, ErrorCorrectionFailure> {
+        stack.decode_observed(frame, |result| self.record(source, result))
+    }
+
+    /// What `layer` has seen from `source`.
+    pub fn counters(&self, source: &S, layer: CorrectionLayer) -> LayerCounters {
+        self.counters
+            .get(&(source.clone(), layer))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// What `layer` has seen across all sources.
+    pub fn layer_totals(&self, layer: CorrectionLayer) -> LayerCounters {
+        self.counters
+            .iter()
+            .filter(|((_, counted), _)| *counted == layer)
+            .fold(LayerCounters::default(), |mut totals, (_, counters)| {
+                totals.merge(counters);
+                totals
+            })
+    }
+
+    /// Every source and layer counted so far, ordered by source.
+    pub fn iter(&self) -> impl Iterator<Item = (&S, CorrectionLayer, &LayerCounters)> + '_ {
+        self.counters
+            .iter()
+            .map(|((source, layer), counters)| (source, *layer, counters))
+    }
+
+    /// Sources with a layer losing more than `threshold_ppm` of its frames.
+    pub fn failing_sources(&self, threshold_ppm: u32) -> Vec<(&S, CorrectionLayer)> {
+        self.iter()
+            .filter(|(_, _, counters)| counters.failure_rate_ppm() > threshold_ppm)
+            .map(|(source, layer, _)| (source, layer))
+            .collect()
+    }
+
+    /// Forgets everything counted for `source`.
+    pub fn reset(&mut self, source: &S) {
+        self.counters.retain(|(counted, _), _| counted != source);
+    }
+}
+
+impl<S: Ord + Clone> Default for CorrectionStats<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{FailureReason, HEADER_COPIES};
+
+    #[test]
+    fn counts_each_layer_a_frame_reaches() {
+        let stack = LayerStack::default();
+        let mut stats = CorrectionStats::new();
+        let mut frame = stack.encode(b"pH 3.1");
+        frame[HEADER_COPIES * 2 + 5] ^= 0x11;
+
+        stats.decode(&stack, &"ELXR-01", &frame).unwrap();
+        stats.decode(&stack, &"ELXR-01", &[]).unwrap_err();
+        stats.decode(&stack, &"ELXR-02", &stack.encode(b"pH 3.4")).unwrap();
+
+        assert_eq!(
+            stats.counters(&"ELXR-01", CorrectionLayer::Classical),
+            LayerCounters {
+                frames: 2,
+                symbols_corrected: 1,
+                unrecoverable: 1,
+            }
+        );
+        // The empty frame never got past the classical layer
+        assert_eq!(stats.counters(&"ELXR-01", CorrectionLayer::Quantum).frames, 1);
+        assert_eq!(stats.layer_totals(CorrectionLayer::Bridge).frames, 2);
+        assert_eq!(
+            stats.failing_sources(PPM / 4),
+            vec![(&"ELXR-01", CorrectionLayer::Classical)]
+        );
+
+        stats.reset(&"ELXR-01");
+        assert_eq!(stats.iter().count(), 3);
+    }
+
+    #[test]
+    fn counters_record_reports_and_failures() {
+        let mut counters = LayerCounters::default();
+        counters.record(Ok(&LayerReport {
+            layer: CorrectionLayer::Bridge,
+            symbols_corrected: 2,
+        }));
+        counters.record(Err(&ErrorCorrectionFailure::new(
+            CorrectionLayer::Bridge,
+            1,
+            FailureReason::NoConsensus,
+        )));
+
+        assert_eq!(counters.frames, 2);
+        assert_eq!(counters.symbols_corrected, 3);
+        assert_eq!(counters.failure_rate_ppm(), PPM / 2);
+        assert_eq!(LayerCounters::decode(&mut &counters.encode()[..]).unwrap(), counters);
+    }
+}
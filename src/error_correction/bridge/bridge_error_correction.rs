@@ -1,26 +1,55 @@
 // bridge_error_correction.rs
 // Bridge error correction for classical-quantum interfaces
 
+use sp_std::prelude::*;
+
+use crate::error::bridge::{BridgeReceiver, Checksum};
+use crate::error::{self, ErrorCorrectionFailure, ErrorCorrector};
+
 pub struct BridgeErrorCorrection {
-    redundancy_factor: u32,
-    verification_rounds: u32,
+    bridge: error::BridgeErrorCorrection,
 }
 
 impl BridgeErrorCorrection {
     pub fn new(redundancy_factor: u32, verification_rounds: u32) -> Self {
         BridgeErrorCorrection {
-            redundancy_factor,
-            verification_rounds,
+            bridge: error::BridgeErrorCorrection::new(
+                redundancy_factor as usize,
+                verification_rounds as usize,
+            ),
         }
     }
-    
+
+    pub fn with_checksum(self, checksum: Checksum) -> Self {
+        BridgeErrorCorrection {
+            bridge: self.bridge.with_checksum(checksum),
+        }
+    }
+
+    pub fn redundancy_factor(&self) -> u32 {
+        self.bridge.redundancy_factor() as u32
+    }
+
+    pub fn verification_rounds(&self) -> u32 {
+        self.bridge.verification_layers() as u32
+    }
+
     pub fn encode_for_quantum_transmission(&self, data: &[u8]) -> Vec<u8> {
-        // Implementation would add redundancy for quantum transmission
-        data.to_vec()
+        self.bridge.encode(data)
     }
-    
-    pub fn decode_from_quantum_transmission(&self, data: &[u8]) -> Result<Vec<u8>, String> {
-        // Implementation would verify and correct transmission errors
-        Ok(data.to_vec())
+
+    pub fn decode_from_quantum_transmission(&self, data: &[u8]) -> Result<Vec<u8>, ErrorCorrectionFailure> {
+        self.bridge.decode(data).map(|output| output.data)
+    }
+
+    /// A receiver for this bridge's frames that tracks sequence numbers and
+    /// produces retransmit requests.
+    pub fn receiver(&self, first_sequence: u32) -> BridgeReceiver {
+        let bridge = error::BridgeErrorCorrection::new(
+            self.bridge.redundancy_factor(),
+            self.bridge.verification_layers(),
+        )
+        .with_checksum(self.bridge.checksum());
+        BridgeReceiver::new(bridge, first_sequence)
     }
 }
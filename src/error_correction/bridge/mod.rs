@@ -0,0 +1,3 @@
+pub mod bridge_error_correction;
+
+pub use bridge_error_correction::BridgeErrorCorrection;
@@ -1,9 +1,14 @@
 // classical_error_correction.rs
 // Implementation of Reed-Solomon codes for classical error correction
 
+use sp_std::prelude::*;
+
+use crate::error::classical::{ClassicalError, ClassicalErrorCorrection, CorrectionReport};
+
 pub struct ReedSolomonCorrector {
     pub data_shards: usize,
     pub parity_shards: usize,
+    codec: ClassicalErrorCorrection,
 }
 
 impl ReedSolomonCorrector {
@@ -11,21 +16,56 @@ impl ReedSolomonCorrector {
         ReedSolomonCorrector {
             data_shards,
             parity_shards,
+            codec: ClassicalErrorCorrection::new(data_shards, parity_shards),
         }
     }
-    
+
+    /// Splits `data` into `data_shards + parity_shards` equally sized shards.
     pub fn encode(&self, data: &[u8]) -> Vec<Vec<u8>> {
-        // Implementation would use Reed-Solomon encoding
-        vec![data.to_vec()]
-    }
-    
-    pub fn decode(&self, shards: &[Vec<u8>]) -> Result<Vec<u8>, String> {
-        // Implementation would use Reed-Solomon decoding
-        Ok(shards[0].clone())
-    }
-    
-    pub fn reconstruct(&self, shards: &mut [Option<Vec<u8>>]) -> Result<(), String> {
-        // Implementation would reconstruct missing shards
-        Ok(())
+        self.codec.encode_bytes(data)
+    }
+
+    /// Recovers the original bytes from a complete set of shards.
+    pub fn decode(&self, shards: &[Vec<u8>]) -> Result<Vec<u8>, ClassicalError> {
+        self.decode_with_corrupt(shards, &[])
+    }
+
+    /// Recovers the original bytes, treating the shards at `corrupt` as lost.
+    pub fn decode_with_corrupt(&self, shards: &[Vec<u8>], corrupt: &[usize]) -> Result<Vec<u8>, ClassicalError> {
+        let mut shards: Vec<Option<Vec<u8>>> = shards
+            .iter()
+            .enumerate()
+            .map(|(index, shard)| if corrupt.contains(&index) { None } else { Some(shard.clone()) })
+            .collect();
+        self.codec.decode_bytes(&mut shards)
+    }
+
+    /// Recovers the original bytes without being told which shards are bad,
+    /// reporting how many symbols had to be corrected.
+    pub fn decode_correcting(&self, shards: &[Vec<u8>]) -> Result<(Vec<u8>, CorrectionReport), ClassicalError> {
+        let mut shards: Vec<Option<Vec<u8>>> = shards.iter().cloned().map(Some).collect();
+        self.codec.decode_bytes_correcting(&mut shards)
+    }
+
+    /// Encodes `data` as a serial byte stream of codewords interleaved
+    /// `depth` deep, for links that lose runs of consecutive bytes.
+    pub fn encode_interleaved(&self, data: &[u8], depth: usize) -> Vec<u8> {
+        self.codec.encode_interleaved(data, depth)
+    }
+
+    /// Recovers bytes from `encode_interleaved`, treating the stream
+    /// positions in `lost` as erasures.
+    pub fn decode_interleaved(
+        &self,
+        stream: &[u8],
+        depth: usize,
+        lost: &[usize],
+    ) -> Result<(Vec<u8>, CorrectionReport), ClassicalError> {
+        self.codec.decode_interleaved(stream, depth, lost)
+    }
+
+    /// Rebuilds missing shards in place.
+    pub fn reconstruct(&self, shards: &mut [Option<Vec<u8>>]) -> Result<(), ClassicalError> {
+        self.codec.reconstruct(shards)
     }
 }
@@ -0,0 +1,3 @@
+pub mod classical_error_correction;
+
+pub use classical_error_correction::ReedSolomonCorrector;
@@ -0,0 +1,8 @@
+// Error correction facades used by the Matrix-Magiq integrations
+// Each type here delegates to the shared layers in `crate::error`.
+
+pub mod bridge;
+pub mod classical;
+pub mod quantum;
+
+pub use crate::error::{AdaptiveCorrector, ErrorCorrector, LayerStack};
@@ -0,0 +1,3 @@
+pub mod quantum_error_correction;
+
+pub use quantum_error_correction::SurfaceCodeQEC;
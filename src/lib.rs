@@ -0,0 +1,34 @@
+//! # Elixir Chain (ELXR)
+//!
+//! Shared components for the ELXR parachain in the Matrix-Magiq ecosystem.
+//!
+//! Builds without `std` for the runtime and for device firmware. The `frame`
+//! feature adds the pallets and runtime API, and `alloc` the error-correction
+//! layers they use; `std` enables both. With neither, only the classical
+//! codec's `heapless` framing and the interleaver are built, and the crate
+//! needs no allocator at all.
+//!
+//! [`pallet`] is the FRAME pallet holding the kombucha registry, and
+//! [`parachain`] stores the telemetry devices report. Frontends read both
+//! through [`runtime_api`], served over JSON-RPC by `rpc` with the `rpc`
+//! feature. [`migrations`] describes how the pallets' storage is upgraded.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+pub mod error;
+#[cfg(feature = "alloc")]
+pub mod error_correction;
+#[cfg(feature = "frame")]
+pub mod migrations;
+#[cfg(feature = "frame")]
+#[path = "pallet/lib.rs"]
+pub mod pallet;
+#[cfg(feature = "frame")]
+pub mod parachain;
+#[cfg(feature = "rpc")]
+pub mod rpc;
+#[cfg(feature = "frame")]
+pub mod runtime_api;
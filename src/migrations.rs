@@ -0,0 +1,41 @@
+//! Storage migrations for the ELXR pallets.
+//!
+//! Each pallet records the layout of its storage in its `STORAGE_VERSION`,
+//! set through `#[pallet::storage_version]`. A change to any stored type
+//! comes with a migration in the pallet's `migrations` module:
+//!
+//! 1. Bump `STORAGE_VERSION` to `N`.
+//! 2. Add a `vN` module holding the old types and, for every storage item
+//!    that changed, a `#[storage_alias]` with the old value type.
+//! 3. Add `vN::MigrateToVN`, an `OnRuntimeUpgrade` that does nothing unless
+//!    the on-chain version is `N - 1`, rewrites the items through the
+//!    aliases and then puts version `N`.
+//! 4. Behind the `try-runtime` feature, check in `pre_upgrade` and
+//!    `post_upgrade` that no records were lost and that every record decodes
+//!    in the new layout.
+//! 5. Test it in the pallet's tests by writing records through the old
+//!    aliases, running the migration with `run_upgrade` and reading them
+//!    back through the pallet.
+//!
+//! Runtimes add each migration to the `OnRuntimeUpgrade` tuple given to
+//! `Executive`, oldest first, and remove it once every chain has upgraded.
+//! [`crate::parachain::migrations::v1`] is the first one.
+//! [`crate::pallet::migrations::v1`] schedules audits for facilities
+//! registered before they were tracked, and
+//! [`crate::pallet::migrations::v2`] queues expiry notices for
+//! certifications added before them.
+
+#[cfg(test)]
+use frame_support::{traits::OnRuntimeUpgrade, weights::Weight};
+
+/// Runs `M` as a runtime upgrade would, with its `pre_upgrade` and
+/// `post_upgrade` checks when the `try-runtime` feature is enabled.
+#[cfg(test)]
+pub(crate) fn run_upgrade<M: OnRuntimeUpgrade>() -> Weight {
+    #[cfg(feature = "try-runtime")]
+    let state = M::pre_upgrade().expect("pre-upgrade checks pass");
+    let weight = M::on_runtime_upgrade();
+    #[cfg(feature = "try-runtime")]
+    M::post_upgrade(state).expect("post-upgrade checks pass");
+    weight
+}
@@ -0,0 +1,499 @@
+// Benchmarks for the ELXR pallet
+//
+// Every ID, name and key is as long as its bound allows, and lists are
+// filled to the size under measurement.
+
+use codec::Encode;
+use frame_benchmarking::v2::*;
+use frame_support::sp_runtime::traits::Saturating;
+use frame_support::{traits::Get, BoundedVec};
+use frame_system::{pallet_prelude::BlockNumberFor, RawOrigin};
+use sp_std::prelude::*;
+
+use super::*;
+use crate::error::LayerStack;
+use crate::parachain::signature::PUBLIC_KEY_LENGTH;
+
+/// `seed` followed by padding, as long as `S` allows.
+fn bytes<S: Get<u32>>(seed: u32) -> BoundedVec<u8, S> {
+    let mut bytes = seed.to_le_bytes().to_vec();
+    bytes.resize(S::get().max(4) as usize, b'x');
+    BoundedVec::truncate_from(bytes)
+}
+
+/// A key as long as a Dilithium2 public key, the only length devices register with.
+fn public_key<T: Config>() -> PublicKeyOf<T> {
+    BoundedVec::truncate_from(vec![7; PUBLIC_KEY_LENGTH as usize])
+}
+
+fn assert_last_event<T: Config>(event: Event<T>) {
+    frame_system::Pallet::<T>::assert_last_event(<T as Config>::RuntimeEvent::from(event).into());
+}
+
+/// An active facility owned by `owner`.
+fn active_facility<T: Config>(owner: &T::AccountId, seed: u32) -> IdOf<T> {
+    let facility_id = bytes(seed);
+    Facilities::<T>::insert(
+        &facility_id,
+        CultivationFacility {
+            name: bytes(seed),
+            location: (0, 0),
+            capacity: 1_000,
+            certifications: BoundedVec::new(),
+            status: FacilityStatus::Active,
+            owner: owner.clone(),
+            registered_at: 0u32.into(),
+            last_audit: None,
+        },
+    );
+    Parameters::<T>::insert(&facility_id, CultivationParameters::default());
+    facility_id
+}
+
+fn auditor<T: Config>() -> T::AccountId {
+    let auditor: T::AccountId = account("auditor", 0, 0);
+    Auditors::<T>::insert(&auditor, ());
+    auditor
+}
+
+/// An authorized device for `facility_id`.
+fn device<T: Config>(facility_id: &IdOf<T>) -> DeviceIdOf<T> {
+    let device_id = bytes(0);
+    Devices::<T>::insert(
+        &device_id,
+        TelemetryDevice {
+            facility_id: facility_id.clone(),
+            public_key: public_key::<T>(),
+            status: DeviceStatus::Authorized,
+            registered_at: 0u32.into(),
+            last_active: 0u32.into(),
+            firmware_version: bytes(0),
+        },
+    );
+    FacilityDevices::<T>::insert(facility_id, &device_id, ());
+    device_id
+}
+
+/// Gives the facility `count` certifications from `issuer`, and returns the
+/// number on the last one, which is the slowest to find.
+fn certify<T: Config>(
+    facility_id: &IdOf<T>,
+    issuer: &T::AccountId,
+    count: u32,
+    valid_until: BlockNumberFor<T>,
+) -> NameOf<T> {
+    let certifications = (0..count).map(|seed| Certification {
+        cert_type: CertificationType::HACCP,
+        cert_id: bytes(seed),
+        issuer: issuer.clone(),
+        valid_until,
+    });
+    Facilities::<T>::mutate(facility_id, |facility| {
+        facility.as_mut().expect("facility was inserted").certifications =
+            BoundedVec::truncate_from(certifications.collect());
+    });
+    bytes(count.saturating_sub(1))
+}
+
+fn characteristics<T: Config>() -> ScobyCharacteristics<T> {
+    ScobyCharacteristics {
+        acidity: 5,
+        fermentation_speed: 5,
+        flavor_notes: BoundedVec::truncate_from((0..T::MaxFlavorNotes::get()).map(bytes).collect()),
+        thickness: 80,
+        avg_fermentation_days: 10,
+    }
+}
+
+fn scoby<T: Config>(facility_id: &IdOf<T>, seed: u32) -> IdOf<T> {
+    let scoby_id = bytes(seed);
+    ScobyCultures::<T>::insert(
+        &scoby_id,
+        ScobyCulture {
+            name: bytes(seed),
+            parent_ids: BoundedVec::new(),
+            facility_id: facility_id.clone(),
+            origin: bytes(seed),
+            generation: 0,
+            registered_at: 0u32.into(),
+            characteristics: characteristics::<T>(),
+        },
+    );
+    scoby_id
+}
+
+/// A fermenting batch at an active facility owned by `owner`.
+fn fermenting_batch<T: Config>(owner: &T::AccountId) -> (IdOf<T>, IdOf<T>) {
+    let facility_id = active_facility::<T>(owner, 0);
+    let scoby_id = scoby::<T>(&facility_id, 1);
+    let batch_id = bytes(2);
+    Batches::<T>::insert(
+        &batch_id,
+        KombuchaBatch {
+            facility_id: facility_id.clone(),
+            scoby_id,
+            producer: owner.clone(),
+            status: BatchStatus::Fermenting,
+            fermentation_start: 0u32.into(),
+            fermentation_end: None,
+            quality_score: None,
+            readings: 0,
+        },
+    );
+    (facility_id, batch_id)
+}
+
+/// `reading` framed by the default error-correction stack, with the payload
+/// padded until the frame is as long as `f` bytes allows, since longer frames
+/// take longer to repair.
+fn reading_frame<T: Config>(reading: FermentationReading, f: u32) -> FrameOf<T> {
+    let stack = LayerStack::default();
+    let mut payload = reading.encode();
+    let mut frame = stack.encode(&payload);
+    loop {
+        payload.push(0);
+        let longer = stack.encode(&payload);
+        if longer.len() > f as usize {
+            break;
+        }
+        frame = longer;
+    }
+    frame.try_into().expect("`MaxFrameLength` must fit a framed reading")
+}
+
+#[benchmarks]
+mod benchmarks {
+    use super::*;
+
+    #[benchmark]
+    fn register_facility() {
+        let caller: T::AccountId = whitelisted_caller();
+        let owned = (1..T::MaxFacilitiesPerOwner::get()).map(bytes).collect();
+        FacilitiesByOwner::<T>::insert(&caller, BoundedVec::truncate_from(owned));
+        let facility_id: IdOf<T> = bytes(0);
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller.clone()), facility_id.clone(), bytes(0), (0, 0), 1_000);
+
+        assert_last_event::<T>(Event::FacilityRegistered { facility_id, owner: caller });
+    }
+
+    #[benchmark]
+    fn update_facility_status(c: Linear<0, { T::MaxCertifications::get() }>) -> Result<(), BenchmarkError> {
+        // Revoking also drops the notices and renewal requests of every certification
+        let origin = T::RegistryOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+        let facility_id = active_facility::<T>(&account("owner", 0, 0), 0);
+        Pallet::<T>::schedule_audit(&facility_id, T::AuditInterval::get());
+        certify::<T>(&facility_id, &auditor::<T>(), c, 10u32.into());
+        for seed in 0..c {
+            let cert_id: NameOf<T> = bytes(seed);
+            Pallet::<T>::schedule_expiry(&facility_id, &cert_id, 10u32.into());
+            RenewalRequests::<T>::insert(&facility_id, &cert_id, BlockNumberFor::<T>::from(1_000u32));
+        }
+
+        #[extrinsic_call]
+        _(origin as T::RuntimeOrigin, facility_id.clone(), FacilityStatus::Revoked);
+
+        assert_last_event::<T>(Event::FacilityStatusChanged {
+            facility_id,
+            status: FacilityStatus::Revoked,
+        });
+        assert_eq!(ExpiryNotices::<T>::iter().count(), 0);
+        Ok(())
+    }
+
+    #[benchmark]
+    fn register_device() {
+        let caller: T::AccountId = whitelisted_caller();
+        let facility_id = active_facility::<T>(&caller, 0);
+        let device_id: DeviceIdOf<T> = bytes(0);
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller), device_id.clone(), facility_id.clone(), public_key::<T>(), bytes(0));
+
+        assert_last_event::<T>(Event::DeviceAuthorized { device_id, facility_id });
+    }
+
+    #[benchmark]
+    fn update_device_status() {
+        let caller: T::AccountId = whitelisted_caller();
+        let facility_id = active_facility::<T>(&caller, 0);
+        let device_id = device::<T>(&facility_id);
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller), device_id.clone(), DeviceStatus::Suspended);
+
+        assert_last_event::<T>(Event::DeviceStatusChanged {
+            device_id,
+            status: DeviceStatus::Suspended,
+        });
+    }
+
+    #[benchmark]
+    fn update_parameters() {
+        let facility_id = active_facility::<T>(&account("owner", 0, 0), 0);
+        let auditor = auditor::<T>();
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(auditor), facility_id.clone(), CultivationParameters::default());
+
+        assert_last_event::<T>(Event::ParametersUpdated { facility_id });
+    }
+
+    #[benchmark]
+    fn add_certification(c: Linear<1, { T::MaxCertifications::get() }>) {
+        let facility_id = active_facility::<T>(&account("owner", 0, 0), 0);
+        let auditor = auditor::<T>();
+        let valid_until: BlockNumberFor<T> = 1_000u32.into();
+        Facilities::<T>::mutate(&facility_id, |facility| {
+            let facility = facility.as_mut().expect("facility was just inserted");
+            for seed in 1..c {
+                let certification = Certification {
+                    cert_type: CertificationType::Organic,
+                    cert_id: bytes(seed),
+                    issuer: auditor.clone(),
+                    valid_until,
+                };
+                facility.certifications.try_push(certification).expect("fewer than the maximum");
+            }
+        });
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(auditor), facility_id.clone(), CertificationType::GMP, bytes(0), valid_until);
+
+        assert_eq!(Facilities::<T>::get(&facility_id).unwrap().certifications.len() as u32, c);
+    }
+
+    #[benchmark]
+    fn perform_audit() {
+        // Passing a suspended facility reinstates it and schedules its next audit
+        let facility_id = active_facility::<T>(&account("owner", 0, 0), 0);
+        Facilities::<T>::mutate(&facility_id, |facility| {
+            facility.as_mut().expect("facility was just inserted").status = FacilityStatus::Suspended;
+        });
+        let auditor = auditor::<T>();
+        let follow_up_by: BlockNumberFor<T> = 10u32.into();
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(auditor), facility_id.clone(), AuditOutcome::Pass, bytes(0), Some(follow_up_by));
+
+        assert_eq!(NextAudit::<T>::get(&facility_id), Some(follow_up_by));
+    }
+
+    #[benchmark]
+    fn add_auditor() -> Result<(), BenchmarkError> {
+        let origin = T::RegistryOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+        let auditor: T::AccountId = account("auditor", 0, 0);
+
+        #[extrinsic_call]
+        _(origin as T::RuntimeOrigin, auditor.clone());
+
+        assert_last_event::<T>(Event::AuditorAdded { auditor });
+        Ok(())
+    }
+
+    #[benchmark]
+    fn remove_auditor() -> Result<(), BenchmarkError> {
+        let origin = T::RegistryOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+        let auditor = auditor::<T>();
+
+        #[extrinsic_call]
+        _(origin as T::RuntimeOrigin, auditor.clone());
+
+        assert_last_event::<T>(Event::AuditorRemoved { auditor });
+        Ok(())
+    }
+
+    #[benchmark]
+    fn update_default_parameters() -> Result<(), BenchmarkError> {
+        let origin = T::RegistryOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+
+        #[extrinsic_call]
+        _(origin as T::RuntimeOrigin, CultivationParameters::default());
+
+        assert_last_event::<T>(Event::DefaultParametersUpdated);
+        Ok(())
+    }
+
+    #[benchmark]
+    fn register_scoby(p: Linear<0, { T::MaxParentCultures::get() }>) {
+        let caller: T::AccountId = whitelisted_caller();
+        let facility_id = active_facility::<T>(&caller, 0);
+        let parent_ids: Vec<_> = (0..p).map(|seed| scoby::<T>(&facility_id, seed + 1)).collect();
+        let scoby_id: IdOf<T> = bytes(u32::MAX);
+
+        #[extrinsic_call]
+        _(
+            RawOrigin::Signed(caller),
+            scoby_id.clone(),
+            facility_id.clone(),
+            bytes(0),
+            BoundedVec::truncate_from(parent_ids),
+            bytes(0),
+            characteristics::<T>(),
+        );
+
+        assert_last_event::<T>(Event::ScobyRegistered {
+            scoby_id,
+            facility_id,
+            generation: if p == 0 { 0 } else { 1 },
+        });
+    }
+
+    #[benchmark]
+    fn start_batch() {
+        let caller: T::AccountId = whitelisted_caller();
+        let facility_id = active_facility::<T>(&caller, 0);
+        let scoby_id = scoby::<T>(&facility_id, 1);
+        let batch_id: IdOf<T> = bytes(2);
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller), batch_id.clone(), facility_id.clone(), scoby_id.clone());
+
+        assert_last_event::<T>(Event::BatchStarted {
+            batch_id,
+            facility_id,
+            scoby_id,
+        });
+    }
+
+    #[benchmark]
+    fn record_reading() {
+        let caller: T::AccountId = whitelisted_caller();
+        let (facility_id, batch_id) = fermenting_batch::<T>(&caller);
+        let device_id = device::<T>(&facility_id);
+        let reading = FermentationReading {
+            ph: 310,
+            temperature: 2400,
+            density: 1010,
+            co2: 6000,
+        };
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller), batch_id.clone(), device_id.clone(), reading);
+
+        assert_last_event::<T>(Event::ReadingRecorded {
+            batch_id,
+            device_id,
+            index: 0,
+            within_parameters: true,
+        });
+    }
+
+    #[benchmark]
+    fn record_framed_reading(f: Linear<0, { T::MaxFrameLength::get() }>) {
+        let caller: T::AccountId = whitelisted_caller();
+        let (facility_id, batch_id) = fermenting_batch::<T>(&caller);
+        let device_id = device::<T>(&facility_id);
+        let reading = FermentationReading {
+            ph: 310,
+            temperature: 2400,
+            density: 1010,
+            co2: 6000,
+        };
+        let frame = reading_frame::<T>(reading, f);
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller), batch_id.clone(), device_id.clone(), frame);
+
+        assert_last_event::<T>(Event::ReadingRecorded {
+            batch_id,
+            device_id,
+            index: 0,
+            within_parameters: true,
+        });
+    }
+
+    #[benchmark]
+    fn complete_batch() {
+        let caller: T::AccountId = whitelisted_caller();
+        let (_, batch_id) = fermenting_batch::<T>(&caller);
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller), batch_id.clone(), Some(90));
+
+        assert_last_event::<T>(Event::BatchCompleted {
+            batch_id,
+            quality_score: Some(90),
+        });
+    }
+
+    #[benchmark]
+    fn suspend_lapsed(n: Linear<0, 100>) {
+        let owner: T::AccountId = account("owner", 0, 0);
+        let due: BlockNumberFor<T> = 10u32.into();
+        let facilities: Vec<_> = (0..n).map(|seed| active_facility::<T>(&owner, seed)).collect();
+        for facility_id in &facilities {
+            Pallet::<T>::schedule_audit(facility_id, due);
+        }
+        LapseCursor::<T>::put(due);
+
+        #[block]
+        {
+            Pallet::<T>::suspend_lapsed(due, n);
+        }
+
+        for facility_id in &facilities {
+            assert_eq!(Facilities::<T>::get(facility_id).unwrap().status, FacilityStatus::Suspended);
+        }
+    }
+
+    #[benchmark]
+    fn request_certification_renewal(c: Linear<1, { T::MaxCertifications::get() }>) {
+        let caller: T::AccountId = whitelisted_caller();
+        let facility_id = active_facility::<T>(&caller, 0);
+        let cert_id = certify::<T>(&facility_id, &auditor::<T>(), c, 10u32.into());
+        let valid_until: BlockNumberFor<T> = 1_000u32.into();
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(caller), facility_id.clone(), cert_id.clone(), valid_until);
+
+        assert_last_event::<T>(Event::RenewalRequested {
+            facility_id,
+            cert_id,
+            valid_until,
+        });
+    }
+
+    #[benchmark]
+    fn renew_certification(c: Linear<1, { T::MaxCertifications::get() }>) {
+        let facility_id = active_facility::<T>(&account("owner", 0, 0), 0);
+        let auditor = auditor::<T>();
+        let cert_id = certify::<T>(&facility_id, &auditor, c, 10u32.into());
+        Pallet::<T>::schedule_expiry(&facility_id, &cert_id, 10u32.into());
+        let valid_until: BlockNumberFor<T> = 1_000u32.into();
+        RenewalRequests::<T>::insert(&facility_id, &cert_id, valid_until);
+
+        #[extrinsic_call]
+        _(RawOrigin::Signed(auditor), facility_id.clone(), cert_id.clone(), valid_until);
+
+        assert_last_event::<T>(Event::CertificationRenewed {
+            facility_id,
+            cert_id,
+            valid_until,
+        });
+    }
+
+    #[benchmark]
+    fn notify_expiries(n: Linear<0, 100>) {
+        let owner: T::AccountId = account("owner", 0, 0);
+        let auditor = auditor::<T>();
+        let valid_until: BlockNumberFor<T> = 10u32.into();
+        let due = valid_until.saturating_add(1u32.into());
+        for seed in 0..n {
+            let facility_id = active_facility::<T>(&owner, seed);
+            let cert_id = certify::<T>(&facility_id, &auditor, 1, valid_until);
+            CertificationsDue::<T>::insert(due, (&facility_id, &cert_id), ExpiryNotice::Lapsed);
+        }
+        ExpiryCursor::<T>::put(due);
+
+        #[block]
+        {
+            Pallet::<T>::notify_expiries(due, n);
+        }
+
+        assert_eq!(CertificationsDue::<T>::iter_prefix(due).count(), 0);
+    }
+
+    impl_benchmark_test_suite!(Pallet, crate::pallet::mock::new_test_ext(), crate::pallet::mock::Test);
+}
@@ -2,142 +2,248 @@
 //!
 //! This module implements the comprehensive three-layer error correction required
 //! by the Matrix-Magiq architecture:
-//! 
+//!
 //! 1. Classical Error Correction: Reed-Solomon codes
 //! 2. Bridge Error Correction: Interface between classical and quantum systems
 //! 3. Quantum Error Correction: Surface codes for quantum state protection
 //!
 //! All Matrix-Magiq components must implement these correction mechanisms to
 //! prevent crashes and ensure system stability.
-
-#![cfg_attr(not(feature = "std"), no_std)]
+//!
+//! A frame is built by [`encode_comprehensive`], which applies the layers in
+//! reverse (quantum, then bridge, then classical). [`apply_comprehensive_correction`]
+//! peels them off again in order and returns the repaired payload together with
+//! what each layer had to fix. The layers themselves live in `crate::error` and
+//! are shared with every other consumer through the `ErrorCorrector` trait.
+//!
+//! [`apply_comprehensive_correction_recorded`] does the same while counting,
+//! per source, what every layer processed, corrected and lost.
 
 use sp_std::prelude::*;
 
+use crate::error::classical::{SERIAL_DATA_SHARDS, SERIAL_INTERLEAVE_DEPTH, SERIAL_PARITY_SHARDS};
+use crate::error::{
+    BridgeErrorCorrection, ClassicalErrorCorrection, ErrorCorrector, LayerStack, QuantumErrorCorrection,
+};
+
+pub use crate::error::stats::LayerCounters;
+pub use crate::error::{
+    CorrectionConfig, CorrectionLayer, CorrectionOutcome, CorrectionStats, ErrorCorrectionFailure,
+    FailureReason, LayerOutput, LayerReport,
+};
+
+/// Builds a frame protected by all three layers.
+pub fn encode_comprehensive<T>(payload: &T, config: &CorrectionConfig) -> Vec<u8>
+where
+    T: AsRef<[u8]>,
+{
+    LayerStack::from_config(config).encode(payload.as_ref())
+}
+
 /// Apply classical error correction using Reed-Solomon codes
-pub fn apply_classical_correction<T>(data: &T) -> Result<(), ()> 
+pub fn apply_classical_correction<T>(frame: &T) -> Result<LayerOutput, ErrorCorrectionFailure>
 where
     T: AsRef<[u8]>,
 {
-    let data_bytes = data.as_ref();
-    
-    // In a full implementation, this would involve:
-    // 1. Encoding with Reed-Solomon
-    // 2. Detecting errors
-    // 3. Correcting errors if possible
-    
-    // Mock implementation for now
-    if data_bytes.is_empty() {
-        return Err(());
-    }
-    
-    Ok(())
+    let config = CorrectionConfig::default();
+    ClassicalErrorCorrection::new(config.data_shards as usize, config.parity_shards as usize)
+        .decode(frame.as_ref())
 }
 
 /// Apply bridge error correction for the classical-quantum interface
-pub fn apply_bridge_correction<T>(data: &T) -> Result<(), ()>
+pub fn apply_bridge_correction<T>(frame: &T) -> Result<LayerOutput, ErrorCorrectionFailure>
 where
     T: AsRef<[u8]>,
 {
-    let data_bytes = data.as_ref();
-    
-    // In a full implementation, this would involve:
-    // 1. Implementing redundancy mechanisms
-    // 2. Verification protocols for data crossing the classical-quantum boundary
-    // 3. Error detection and correction specific to the bridge
-    
-    // Mock implementation for now
-    if data_bytes.is_empty() {
-        return Err(());
-    }
-    
-    Ok(())
+    let config = CorrectionConfig::default();
+    BridgeErrorCorrection::new(config.redundancy_factor as usize, 1).decode(frame.as_ref())
 }
 
 /// Apply quantum error correction using Surface codes
-pub fn apply_quantum_correction<T>(data: &T) -> Result<(), ()>
+pub fn apply_quantum_correction<T>(frame: &T) -> Result<LayerOutput, ErrorCorrectionFailure>
 where
     T: AsRef<[u8]>,
 {
-    let data_bytes = data.as_ref();
-    
-    // In a full implementation, this would involve:
-    // 1. Implementation of Surface codes or similar quantum error correction codes
-    // 2. Protection against decoherence
-    // 3. Correction of operational errors in quantum states
-    
-    // Mock implementation for now
-    if data_bytes.is_empty() {
-        return Err(());
-    }
-    
-    Ok(())
+    let config = CorrectionConfig::default();
+    QuantumErrorCorrection::new(config.code_distance as usize).decode(frame.as_ref())
+}
+
+/// Decode a serial frame from the telemetry firmware, which Reed-Solomon
+/// encodes its JSON with the same codec before writing it out
+pub fn decode_telemetry_frame<T>(stream: &T) -> Result<LayerOutput, ErrorCorrectionFailure>
+where
+    T: AsRef<[u8]>,
+{
+    let layer = CorrectionLayer::Classical;
+    let (data, report) = ClassicalErrorCorrection::new(SERIAL_DATA_SHARDS, SERIAL_PARITY_SHARDS)
+        .decode_interleaved(stream.as_ref(), SERIAL_INTERLEAVE_DEPTH, &[])
+        .map_err(|e| ErrorCorrectionFailure::new(layer, 0, e.reason()))?;
+
+    Ok(LayerOutput {
+        data,
+        report: LayerReport {
+            layer,
+            symbols_corrected: report.symbols_corrected as u32,
+        },
+    })
 }
 
 /// Comprehensive error correction applying all three layers
-pub fn apply_comprehensive_correction<T>(data: &T) -> Result<(), ErrorCorrectionFailure>
+pub fn apply_comprehensive_correction<T>(frame: &T) -> Result<CorrectionOutcome, ErrorCorrectionFailure>
 where
     T: AsRef<[u8]>,
 {
-    // Apply classical error correction
-    apply_classical_correction(data)
-        .map_err(|_| ErrorCorrectionFailure::ClassicalCorrectionFailed)?;
-    
-    // Apply bridge error correction
-    apply_bridge_correction(data)
-        .map_err(|_| ErrorCorrectionFailure::BridgeCorrectionFailed)?;
-    
-    // Apply quantum error correction
-    apply_quantum_correction(data)
-        .map_err(|_| ErrorCorrectionFailure::QuantumCorrectionFailed)?;
-    
-    Ok(())
+    LayerStack::default().decode(frame.as_ref())
 }
 
-/// Error correction failure types
-pub enum ErrorCorrectionFailure {
-    ClassicalCorrectionFailed,
-    BridgeCorrectionFailed,
-    QuantumCorrectionFailed,
+/// Comprehensive error correction that also counts every layer's result
+/// against `source` in `stats`
+pub fn apply_comprehensive_correction_recorded<T, S>(
+    frame: &T,
+    source: &S,
+    stats: &mut CorrectionStats<S>,
+) -> Result<CorrectionOutcome, ErrorCorrectionFailure>
+where
+    T: AsRef<[u8]>,
+    S: Ord + Clone,
+{
+    stats.decode(&LayerStack::default(), source, frame.as_ref())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+    use codec::{Decode, Encode};
+
+    /// Offset of the first body byte after a `fields`-byte layer header.
+    fn body(fields: usize) -> usize {
+        fields * 3
+    }
+
     #[test]
     fn test_classical_correction() {
-        let data = vec![1, 2, 3, 4];
-        assert!(apply_classical_correction(&data).is_ok());
-        
+        let mut encoded = ErrorCorrector::encode(&ClassicalErrorCorrection::new(4, 2), &[1, 2, 3, 4]);
+        encoded[body(2) + 1] ^= 0xff;
+
+        let output = apply_classical_correction(&encoded).unwrap();
+        assert_eq!(output.data, vec![1, 2, 3, 4]);
+        assert_eq!(output.report.symbols_corrected, 1);
+
         let empty_data: Vec<u8> = Vec::new();
-        assert!(apply_classical_correction(&empty_data).is_err());
+        assert_eq!(
+            apply_classical_correction(&empty_data).unwrap_err().reason,
+            FailureReason::EmptyFrame
+        );
     }
-    
+
     #[test]
     fn test_bridge_correction() {
-        let data = vec![1, 2, 3, 4];
-        assert!(apply_bridge_correction(&data).is_ok());
-        
-        let empty_data: Vec<u8> = Vec::new();
-        assert!(apply_bridge_correction(&empty_data).is_err());
+        let mut encoded = BridgeErrorCorrection::new(3, 1).encode(&[1, 2, 3, 4]);
+        let packet_len = (encoded.len() - body(3)) / 3;
+        encoded[body(3) + 8] = 0;
+
+        let output = apply_bridge_correction(&encoded).unwrap();
+        assert_eq!(output.data, vec![1, 2, 3, 4]);
+        assert_eq!(output.report.symbols_corrected, 1);
+
+        // Every copy damaged differently in the same place
+        for copy in 0..3 {
+            encoded[body(3) + copy * packet_len + 8] = 0x10 + copy as u8;
+        }
+        assert_eq!(
+            apply_bridge_correction(&encoded).unwrap_err().reason,
+            FailureReason::NoConsensus
+        );
     }
-    
+
     #[test]
     fn test_quantum_correction() {
-        let data = vec![1, 2, 3, 4];
-        assert!(apply_quantum_correction(&data).is_ok());
-        
+        let mut encoded = QuantumErrorCorrection::new(3).encode(&[1, 2, 3, 4]);
+        encoded[body(1)] ^= 0b1000_0000;
+
+        let output = apply_quantum_correction(&encoded).unwrap();
+        assert_eq!(output.data, vec![1, 2, 3, 4]);
+        assert_eq!(output.report.symbols_corrected, 1);
+
         let empty_data: Vec<u8> = Vec::new();
         assert!(apply_quantum_correction(&empty_data).is_err());
     }
-    
+
+    #[test]
+    fn test_telemetry_frame_correction() {
+        let data = b"{\"device_id\":\"ELXR-01\",\"ph\":9.1}";
+        let codec = ClassicalErrorCorrection::new(SERIAL_DATA_SHARDS, SERIAL_PARITY_SHARDS);
+        let mut stream: heapless::Vec<u8, 256> = codec
+            .encode_interleaved_heapless(data, SERIAL_INTERLEAVE_DEPTH)
+            .unwrap();
+        stream[3] ^= 0x20;
+
+        let output = decode_telemetry_frame(&stream).unwrap();
+        assert_eq!(output.data, data.to_vec());
+        assert_eq!(output.report.symbols_corrected, 1);
+
+        assert_eq!(
+            decode_telemetry_frame(&stream[..stream.len() - 1].to_vec()).unwrap_err().reason,
+            FailureReason::LengthMismatch
+        );
+    }
+
     #[test]
     fn test_comprehensive_correction() {
         let data = vec![1, 2, 3, 4];
-        assert!(apply_comprehensive_correction(&data).is_ok());
-        
+        let frame = encode_comprehensive(&data, &CorrectionConfig::default());
+        let outcome = apply_comprehensive_correction(&frame).unwrap();
+        assert_eq!(outcome.payload, data);
+        assert_eq!(outcome.symbols_corrected(), 0);
+
         let empty_data: Vec<u8> = Vec::new();
-        assert!(apply_comprehensive_correction(&empty_data).is_err());
+        let failure = apply_comprehensive_correction(&empty_data).unwrap_err();
+        assert_eq!(failure.layer, CorrectionLayer::Classical);
+        assert_eq!(failure.reason, FailureReason::EmptyFrame);
+    }
+
+    #[test]
+    fn test_comprehensive_correction_repairs_damage() {
+        let data = b"{\"device_id\":\"ELXR-01\",\"ph\":3.2}".to_vec();
+        let mut encoded = encode_comprehensive(&data, &CorrectionConfig::default());
+        encoded[body(2) + 17] ^= 0x42;
+        encoded[body(2) + 900] ^= 0x01;
+
+        let outcome = apply_comprehensive_correction(&encoded).unwrap();
+        assert_eq!(outcome.payload, data);
+        assert_eq!(outcome.report(CorrectionLayer::Classical).unwrap().symbols_corrected, 2);
+    }
+
+    #[test]
+    fn test_comprehensive_correction_is_recorded_per_source() {
+        let mut stats = CorrectionStats::new();
+        let frame = encode_comprehensive(&b"temp 24.5".to_vec(), &CorrectionConfig::default());
+        let mut damaged = frame.clone();
+        damaged[body(2) + 3] ^= 0x08;
+
+        apply_comprehensive_correction_recorded(&frame, &1000u32, &mut stats).unwrap();
+        apply_comprehensive_correction_recorded(&damaged, &2000u32, &mut stats).unwrap();
+        apply_comprehensive_correction_recorded(&Vec::new(), &2000u32, &mut stats).unwrap_err();
+
+        assert_eq!(stats.counters(&1000, CorrectionLayer::Classical).symbols_corrected, 0);
+        assert_eq!(
+            stats.counters(&2000, CorrectionLayer::Classical),
+            LayerCounters {
+                frames: 2,
+                symbols_corrected: 1,
+                unrecoverable: 1,
+            }
+        );
+        assert_eq!(stats.counters(&2000, CorrectionLayer::Quantum).frames, 1);
+    }
+
+    #[test]
+    fn test_failure_is_displayable_and_encodable() {
+        let failure = ErrorCorrectionFailure::new(CorrectionLayer::Bridge, 3, FailureReason::NoConsensus);
+        assert_eq!(
+            failure.to_string(),
+            "bridge error correction failed after 3 corrected symbols: no consensus across redundant copies"
+        );
+        assert_eq!(ErrorCorrectionFailure::decode(&mut &failure.encode()[..]).unwrap(), failure);
     }
 }
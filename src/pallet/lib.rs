@@ -1,55 +1,1449 @@
 //! # ELXR Pallet
 //!
 //! A pallet that provides functionality for the ELXR in Matrix-Magiq ecosystem.
-
-#![cfg_attr(not(feature = "std"), no_std)]
+//!
+//! It keeps the kombucha registry on chain: production facilities and their
+//! certifications and audits, the telemetry devices that report for them,
+//! SCOBY cultures and their lineage, and fermentation batches with the
+//! readings recorded against them. The calls are ported from the ink!
+//! `KombuchaRegistry`, with the contract owner replaced by `Config::RegistryOrigin`.
+//!
+//! Frames from devices and other chains are repaired through
+//! [`Pallet::correct_frame`], which keeps per-source, per-layer counters in
+//! [`CorrectionCounters`] and emits [`Event::FrameCorrected`] and
+//! [`Event::FrameUnrecoverable`], so a failing sensor link or bridge shows up
+//! on chain before its data is lost. Devices send readings in such frames
+//! through [`Pallet::record_framed_reading`].
+//!
+//! Facilities move through a fixed lifecycle, see [`FacilityStatus`]: an
+//! auditor activates a pending facility, suspends an active one or
+//! reinstates a suspended one, and only `RegistryOrigin` revokes. Each
+//! [`Pallet::perform_audit`] records a pass or fail with findings; a pass
+//! activates the facility and a fail suspends it. An active facility is due
+//! for its next audit after `AuditInterval` blocks, or by an earlier
+//! follow-up deadline, and is suspended in `on_idle` once that lapses. A
+//! failed facility given a follow-up deadline that passes without another
+//! audit is reported with [`Event::FollowUpMissed`] for `RegistryOrigin` to
+//! revoke.
+//!
+//! Certifications are watched the same way: `on_idle` emits
+//! [`Event::CertificationExpiring`] `ExpiryWarningPeriod` blocks before one
+//! expires and [`Event::CertificationLapsed`] once it has, when it is removed
+//! so that a new certification can take its place. A facility without
+//! an unexpired certification of every type in `RequiredCertifications`
+//! cannot start batches. The owner renews a certification with
+//! [`Pallet::request_certification_renewal`], which the auditor who issued
+//! it co-signs with [`Pallet::renew_certification`].
+//!
+//! Testnets and development chains can start with facilities, auditors,
+//! devices and default parameters through [`GenesisConfig`].
+//!
+//! The queries behind [`crate::runtime_api::RegistryApi`] are plain functions
+//! on [`Pallet`] with the same names, for a runtime to forward to.
 
 pub use pallet::*;
+pub use types::*;
+pub use weights::WeightInfo;
+
+pub mod error_correction;
+pub mod migrations;
+pub mod types;
+pub mod weights;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
 
 #[frame_support::pallet]
 pub mod pallet {
     use frame_support::pallet_prelude::*;
+    use frame_support::sp_runtime::traits::{One, Saturating};
     use frame_system::pallet_prelude::*;
+    use sp_std::collections::{btree_set::BTreeSet, vec_deque::VecDeque};
+    use sp_std::prelude::*;
+
+    use super::types::*;
+    use super::WeightInfo;
+    use crate::error::stats::LayerCounters;
+    use crate::error::{CorrectionLayer, CorrectionOutcome, ErrorCorrectionFailure, FailureReason, LayerStack};
+    use crate::parachain::signature::PUBLIC_KEY_LENGTH;
+    use crate::runtime_api::Page;
+
+    /// Layout of the pallet's storage, see [`crate::migrations`].
+    /// Version 1 added audit schedules, version 2 certification expiry notices.
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(2);
 
     #[pallet::pallet]
-    #[pallet::generate_store(pub(super) trait Store)]
+    #[pallet::storage_version(STORAGE_VERSION)]
     pub struct Pallet<T>(_);
 
     #[pallet::config]
     pub trait Config: frame_system::Config {
         /// The overarching event type.
         type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+        /// Governance origin that manages auditors and default parameters.
+        type RegistryOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Longest facility, SCOBY culture or batch ID.
+        #[pallet::constant]
+        type MaxIdLength: Get<u32>;
+
+        /// Longest device ID.
+        #[pallet::constant]
+        type MaxDeviceIdLength: Get<u32>;
+
+        /// Longest name, origin, certificate number or firmware version.
+        #[pallet::constant]
+        type MaxNameLength: Get<u32>;
+
+        /// Longest device public key. Devices are registered with Dilithium2 keys,
+        /// so this must be at least `parachain::signature::PUBLIC_KEY_LENGTH`.
+        #[pallet::constant]
+        type MaxPublicKeyLength: Get<u32>;
+
+        /// Most certifications held by one facility.
+        #[pallet::constant]
+        type MaxCertifications: Get<u32>;
+
+        /// Most facilities registered by one account.
+        #[pallet::constant]
+        type MaxFacilitiesPerOwner: Get<u32>;
+
+        /// Most parents of one SCOBY culture.
+        #[pallet::constant]
+        type MaxParentCultures: Get<u32>;
+
+        /// Most flavor notes describing one SCOBY culture.
+        #[pallet::constant]
+        type MaxFlavorNotes: Get<u32>;
+
+        /// Most readings recorded against one batch.
+        #[pallet::constant]
+        type MaxReadingsPerBatch: Get<u32>;
+
+        /// Longest audit findings.
+        #[pallet::constant]
+        type MaxFindingsLength: Get<u32>;
+
+        /// Longest frame accepted by [`Pallet::record_framed_reading`].
+        #[pallet::constant]
+        type MaxFrameLength: Get<u32>;
+
+        /// Blocks an active facility may go without an audit before it is suspended.
+        #[pallet::constant]
+        type AuditInterval: Get<BlockNumberFor<Self>>;
+
+        /// Blocks before a certification expires that [`Event::CertificationExpiring`] is emitted.
+        #[pallet::constant]
+        type ExpiryWarningPeriod: Get<BlockNumberFor<Self>>;
+
+        /// Certification types a facility must hold, unexpired, to start batches.
+        #[pallet::constant]
+        type RequiredCertifications: Get<Vec<CertificationType>>;
+
+        /// Weight information for extrinsics in this pallet.
+        type WeightInfo: WeightInfo;
     }
 
     #[pallet::storage]
-    pub type ExampleStorage<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, u64, ValueQuery>;
+    pub type Facilities<T: Config> = StorageMap<_, Blake2_128Concat, IdOf<T>, CultivationFacility<T>>;
+
+    #[pallet::storage]
+    pub type FacilitiesByOwner<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        BoundedVec<IdOf<T>, T::MaxFacilitiesPerOwner>,
+        ValueQuery,
+    >;
+
+    /// Target conditions per facility, starting from `DefaultParameters`.
+    #[pallet::storage]
+    pub type Parameters<T: Config> = StorageMap<_, Blake2_128Concat, IdOf<T>, CultivationParameters>;
+
+    #[pallet::storage]
+    pub type DefaultParameters<T: Config> = StorageValue<_, CultivationParameters, ValueQuery>;
+
+    #[pallet::storage]
+    pub type Devices<T: Config> = StorageMap<_, Blake2_128Concat, DeviceIdOf<T>, TelemetryDevice<T>>;
+
+    /// Devices registered for each facility.
+    #[pallet::storage]
+    pub type FacilityDevices<T: Config> =
+        StorageDoubleMap<_, Blake2_128Concat, IdOf<T>, Blake2_128Concat, DeviceIdOf<T>, ()>;
+
+    #[pallet::storage]
+    pub type Auditors<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, ()>;
+
+    /// Latest audit of each facility.
+    #[pallet::storage]
+    pub type Audits<T: Config> = StorageMap<_, Blake2_128Concat, IdOf<T>, Audit<T>>;
+
+    /// Block by which each active facility must next be audited.
+    #[pallet::storage]
+    pub type NextAudit<T: Config> = StorageMap<_, Blake2_128Concat, IdOf<T>, BlockNumberFor<T>>;
+
+    /// Active facilities by the block their next audit is due, for suspending lapsed ones.
+    #[pallet::storage]
+    pub type AuditsDue<T: Config> =
+        StorageDoubleMap<_, Twox64Concat, BlockNumberFor<T>, Blake2_128Concat, IdOf<T>, ()>;
+
+    /// Oldest due block that may still have lapsed facilities.
+    #[pallet::storage]
+    pub type LapseCursor<T: Config> = StorageValue<_, BlockNumberFor<T>, ValueQuery>;
+
+    /// Expiry notices by the block they are due, for each facility and certificate number.
+    #[pallet::storage]
+    pub type CertificationsDue<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        BlockNumberFor<T>,
+        Blake2_128Concat,
+        (IdOf<T>, NameOf<T>),
+        ExpiryNotice,
+    >;
+
+    /// Oldest block that may still have expiry notices due.
+    #[pallet::storage]
+    pub type ExpiryCursor<T: Config> = StorageValue<_, BlockNumberFor<T>, ValueQuery>;
+
+    /// Blocks each certification's warning and lapse notices are queued for in
+    /// `CertificationsDue`, by facility and certificate number.
+    #[pallet::storage]
+    pub type ExpiryNotices<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        IdOf<T>,
+        Blake2_128Concat,
+        NameOf<T>,
+        (BlockNumberFor<T>, BlockNumberFor<T>),
+    >;
+
+    /// Renewals requested by facility owners, by facility and certificate
+    /// number, awaiting the issuing auditor's approval.
+    #[pallet::storage]
+    pub type RenewalRequests<T: Config> =
+        StorageDoubleMap<_, Blake2_128Concat, IdOf<T>, Blake2_128Concat, NameOf<T>, BlockNumberFor<T>>;
+
+    /// Accounts trusted to sign oracle feeds. Only seeded at genesis until the
+    /// daemonless oracle is ported.
+    #[pallet::storage]
+    pub type OracleValidators<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, ()>;
+
+    #[pallet::storage]
+    pub type ScobyCultures<T: Config> = StorageMap<_, Blake2_128Concat, IdOf<T>, ScobyCulture<T>>;
+
+    #[pallet::storage]
+    pub type Batches<T: Config> = StorageMap<_, Blake2_128Concat, IdOf<T>, KombuchaBatch<T>>;
+
+    /// Readings per batch, by index in the order they were recorded.
+    #[pallet::storage]
+    pub type BatchReadings<T: Config> =
+        StorageDoubleMap<_, Blake2_128Concat, IdOf<T>, Twox64Concat, u32, TelemetryReading<T>>;
+
+    #[pallet::storage]
+    pub type FacilityCount<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    #[pallet::storage]
+    pub type DeviceCount<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    #[pallet::storage]
+    pub type ScobyCount<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    #[pallet::storage]
+    pub type BatchCount<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// Frames processed, symbols corrected and frames lost by each layer, per source.
+    #[pallet::storage]
+    pub type CorrectionCounters<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        CorrectionSource<T>,
+        Twox64Concat,
+        CorrectionLayer,
+        LayerCounters,
+        ValueQuery,
+    >;
+
+    /// Registry state for testnets and development chains.
+    #[pallet::genesis_config]
+    #[derive(frame_support::DefaultNoBound)]
+    pub struct GenesisConfig<T: Config> {
+        /// Facilities as `(id, owner, name, location, capacity)`, registered active
+        pub facilities: Vec<(IdOf<T>, T::AccountId, NameOf<T>, (i32, i32), u32)>,
+        pub auditors: Vec<T::AccountId>,
+        /// Devices as `(id, facility id, public key, firmware version)`, registered authorized
+        pub devices: Vec<(DeviceIdOf<T>, IdOf<T>, PublicKeyOf<T>, NameOf<T>)>,
+        /// Parameters for the genesis facilities and any registered later
+        pub default_parameters: CultivationParameters,
+        pub oracle_validators: Vec<T::AccountId>,
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
+        fn build(&self) {
+            assert!(self.default_parameters.is_valid(), "genesis default parameters are invalid");
+            DefaultParameters::<T>::put(self.default_parameters);
+            for auditor in &self.auditors {
+                Auditors::<T>::insert(auditor, ());
+            }
+            for validator in &self.oracle_validators {
+                OracleValidators::<T>::insert(validator, ());
+            }
+
+            for (facility_id, owner, name, location, capacity) in &self.facilities {
+                assert!(!Facilities::<T>::contains_key(facility_id), "genesis facility IDs must be unique");
+                FacilitiesByOwner::<T>::try_mutate(owner, |owned| owned.try_push(facility_id.clone()))
+                    .expect("genesis owners must not exceed `MaxFacilitiesPerOwner`");
+                Facilities::<T>::insert(
+                    facility_id,
+                    CultivationFacility {
+                        name: name.clone(),
+                        location: *location,
+                        capacity: *capacity,
+                        certifications: BoundedVec::new(),
+                        status: FacilityStatus::Active,
+                        owner: owner.clone(),
+                        registered_at: Default::default(),
+                        last_audit: None,
+                    },
+                );
+                Parameters::<T>::insert(facility_id, self.default_parameters);
+                Pallet::<T>::schedule_audit(facility_id, T::AuditInterval::get());
+            }
+            FacilityCount::<T>::put(self.facilities.len() as u32);
+
+            for (device_id, facility_id, public_key, firmware_version) in &self.devices {
+                assert!(Facilities::<T>::contains_key(facility_id), "genesis devices need a facility");
+                assert!(!Devices::<T>::contains_key(device_id), "genesis device IDs must be unique");
+                assert!(
+                    public_key.len() == PUBLIC_KEY_LENGTH as usize,
+                    "genesis device keys must be Dilithium2 public keys"
+                );
+                Devices::<T>::insert(
+                    device_id,
+                    TelemetryDevice {
+                        facility_id: facility_id.clone(),
+                        public_key: public_key.clone(),
+                        status: DeviceStatus::Authorized,
+                        registered_at: Default::default(),
+                        last_active: Default::default(),
+                        firmware_version: firmware_version.clone(),
+                    },
+                );
+                FacilityDevices::<T>::insert(facility_id, device_id, ());
+            }
+            DeviceCount::<T>::put(self.devices.len() as u32);
+        }
+    }
 
     #[pallet::event]
     #[pallet::generate_deposit(pub(super) fn deposit_event)]
     pub enum Event<T: Config> {
-        /// Example event
-        ExampleEvent(T::AccountId),
+        FacilityRegistered {
+            facility_id: IdOf<T>,
+            owner: T::AccountId,
+        },
+        FacilityStatusChanged {
+            facility_id: IdOf<T>,
+            status: FacilityStatus,
+        },
+        DeviceAuthorized {
+            device_id: DeviceIdOf<T>,
+            facility_id: IdOf<T>,
+        },
+        DeviceStatusChanged {
+            device_id: DeviceIdOf<T>,
+            status: DeviceStatus,
+        },
+        ParametersUpdated {
+            facility_id: IdOf<T>,
+        },
+        DefaultParametersUpdated,
+        CertificationAdded {
+            facility_id: IdOf<T>,
+            cert_type: CertificationType,
+            valid_until: BlockNumberFor<T>,
+        },
+        /// A certification expires within `ExpiryWarningPeriod` blocks
+        CertificationExpiring {
+            facility_id: IdOf<T>,
+            cert_type: CertificationType,
+            cert_id: NameOf<T>,
+            valid_until: BlockNumberFor<T>,
+        },
+        /// A certification expired without being renewed and was removed from its facility
+        CertificationLapsed {
+            facility_id: IdOf<T>,
+            cert_type: CertificationType,
+            cert_id: NameOf<T>,
+        },
+        /// The facility owner asked the issuing auditor to extend a certification
+        RenewalRequested {
+            facility_id: IdOf<T>,
+            cert_id: NameOf<T>,
+            valid_until: BlockNumberFor<T>,
+        },
+        CertificationRenewed {
+            facility_id: IdOf<T>,
+            cert_id: NameOf<T>,
+            valid_until: BlockNumberFor<T>,
+        },
+        AuditPerformed {
+            facility_id: IdOf<T>,
+            auditor: T::AccountId,
+            outcome: AuditOutcome,
+        },
+        /// An active facility went `AuditInterval` blocks, or past its
+        /// follow-up deadline, without an audit and was suspended
+        AuditLapsed {
+            facility_id: IdOf<T>,
+        },
+        /// A facility that failed its audit did not pass another by the
+        /// follow-up deadline, and is due for revocation by `RegistryOrigin`
+        FollowUpMissed {
+            facility_id: IdOf<T>,
+        },
+        AuditorAdded {
+            auditor: T::AccountId,
+        },
+        AuditorRemoved {
+            auditor: T::AccountId,
+        },
+        ScobyRegistered {
+            scoby_id: IdOf<T>,
+            facility_id: IdOf<T>,
+            generation: u32,
+        },
+        BatchStarted {
+            batch_id: IdOf<T>,
+            facility_id: IdOf<T>,
+            scoby_id: IdOf<T>,
+        },
+        /// A reading was recorded; `within_parameters` is false when it left
+        /// the facility's target ranges
+        ReadingRecorded {
+            batch_id: IdOf<T>,
+            device_id: DeviceIdOf<T>,
+            index: u32,
+            within_parameters: bool,
+        },
+        BatchCompleted {
+            batch_id: IdOf<T>,
+            quality_score: Option<u8>,
+        },
+        /// A layer repaired symbols in a frame from `source`
+        FrameCorrected {
+            source: CorrectionSource<T>,
+            layer: CorrectionLayer,
+            symbols_corrected: u32,
+        },
+        /// A layer could not recover a frame from `source`, whose payload is lost
+        FrameUnrecoverable {
+            source: CorrectionSource<T>,
+            layer: CorrectionLayer,
+            reason: FailureReason,
+        },
     }
 
     #[pallet::error]
     pub enum Error<T> {
-        /// Example error
-        ExampleError,
+        /// The caller does not own the facility
+        NotFacilityOwner,
+        /// The caller is not an auditor
+        NotAuditor,
+        /// The facility cannot move from its status to the one requested
+        InvalidStatusTransition,
+        /// The facility has been revoked
+        FacilityRevoked,
+        /// The follow-up deadline has already passed
+        FollowUpInPast,
+        /// The facility ID is already registered
+        FacilityAlreadyExists,
+        /// No facility has this ID
+        FacilityNotFound,
+        /// The facility is not active
+        FacilityNotActive,
+        /// The owner has registered the maximum number of facilities
+        TooManyFacilities,
+        /// The facility holds the maximum number of certifications
+        TooManyCertifications,
+        /// The certification is already expired
+        CertificationExpired,
+        /// The facility already holds a certification with this number
+        CertificationAlreadyHeld,
+        /// The facility holds no certification with this number
+        CertificationNotFound,
+        /// The caller did not issue the certification
+        NotCertificationIssuer,
+        /// The facility owner has not requested a renewal on these terms
+        RenewalNotRequested,
+        /// The facility lacks an unexpired certification of a type in `RequiredCertifications`
+        MissingRequiredCertification,
+        /// The device ID is already registered
+        DeviceAlreadyExists,
+        /// The public key is not a Dilithium2 public key
+        InvalidPublicKey,
+        /// No device has this ID
+        DeviceNotFound,
+        /// The device is suspended or revoked
+        DeviceNotAuthorized,
+        /// The device has been revoked
+        DeviceRevoked,
+        /// The device reports for a different facility
+        DeviceFacilityMismatch,
+        /// The account is already an auditor
+        AuditorAlreadyExists,
+        /// The account is not an auditor
+        AuditorNotFound,
+        /// A range is empty or outside `CultivationParameters::LIMITS`
+        InvalidParameters,
+        /// The SCOBY culture ID is already registered
+        ScobyAlreadyExists,
+        /// No SCOBY culture has this ID
+        ScobyNotFound,
+        /// The SCOBY culture belongs to a different facility
+        ScobyFacilityMismatch,
+        /// The batch ID is already registered
+        BatchAlreadyExists,
+        /// No batch has this ID
+        BatchNotFound,
+        /// The batch has already completed
+        BatchNotFermenting,
+        /// The batch holds the maximum number of readings
+        TooManyReadings,
+        /// The repaired frame does not start with a reading
+        MalformedReading,
+        /// Quality scores are out of 100
+        InvalidQualityScore,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_idle(now: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+            let lapses = Self::idle_steps(remaining_weight, T::WeightInfo::suspend_lapsed, |limit| {
+                Self::suspend_lapsed(now, limit)
+            });
+            let notices = Self::idle_steps(
+                remaining_weight.saturating_sub(lapses),
+                T::WeightInfo::notify_expiries,
+                |limit| Self::notify_expiries(now, limit),
+            );
+            lapses.saturating_add(notices)
+        }
+
+        fn integrity_test() {
+            assert!(
+                T::MaxPublicKeyLength::get() >= PUBLIC_KEY_LENGTH,
+                "`MaxPublicKeyLength` must fit a Dilithium2 public key"
+            );
+        }
     }
 
     #[pallet::call]
     impl<T: Config> Pallet<T> {
-        /// Example call
+        /// Registers a facility owned by the caller, pending activation by an auditor.
         #[pallet::call_index(0)]
-        #[pallet::weight(10_000)]
-        pub fn example_function(origin: OriginFor<T>) -> DispatchResult {
-            let who = ensure_signed(origin)?;
-            
-            // Implement function logic here
-            
-            // Emit an event
-            Self::deposit_event(Event::ExampleEvent(who));
-            
+        #[pallet::weight(T::WeightInfo::register_facility())]
+        pub fn register_facility(
+            origin: OriginFor<T>,
+            facility_id: IdOf<T>,
+            name: NameOf<T>,
+            location: (i32, i32),
+            capacity: u32,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(!Facilities::<T>::contains_key(&facility_id), Error::<T>::FacilityAlreadyExists);
+
+            FacilitiesByOwner::<T>::try_mutate(&who, |owned| owned.try_push(facility_id.clone()))
+                .map_err(|_| Error::<T>::TooManyFacilities)?;
+            Facilities::<T>::insert(
+                &facility_id,
+                CultivationFacility {
+                    name,
+                    location,
+                    capacity,
+                    certifications: BoundedVec::new(),
+                    status: FacilityStatus::Pending,
+                    owner: who.clone(),
+                    registered_at: frame_system::Pallet::<T>::block_number(),
+                    last_audit: None,
+                },
+            );
+            Parameters::<T>::insert(&facility_id, DefaultParameters::<T>::get());
+            FacilityCount::<T>::mutate(|count| *count = count.saturating_add(1));
+
+            Self::deposit_event(Event::FacilityRegistered { facility_id, owner: who });
+            Ok(())
+        }
+
+        /// Moves a facility along its lifecycle, see [`FacilityStatus`]. Auditors
+        /// activate and suspend; only governance revokes.
+        #[pallet::call_index(1)]
+        #[pallet::weight(T::WeightInfo::update_facility_status(T::MaxCertifications::get()))]
+        pub fn update_facility_status(
+            origin: OriginFor<T>,
+            facility_id: IdOf<T>,
+            status: FacilityStatus,
+        ) -> DispatchResult {
+            match T::RegistryOrigin::try_origin(origin) {
+                Ok(_) => ensure!(status == FacilityStatus::Revoked, DispatchError::BadOrigin),
+                Err(origin) => {
+                    Self::ensure_auditor(origin)?;
+                    ensure!(status != FacilityStatus::Revoked, DispatchError::BadOrigin);
+                }
+            }
+            Self::set_status(&facility_id, status)
+        }
+
+        /// Authorizes a telemetry device for one of the caller's active facilities.
+        /// `public_key` is the Dilithium2 key it signs its readings with.
+        #[pallet::call_index(2)]
+        #[pallet::weight(T::WeightInfo::register_device())]
+        pub fn register_device(
+            origin: OriginFor<T>,
+            device_id: DeviceIdOf<T>,
+            facility_id: IdOf<T>,
+            public_key: PublicKeyOf<T>,
+            firmware_version: NameOf<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let facility = Facilities::<T>::get(&facility_id).ok_or(Error::<T>::FacilityNotFound)?;
+            ensure!(facility.owner == who, Error::<T>::NotFacilityOwner);
+            ensure!(facility.status == FacilityStatus::Active, Error::<T>::FacilityNotActive);
+            ensure!(!Devices::<T>::contains_key(&device_id), Error::<T>::DeviceAlreadyExists);
+            ensure!(public_key.len() == PUBLIC_KEY_LENGTH as usize, Error::<T>::InvalidPublicKey);
+
+            let now = frame_system::Pallet::<T>::block_number();
+            Devices::<T>::insert(
+                &device_id,
+                TelemetryDevice {
+                    facility_id: facility_id.clone(),
+                    public_key,
+                    status: DeviceStatus::Authorized,
+                    registered_at: now,
+                    last_active: now,
+                    firmware_version,
+                },
+            );
+            FacilityDevices::<T>::insert(&facility_id, &device_id, ());
+            DeviceCount::<T>::mutate(|count| *count = count.saturating_add(1));
+
+            Self::deposit_event(Event::DeviceAuthorized { device_id, facility_id });
+            Ok(())
+        }
+
+        /// Sets a device's status. Governance or the owner of the device's facility only.
+        /// Revoking is final: a revoked device must be registered again under a new ID.
+        #[pallet::call_index(3)]
+        #[pallet::weight(T::WeightInfo::update_device_status())]
+        pub fn update_device_status(
+            origin: OriginFor<T>,
+            device_id: DeviceIdOf<T>,
+            status: DeviceStatus,
+        ) -> DispatchResult {
+            let mut device = Devices::<T>::get(&device_id).ok_or(Error::<T>::DeviceNotFound)?;
+            let facility = Facilities::<T>::get(&device.facility_id).ok_or(Error::<T>::FacilityNotFound)?;
+            if let Err(origin) = T::RegistryOrigin::try_origin(origin) {
+                ensure!(ensure_signed(origin)? == facility.owner, Error::<T>::NotFacilityOwner);
+            }
+            ensure!(device.status != DeviceStatus::Revoked, Error::<T>::DeviceRevoked);
+
+            device.status = status;
+            Devices::<T>::insert(&device_id, device);
+
+            Self::deposit_event(Event::DeviceStatusChanged { device_id, status });
+            Ok(())
+        }
+
+        /// Replaces a facility's target conditions. Its owner or an auditor only.
+        #[pallet::call_index(4)]
+        #[pallet::weight(T::WeightInfo::update_parameters())]
+        pub fn update_parameters(
+            origin: OriginFor<T>,
+            facility_id: IdOf<T>,
+            parameters: CultivationParameters,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let facility = Facilities::<T>::get(&facility_id).ok_or(Error::<T>::FacilityNotFound)?;
+            ensure!(
+                facility.owner == who || Auditors::<T>::contains_key(&who),
+                Error::<T>::NotFacilityOwner
+            );
+            ensure!(parameters.is_valid(), Error::<T>::InvalidParameters);
+
+            Parameters::<T>::insert(&facility_id, parameters);
+
+            Self::deposit_event(Event::ParametersUpdated { facility_id });
+            Ok(())
+        }
+
+        /// Records a certification the calling auditor has checked.
+        #[pallet::call_index(5)]
+        #[pallet::weight(T::WeightInfo::add_certification(T::MaxCertifications::get()))]
+        pub fn add_certification(
+            origin: OriginFor<T>,
+            facility_id: IdOf<T>,
+            cert_type: CertificationType,
+            cert_id: NameOf<T>,
+            valid_until: BlockNumberFor<T>,
+        ) -> DispatchResultWithPostInfo {
+            let who = Self::ensure_auditor(origin)?;
+            ensure!(
+                valid_until > frame_system::Pallet::<T>::block_number(),
+                Error::<T>::CertificationExpired
+            );
+
+            let held = Facilities::<T>::try_mutate(&facility_id, |facility| -> Result<u32, DispatchError> {
+                let facility = facility.as_mut().ok_or(Error::<T>::FacilityNotFound)?;
+                ensure!(
+                    facility.certifications.iter().all(|held| held.cert_id != cert_id),
+                    Error::<T>::CertificationAlreadyHeld
+                );
+                facility
+                    .certifications
+                    .try_push(Certification {
+                        cert_type,
+                        cert_id: cert_id.clone(),
+                        issuer: who,
+                        valid_until,
+                    })
+                    .map_err(|_| Error::<T>::TooManyCertifications)?;
+                Ok(facility.certifications.len() as u32)
+            })?;
+            Self::schedule_expiry(&facility_id, &cert_id, valid_until);
+
+            Self::deposit_event(Event::CertificationAdded {
+                facility_id,
+                cert_type,
+                valid_until,
+            });
+            Ok(Some(T::WeightInfo::add_certification(held)).into())
+        }
+
+        /// Records an audit of a facility by the calling auditor. A pass
+        /// activates a pending or suspended facility and a fail suspends an
+        /// active one. An active facility is next due for an audit after
+        /// `AuditInterval` blocks, or by `follow_up_by` if that is sooner.
+        /// A failed facility is due for another audit by `follow_up_by`, and
+        /// is reported for revocation if it has not passed one by then.
+        #[pallet::call_index(6)]
+        #[pallet::weight(T::WeightInfo::perform_audit())]
+        pub fn perform_audit(
+            origin: OriginFor<T>,
+            facility_id: IdOf<T>,
+            outcome: AuditOutcome,
+            findings: FindingsOf<T>,
+            follow_up_by: Option<BlockNumberFor<T>>,
+        ) -> DispatchResult {
+            let who = Self::ensure_auditor(origin)?;
+            let now = frame_system::Pallet::<T>::block_number();
+            ensure!(follow_up_by.is_none_or(|deadline| deadline > now), Error::<T>::FollowUpInPast);
+            let status = Facilities::<T>::try_mutate(&facility_id, |facility| -> Result<_, DispatchError> {
+                let facility = facility.as_mut().ok_or(Error::<T>::FacilityNotFound)?;
+                ensure!(facility.status != FacilityStatus::Revoked, Error::<T>::FacilityRevoked);
+                facility.last_audit = Some(now);
+                Ok(facility.status)
+            })?;
+            Audits::<T>::insert(
+                &facility_id,
+                Audit {
+                    auditor: who.clone(),
+                    outcome,
+                    findings,
+                    follow_up_by,
+                    audited_at: now,
+                },
+            );
+            Self::deposit_event(Event::AuditPerformed {
+                facility_id: facility_id.clone(),
+                auditor: who,
+                outcome,
+            });
+
+            match (outcome, status) {
+                (AuditOutcome::Pass, FacilityStatus::Active) => {}
+                (AuditOutcome::Pass, _) => Self::set_status(&facility_id, FacilityStatus::Active)?,
+                (AuditOutcome::Fail, status) => {
+                    if status == FacilityStatus::Active {
+                        Self::set_status(&facility_id, FacilityStatus::Suspended)?;
+                    }
+                    if let Some(deadline) = follow_up_by {
+                        Self::schedule_audit(&facility_id, deadline);
+                    }
+                    return Ok(());
+                }
+            }
+            let due = now.saturating_add(T::AuditInterval::get());
+            Self::schedule_audit(&facility_id, follow_up_by.map_or(due, |deadline| deadline.min(due)));
+            Ok(())
+        }
+
+        #[pallet::call_index(7)]
+        #[pallet::weight(T::WeightInfo::add_auditor())]
+        pub fn add_auditor(origin: OriginFor<T>, auditor: T::AccountId) -> DispatchResult {
+            T::RegistryOrigin::ensure_origin(origin)?;
+            ensure!(!Auditors::<T>::contains_key(&auditor), Error::<T>::AuditorAlreadyExists);
+
+            Auditors::<T>::insert(&auditor, ());
+
+            Self::deposit_event(Event::AuditorAdded { auditor });
+            Ok(())
+        }
+
+        #[pallet::call_index(8)]
+        #[pallet::weight(T::WeightInfo::remove_auditor())]
+        pub fn remove_auditor(origin: OriginFor<T>, auditor: T::AccountId) -> DispatchResult {
+            T::RegistryOrigin::ensure_origin(origin)?;
+            ensure!(Auditors::<T>::contains_key(&auditor), Error::<T>::AuditorNotFound);
+
+            Auditors::<T>::remove(&auditor);
+
+            Self::deposit_event(Event::AuditorRemoved { auditor });
+            Ok(())
+        }
+
+        /// Sets the parameters given to facilities registered from now on.
+        #[pallet::call_index(9)]
+        #[pallet::weight(T::WeightInfo::update_default_parameters())]
+        pub fn update_default_parameters(
+            origin: OriginFor<T>,
+            parameters: CultivationParameters,
+        ) -> DispatchResult {
+            T::RegistryOrigin::ensure_origin(origin)?;
+            ensure!(parameters.is_valid(), Error::<T>::InvalidParameters);
+
+            DefaultParameters::<T>::put(parameters);
+
+            Self::deposit_event(Event::DefaultParametersUpdated);
+            Ok(())
+        }
+
+        /// Registers a SCOBY culture for one of the caller's active facilities.
+        /// Its generation follows from its parents, which must already be registered.
+        #[pallet::call_index(10)]
+        #[pallet::weight(T::WeightInfo::register_scoby(parent_ids.len() as u32))]
+        pub fn register_scoby(
+            origin: OriginFor<T>,
+            scoby_id: IdOf<T>,
+            facility_id: IdOf<T>,
+            name: NameOf<T>,
+            parent_ids: BoundedVec<IdOf<T>, T::MaxParentCultures>,
+            scoby_origin: NameOf<T>,
+            characteristics: ScobyCharacteristics<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::ensure_active_facility(&who, &facility_id)?;
+            ensure!(!ScobyCultures::<T>::contains_key(&scoby_id), Error::<T>::ScobyAlreadyExists);
+
+            let mut generation = 0;
+            for parent_id in &parent_ids {
+                let parent = ScobyCultures::<T>::get(parent_id).ok_or(Error::<T>::ScobyNotFound)?;
+                generation = generation.max(parent.generation.saturating_add(1));
+            }
+
+            ScobyCultures::<T>::insert(
+                &scoby_id,
+                ScobyCulture {
+                    name,
+                    parent_ids,
+                    facility_id: facility_id.clone(),
+                    origin: scoby_origin,
+                    generation,
+                    registered_at: frame_system::Pallet::<T>::block_number(),
+                    characteristics,
+                },
+            );
+            ScobyCount::<T>::mutate(|count| *count = count.saturating_add(1));
+
+            Self::deposit_event(Event::ScobyRegistered {
+                scoby_id,
+                facility_id,
+                generation,
+            });
+            Ok(())
+        }
+
+        /// Starts fermenting a batch from one of the facility's SCOBY cultures.
+        #[pallet::call_index(11)]
+        #[pallet::weight(T::WeightInfo::start_batch())]
+        pub fn start_batch(
+            origin: OriginFor<T>,
+            batch_id: IdOf<T>,
+            facility_id: IdOf<T>,
+            scoby_id: IdOf<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let facility = Self::ensure_active_facility(&who, &facility_id)?;
+            Self::ensure_certified(&facility)?;
+            let scoby = ScobyCultures::<T>::get(&scoby_id).ok_or(Error::<T>::ScobyNotFound)?;
+            ensure!(scoby.facility_id == facility_id, Error::<T>::ScobyFacilityMismatch);
+            ensure!(!Batches::<T>::contains_key(&batch_id), Error::<T>::BatchAlreadyExists);
+
+            Batches::<T>::insert(
+                &batch_id,
+                KombuchaBatch {
+                    facility_id: facility_id.clone(),
+                    scoby_id: scoby_id.clone(),
+                    producer: who,
+                    status: BatchStatus::Fermenting,
+                    fermentation_start: frame_system::Pallet::<T>::block_number(),
+                    fermentation_end: None,
+                    quality_score: None,
+                    readings: 0,
+                },
+            );
+            BatchCount::<T>::mutate(|count| *count = count.saturating_add(1));
+
+            Self::deposit_event(Event::BatchStarted {
+                batch_id,
+                facility_id,
+                scoby_id,
+            });
+            Ok(())
+        }
+
+        /// Records a reading from one of an active facility's authorized
+        /// devices against a fermenting batch, and marks the device active.
+        #[pallet::call_index(12)]
+        #[pallet::weight(T::WeightInfo::record_reading())]
+        pub fn record_reading(
+            origin: OriginFor<T>,
+            batch_id: IdOf<T>,
+            device_id: DeviceIdOf<T>,
+            reading: FermentationReading,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let (batch, device) = Self::reading_target(&who, &batch_id, &device_id)?;
+            Self::store_reading(batch_id, batch, device_id, device, reading);
+            Ok(())
+        }
+
+        /// Like [`Pallet::record_reading`], for a reading as the device sent
+        /// it: SCALE encoded and protected by every error-correction layer.
+        ///
+        /// The frame is repaired through [`Pallet::correct_frame`] and counted
+        /// against the device. A frame no layer can recover is not an error,
+        /// so its counters and [`Event::FrameUnrecoverable`] are kept; nothing
+        /// is recorded for it. Bytes after the reading, such as padding up to
+        /// a fixed frame size, are ignored.
+        #[pallet::call_index(16)]
+        #[pallet::weight(T::WeightInfo::record_framed_reading(frame.len() as u32))]
+        pub fn record_framed_reading(
+            origin: OriginFor<T>,
+            batch_id: IdOf<T>,
+            device_id: DeviceIdOf<T>,
+            frame: FrameOf<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let (batch, device) = Self::reading_target(&who, &batch_id, &device_id)?;
+
+            let source = CorrectionSource::Device(device_id.clone());
+            let Ok(outcome) = Self::correct_frame(&source, &frame) else {
+                return Ok(());
+            };
+            let reading = FermentationReading::decode(&mut &outcome.payload[..])
+                .map_err(|_| Error::<T>::MalformedReading)?;
+            Self::store_reading(batch_id, batch, device_id, device, reading);
+            Ok(())
+        }
+
+        /// Ends fermentation of a batch, optionally with a quality score out of 100.
+        #[pallet::call_index(13)]
+        #[pallet::weight(T::WeightInfo::complete_batch())]
+        pub fn complete_batch(
+            origin: OriginFor<T>,
+            batch_id: IdOf<T>,
+            quality_score: Option<u8>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(quality_score.is_none_or(|score| score <= 100), Error::<T>::InvalidQualityScore);
+            Batches::<T>::try_mutate(&batch_id, |batch| -> DispatchResult {
+                let batch = batch.as_mut().ok_or(Error::<T>::BatchNotFound)?;
+                ensure!(batch.producer == who, Error::<T>::NotFacilityOwner);
+                ensure!(batch.status == BatchStatus::Fermenting, Error::<T>::BatchNotFermenting);
+                batch.status = BatchStatus::Completed;
+                batch.fermentation_end = Some(frame_system::Pallet::<T>::block_number());
+                batch.quality_score = quality_score;
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::BatchCompleted { batch_id, quality_score });
+            Ok(())
+        }
+
+        /// Asks the auditor who issued one of the caller's certifications to
+        /// extend it to `valid_until`. Replaces any earlier request for it.
+        #[pallet::call_index(14)]
+        #[pallet::weight(T::WeightInfo::request_certification_renewal(T::MaxCertifications::get()))]
+        pub fn request_certification_renewal(
+            origin: OriginFor<T>,
+            facility_id: IdOf<T>,
+            cert_id: NameOf<T>,
+            valid_until: BlockNumberFor<T>,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            ensure!(
+                valid_until > frame_system::Pallet::<T>::block_number(),
+                Error::<T>::CertificationExpired
+            );
+            let facility = Facilities::<T>::get(&facility_id).ok_or(Error::<T>::FacilityNotFound)?;
+            ensure!(facility.owner == who, Error::<T>::NotFacilityOwner);
+            ensure!(facility.status != FacilityStatus::Revoked, Error::<T>::FacilityRevoked);
+            ensure!(
+                facility.certifications.iter().any(|held| held.cert_id == cert_id),
+                Error::<T>::CertificationNotFound
+            );
+
+            RenewalRequests::<T>::insert(&facility_id, &cert_id, valid_until);
+
+            Self::deposit_event(Event::RenewalRequested {
+                facility_id,
+                cert_id,
+                valid_until,
+            });
+            let held = facility.certifications.len() as u32;
+            Ok(Some(T::WeightInfo::request_certification_renewal(held)).into())
+        }
+
+        /// Co-signs the owner's renewal request as the auditor who issued the
+        /// certification, extending it to the requested `valid_until`.
+        #[pallet::call_index(15)]
+        #[pallet::weight(T::WeightInfo::renew_certification(T::MaxCertifications::get()))]
+        pub fn renew_certification(
+            origin: OriginFor<T>,
+            facility_id: IdOf<T>,
+            cert_id: NameOf<T>,
+            valid_until: BlockNumberFor<T>,
+        ) -> DispatchResultWithPostInfo {
+            let who = Self::ensure_auditor(origin)?;
+            ensure!(
+                RenewalRequests::<T>::get(&facility_id, &cert_id) == Some(valid_until),
+                Error::<T>::RenewalNotRequested
+            );
+            ensure!(
+                valid_until > frame_system::Pallet::<T>::block_number(),
+                Error::<T>::CertificationExpired
+            );
+
+            let held = Facilities::<T>::try_mutate(&facility_id, |facility| {
+                let facility = facility.as_mut().ok_or(Error::<T>::FacilityNotFound)?;
+                ensure!(facility.status != FacilityStatus::Revoked, Error::<T>::FacilityRevoked);
+                let held = facility.certifications.len() as u32;
+                let certification = facility
+                    .certifications
+                    .iter_mut()
+                    .find(|held| held.cert_id == cert_id)
+                    .ok_or(Error::<T>::CertificationNotFound)?;
+                ensure!(certification.issuer == who, Error::<T>::NotCertificationIssuer);
+                certification.valid_until = valid_until;
+                Ok::<_, DispatchError>(held)
+            })?;
+            RenewalRequests::<T>::remove(&facility_id, &cert_id);
+            Self::unschedule_expiry(&facility_id, &cert_id);
+            Self::schedule_expiry(&facility_id, &cert_id, valid_until);
+
+            Self::deposit_event(Event::CertificationRenewed {
+                facility_id,
+                cert_id,
+                valid_until,
+            });
+            Ok(Some(T::WeightInfo::renew_certification(held)).into())
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        pub fn is_auditor(account: &T::AccountId) -> bool {
+            Auditors::<T>::contains_key(account)
+        }
+
+        pub fn is_oracle_validator(account: &T::AccountId) -> bool {
+            OracleValidators::<T>::contains_key(account)
+        }
+
+        /// Whether `device_id` is authorized to report for `facility_id`.
+        pub fn is_device_authorized(device_id: &DeviceIdOf<T>, facility_id: &IdOf<T>) -> bool {
+            Devices::<T>::get(device_id).is_some_and(|device| {
+                device.facility_id == *facility_id && device.status == DeviceStatus::Authorized
+            })
+        }
+
+        /// Peels every error-correction layer off `frame`, counting each
+        /// layer's result against `source` and emitting an event for every
+        /// layer that had to correct symbols or gave up.
+        pub fn correct_frame(
+            source: &CorrectionSource<T>,
+            frame: &[u8],
+        ) -> Result<CorrectionOutcome, ErrorCorrectionFailure> {
+            LayerStack::default().decode_observed(frame, |result| {
+                let layer = match result {
+                    Ok(report) => report.layer,
+                    Err(failure) => failure.layer,
+                };
+                CorrectionCounters::<T>::mutate(source, layer, |counters| counters.record(result));
+
+                match result {
+                    Ok(report) if report.symbols_corrected > 0 => Self::deposit_event(Event::FrameCorrected {
+                        source: source.clone(),
+                        layer,
+                        symbols_corrected: report.symbols_corrected,
+                    }),
+                    Ok(_) => {}
+                    Err(failure) => Self::deposit_event(Event::FrameUnrecoverable {
+                        source: source.clone(),
+                        layer,
+                        reason: failure.reason,
+                    }),
+                }
+            })
+        }
+
+        /// The facility registered under `facility_id`.
+        pub fn facility(facility_id: Vec<u8>) -> Option<CultivationFacility<T>> {
+            Facilities::<T>::get(IdOf::<T>::try_from(facility_id).ok()?)
+        }
+
+        /// The facilities registered by `owner`, in registration order.
+        pub fn facilities_by_owner(
+            owner: T::AccountId,
+            page: Page,
+        ) -> Vec<(Vec<u8>, CultivationFacility<T>)> {
+            page.of(FacilitiesByOwner::<T>::get(owner).into_iter().filter_map(|facility_id| {
+                Facilities::<T>::get(&facility_id).map(|facility| (facility_id.into_inner(), facility))
+            }))
+        }
+
+        /// The devices registered for `facility_id`.
+        pub fn facility_devices(facility_id: Vec<u8>, page: Page) -> Vec<(Vec<u8>, TelemetryDevice<T>)> {
+            let Ok(facility_id) = IdOf::<T>::try_from(facility_id) else {
+                return Vec::new();
+            };
+            page.of(FacilityDevices::<T>::iter_key_prefix(facility_id).filter_map(|device_id| {
+                Devices::<T>::get(&device_id).map(|device| (device_id.into_inner(), device))
+            }))
+        }
+
+        /// The culture `scoby_id` followed by its ancestors, breadth first, each
+        /// listed once.
+        pub fn scoby_lineage(scoby_id: Vec<u8>, page: Page) -> Vec<(Vec<u8>, ScobyCulture<T>)> {
+            let Ok(scoby_id) = IdOf::<T>::try_from(scoby_id) else {
+                return Vec::new();
+            };
+            let mut seen = BTreeSet::new();
+            let mut queue = VecDeque::from([scoby_id]);
+            page.of(core::iter::from_fn(move || {
+                while let Some(scoby_id) = queue.pop_front() {
+                    if !seen.insert(scoby_id.clone()) {
+                        continue;
+                    }
+                    if let Some(culture) = ScobyCultures::<T>::get(&scoby_id) {
+                        queue.extend(culture.parent_ids.iter().cloned());
+                        return Some((scoby_id.into_inner(), culture));
+                    }
+                }
+                None
+            }))
+        }
+
+        /// The batch registered under `batch_id`.
+        pub fn batch(batch_id: Vec<u8>) -> Option<KombuchaBatch<T>> {
+            Batches::<T>::get(IdOf::<T>::try_from(batch_id).ok()?)
+        }
+
+        fn ensure_auditor(origin: OriginFor<T>) -> Result<T::AccountId, DispatchError> {
+            let who = ensure_signed(origin)?;
+            ensure!(Self::is_auditor(&who), Error::<T>::NotAuditor);
+            Ok(who)
+        }
+
+        /// Moves a facility to `status` if its lifecycle allows it. A facility
+        /// becoming active is due for an audit after `AuditInterval` blocks;
+        /// one leaving active is no longer due.
+        fn set_status(facility_id: &IdOf<T>, status: FacilityStatus) -> DispatchResult {
+            let certifications = Facilities::<T>::try_mutate(facility_id, |facility| {
+                let facility = facility.as_mut().ok_or(Error::<T>::FacilityNotFound)?;
+                ensure!(facility.status.can_become(status), Error::<T>::InvalidStatusTransition);
+                facility.status = status;
+                Ok::<_, DispatchError>(facility.certifications.clone())
+            })?;
+            if status == FacilityStatus::Active {
+                let now = frame_system::Pallet::<T>::block_number();
+                Self::schedule_audit(facility_id, now.saturating_add(T::AuditInterval::get()));
+            } else {
+                Self::unschedule_audit(facility_id);
+            }
+            // A revoked facility's certifications are no longer watched or renewed
+            if status == FacilityStatus::Revoked {
+                for Certification { cert_id, .. } in certifications {
+                    RenewalRequests::<T>::remove(facility_id, &cert_id);
+                    Self::unschedule_expiry(facility_id, &cert_id);
+                }
+            }
+
+            Self::deposit_event(Event::FacilityStatusChanged {
+                facility_id: facility_id.clone(),
+                status,
+            });
+            Ok(())
+        }
+
+        /// Makes `facility_id` due for an audit by `due`, replacing its previous due block.
+        pub(crate) fn schedule_audit(facility_id: &IdOf<T>, due: BlockNumberFor<T>) {
+            Self::unschedule_audit(facility_id);
+            NextAudit::<T>::insert(facility_id, due);
+            AuditsDue::<T>::insert(due, facility_id, ());
+        }
+
+        fn unschedule_audit(facility_id: &IdOf<T>) {
+            if let Some(due) = NextAudit::<T>::take(facility_id) {
+                AuditsDue::<T>::remove(due, facility_id);
+            }
+        }
+
+        /// Suspends active facilities whose audit was due at or before `now`,
+        /// and reports failed facilities whose follow-up was, taking at most
+        /// `limit` steps. A step is one lapsed facility or one due block found
+        /// empty. Returns the number of steps taken.
+        pub(crate) fn suspend_lapsed(now: BlockNumberFor<T>, limit: u32) -> u32 {
+            let mut cursor = LapseCursor::<T>::get();
+            let mut steps = 0;
+            while cursor <= now && steps < limit {
+                let budget = (limit - steps) as usize;
+                let lapsed: Vec<_> = AuditsDue::<T>::iter_key_prefix(cursor).take(budget).collect();
+                let exhausted = lapsed.len() < budget;
+                steps += lapsed.len() as u32;
+
+                for facility_id in lapsed {
+                    AuditsDue::<T>::remove(cursor, &facility_id);
+                    // Rescheduling removes the old entry
+                    if NextAudit::<T>::get(&facility_id) != Some(cursor) {
+                        continue;
+                    }
+                    // Only active facilities and failed ones awaiting a follow-up are scheduled
+                    let active = Facilities::<T>::get(&facility_id)
+                        .is_some_and(|facility| facility.status == FacilityStatus::Active);
+                    if !active {
+                        Self::unschedule_audit(&facility_id);
+                        Self::deposit_event(Event::FollowUpMissed { facility_id });
+                    } else if Self::set_status(&facility_id, FacilityStatus::Suspended).is_ok() {
+                        Self::deposit_event(Event::AuditLapsed { facility_id });
+                    }
+                }
+                if exhausted {
+                    cursor.saturating_inc();
+                    steps += 1;
+                }
+            }
+            LapseCursor::<T>::put(cursor);
+            steps
+        }
+
+        /// The fermenting batch and authorized device a reading from `who`
+        /// would be recorded against.
+        fn reading_target(
+            who: &T::AccountId,
+            batch_id: &IdOf<T>,
+            device_id: &DeviceIdOf<T>,
+        ) -> Result<(KombuchaBatch<T>, TelemetryDevice<T>), DispatchError> {
+            let batch = Batches::<T>::get(batch_id).ok_or(Error::<T>::BatchNotFound)?;
+            ensure!(batch.status == BatchStatus::Fermenting, Error::<T>::BatchNotFermenting);
+            let facility = Facilities::<T>::get(&batch.facility_id).ok_or(Error::<T>::FacilityNotFound)?;
+            ensure!(facility.owner == *who, Error::<T>::NotFacilityOwner);
+            ensure!(facility.status == FacilityStatus::Active, Error::<T>::FacilityNotActive);
+            let device = Devices::<T>::get(device_id).ok_or(Error::<T>::DeviceNotFound)?;
+            ensure!(device.status == DeviceStatus::Authorized, Error::<T>::DeviceNotAuthorized);
+            ensure!(device.facility_id == batch.facility_id, Error::<T>::DeviceFacilityMismatch);
+            ensure!(batch.readings < T::MaxReadingsPerBatch::get(), Error::<T>::TooManyReadings);
+            Ok((batch, device))
+        }
+
+        /// Appends `reading` to the batch and marks the device active.
+        fn store_reading(
+            batch_id: IdOf<T>,
+            mut batch: KombuchaBatch<T>,
+            device_id: DeviceIdOf<T>,
+            mut device: TelemetryDevice<T>,
+            reading: FermentationReading,
+        ) {
+            let now = frame_system::Pallet::<T>::block_number();
+            let within_parameters = Parameters::<T>::get(&batch.facility_id)
+                .unwrap_or_default()
+                .contains(&reading);
+            let index = batch.readings;
+            BatchReadings::<T>::insert(
+                &batch_id,
+                index,
+                TelemetryReading {
+                    device_id: device_id.clone(),
+                    recorded_at: now,
+                    reading,
+                    within_parameters,
+                },
+            );
+            batch.readings += 1;
+            Batches::<T>::insert(&batch_id, batch);
+            device.last_active = now;
+            Devices::<T>::insert(&device_id, device);
+
+            Self::deposit_event(Event::ReadingRecorded {
+                batch_id,
+                device_id,
+                index,
+                within_parameters,
+            });
+        }
+
+        /// Spends as much of `remaining_weight` as `steps` can use, given its
+        /// `weight` for a number of steps. `steps` takes the most
+        /// steps it may take and returns the number it took.
+        fn idle_steps(
+            remaining_weight: Weight,
+            weight: fn(u32) -> Weight,
+            steps: impl FnOnce(u32) -> u32,
+        ) -> Weight {
+            let base = weight(0);
+            if remaining_weight.any_lt(base) {
+                return Weight::zero();
+            }
+            let per_step = weight(1).saturating_sub(base);
+            let limit = remaining_weight
+                .saturating_sub(base)
+                .checked_div_per_component(&per_step)
+                .unwrap_or(u64::MAX)
+                .min(u32::MAX.into()) as u32;
+
+            weight(steps(limit))
+        }
+
+        /// Queues the warning and lapse notices for a certification valid until
+        /// `valid_until`. Notices already due are queued for the current block.
+        pub(crate) fn schedule_expiry(
+            facility_id: &IdOf<T>,
+            cert_id: &NameOf<T>,
+            valid_until: BlockNumberFor<T>,
+        ) {
+            let now = frame_system::Pallet::<T>::block_number();
+            let key = (facility_id, cert_id);
+            let warn_at = valid_until.saturating_sub(T::ExpiryWarningPeriod::get()).max(now);
+            let lapse_at = valid_until.saturating_add(One::one()).max(now);
+            // Both are only due at once for a certification that had already expired
+            // when `migrations::v2` queued it, so the lapse wins
+            CertificationsDue::<T>::insert(warn_at, key, ExpiryNotice::Expiring);
+            CertificationsDue::<T>::insert(lapse_at, key, ExpiryNotice::Lapsed);
+            ExpiryNotices::<T>::insert(facility_id, cert_id, (warn_at, lapse_at));
+        }
+
+        /// Drops the notices queued for a certification.
+        fn unschedule_expiry(facility_id: &IdOf<T>, cert_id: &NameOf<T>) {
+            if let Some((warn_at, lapse_at)) = ExpiryNotices::<T>::take(facility_id, cert_id) {
+                let key = (facility_id, cert_id);
+                CertificationsDue::<T>::remove(warn_at, key);
+                CertificationsDue::<T>::remove(lapse_at, key);
+            }
+        }
+
+        /// Removes a lapsed certification from its facility, with its renewal
+        /// request and notices.
+        fn drop_certification(facility_id: &IdOf<T>, cert_id: &NameOf<T>) {
+            Facilities::<T>::mutate(facility_id, |facility| {
+                if let Some(facility) = facility {
+                    facility.certifications.retain(|held| held.cert_id != *cert_id);
+                }
+            });
+            RenewalRequests::<T>::remove(facility_id, cert_id);
+            Self::unschedule_expiry(facility_id, cert_id);
+        }
+
+        /// Emits the certification notices due at or before `now`, and removes
+        /// the certifications that lapsed, taking at most `limit` steps. A step
+        /// is one notice or one block found empty. Returns the number of steps taken.
+        pub(crate) fn notify_expiries(now: BlockNumberFor<T>, limit: u32) -> u32 {
+            let mut cursor = ExpiryCursor::<T>::get();
+            let mut steps = 0;
+            while cursor <= now && steps < limit {
+                let budget = (limit - steps) as usize;
+                let due: Vec<_> = CertificationsDue::<T>::iter_prefix(cursor).take(budget).collect();
+                let exhausted = due.len() < budget;
+                steps += due.len() as u32;
+
+                for ((facility_id, cert_id), notice) in due {
+                    CertificationsDue::<T>::remove(cursor, (&facility_id, &cert_id));
+                    let event = Self::expiry_event(cursor, facility_id.clone(), cert_id.clone(), notice);
+                    if let Some(event) = event {
+                        if notice == ExpiryNotice::Lapsed {
+                            Self::drop_certification(&facility_id, &cert_id);
+                        }
+                        Self::deposit_event(event);
+                    }
+                }
+                if exhausted {
+                    cursor.saturating_inc();
+                    steps += 1;
+                }
+            }
+            ExpiryCursor::<T>::put(cursor);
+            steps
+        }
+
+        /// The event for a notice due at `at`, unless the certification has
+        /// since been renewed past it or its facility revoked.
+        fn expiry_event(
+            at: BlockNumberFor<T>,
+            facility_id: IdOf<T>,
+            cert_id: NameOf<T>,
+            notice: ExpiryNotice,
+        ) -> Option<Event<T>> {
+            let facility = Facilities::<T>::get(&facility_id)?;
+            if facility.status == FacilityStatus::Revoked {
+                return None;
+            }
+            let certification = facility.certifications.into_iter().find(|held| held.cert_id == cert_id)?;
+            let Certification { cert_type, valid_until, .. } = certification;
+            match notice {
+                ExpiryNotice::Expiring
+                    if valid_until >= at && valid_until.saturating_sub(T::ExpiryWarningPeriod::get()) <= at =>
+                {
+                    Some(Event::CertificationExpiring {
+                        facility_id,
+                        cert_type,
+                        cert_id,
+                        valid_until,
+                    })
+                }
+                ExpiryNotice::Lapsed if valid_until < at => Some(Event::CertificationLapsed {
+                    facility_id,
+                    cert_type,
+                    cert_id,
+                }),
+                _ => None,
+            }
+        }
+
+        /// The facility exists, is owned by `who` and is active.
+        fn ensure_active_facility(
+            who: &T::AccountId,
+            facility_id: &IdOf<T>,
+        ) -> Result<CultivationFacility<T>, DispatchError> {
+            let facility = Facilities::<T>::get(facility_id).ok_or(Error::<T>::FacilityNotFound)?;
+            ensure!(facility.owner == *who, Error::<T>::NotFacilityOwner);
+            ensure!(facility.status == FacilityStatus::Active, Error::<T>::FacilityNotActive);
+            Ok(facility)
+        }
+
+        /// The facility holds an unexpired certification of every type in `RequiredCertifications`.
+        fn ensure_certified(facility: &CultivationFacility<T>) -> DispatchResult {
+            let now = frame_system::Pallet::<T>::block_number();
+            let certified = T::RequiredCertifications::get().iter().all(|required| {
+                facility
+                    .certifications
+                    .iter()
+                    .any(|held| held.cert_type == *required && held.valid_until >= now)
+            });
+            ensure!(certified, Error::<T>::MissingRequiredCertification);
             Ok(())
         }
     }
@@ -0,0 +1,155 @@
+// Storage migrations for the ELXR pallet
+//
+// See `crate::migrations` for how these are written and tested.
+use frame_support::sp_runtime::traits::Saturating;
+use frame_support::{
+    pallet_prelude::*,
+    traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+};
+use frame_system::pallet_prelude::BlockNumberFor;
+use sp_std::marker::PhantomData;
+#[cfg(feature = "try-runtime")]
+use frame_support::sp_runtime::TryRuntimeError;
+#[cfg(feature = "try-runtime")]
+use sp_std::prelude::*;
+
+use super::*;
+
+/// Version 1 added audit schedules.
+///
+/// Every active facility is made due `AuditInterval` blocks after its latest
+/// audit, or after it was registered if it was never audited. Facilities
+/// already past that are due in the upgrade block, and are suspended once a
+/// block has idle weight left for them. No layout changed, so no aliases are
+/// needed.
+pub mod v1 {
+    use super::*;
+
+    pub struct MigrateToV1<T>(PhantomData<T>);
+
+    impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+        fn on_runtime_upgrade() -> Weight {
+            if Pallet::<T>::on_chain_storage_version() != 0 {
+                return T::DbWeight::get().reads(1);
+            }
+
+            let now = frame_system::Pallet::<T>::block_number();
+            let mut facilities = 0;
+            let mut scheduled = 0;
+            for (facility_id, facility) in Facilities::<T>::iter() {
+                facilities += 1;
+                if facility.status != FacilityStatus::Active {
+                    continue;
+                }
+                let audited = facility.last_audit.unwrap_or(facility.registered_at);
+                let due: BlockNumberFor<T> = audited.saturating_add(T::AuditInterval::get()).max(now);
+                Pallet::<T>::schedule_audit(&facility_id, due);
+                scheduled += 1;
+            }
+            // Nothing was due before the upgrade, so there is nothing behind it to walk
+            LapseCursor::<T>::put(now);
+            StorageVersion::new(1).put::<Pallet<T>>();
+
+            // A facility read each, then a schedule read and two schedule writes per active one
+            T::DbWeight::get().reads_writes(1 + facilities + scheduled, 2 + 2 * scheduled)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            let active = Facilities::<T>::iter_values()
+                .filter(|facility| facility.status == FacilityStatus::Active)
+                .count();
+            Ok((active as u32).encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let before = u32::decode(&mut &state[..]).map_err(|_| "pre-upgrade state does not decode")?;
+            ensure!(Pallet::<T>::on_chain_storage_version() == 1, "storage version was not set to 1");
+
+            let mut scheduled = 0;
+            for (facility_id, facility) in Facilities::<T>::iter() {
+                let due = NextAudit::<T>::get(&facility_id);
+                if facility.status != FacilityStatus::Active {
+                    ensure!(due.is_none(), "an inactive facility is due for an audit");
+                    continue;
+                }
+                let due = due.ok_or("an active facility is not due for an audit")?;
+                let queued = AuditsDue::<T>::contains_key(due, &facility_id);
+                ensure!(queued, "an audit is missing from AuditsDue");
+                scheduled += 1;
+            }
+            ensure!(scheduled == before, "active facilities were lost");
+            Ok(())
+        }
+    }
+}
+
+/// Version 2 added certification expiry notices.
+///
+/// Every certification of a facility that is not revoked is queued for its
+/// warning and lapse notices. Notices already due are queued for the upgrade
+/// block, so certifications that expired before then are reported as lapsed
+/// and removed.
+pub mod v2 {
+    use super::*;
+
+    pub struct MigrateToV2<T>(PhantomData<T>);
+
+    impl<T: Config> OnRuntimeUpgrade for MigrateToV2<T> {
+        fn on_runtime_upgrade() -> Weight {
+            if Pallet::<T>::on_chain_storage_version() != 1 {
+                return T::DbWeight::get().reads(1);
+            }
+
+            let mut facilities = 0;
+            let mut certifications = 0;
+            for (facility_id, facility) in Facilities::<T>::iter() {
+                facilities += 1;
+                if facility.status == FacilityStatus::Revoked {
+                    continue;
+                }
+                for Certification { cert_id, valid_until, .. } in facility.certifications {
+                    Pallet::<T>::schedule_expiry(&facility_id, &cert_id, valid_until);
+                    certifications += 1;
+                }
+            }
+            ExpiryCursor::<T>::put(frame_system::Pallet::<T>::block_number());
+            StorageVersion::new(2).put::<Pallet<T>>();
+
+            T::DbWeight::get().reads_writes(1 + facilities, 2 + 3 * certifications)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            let certifications: usize = Facilities::<T>::iter_values()
+                .filter(|facility| facility.status != FacilityStatus::Revoked)
+                .map(|facility| facility.certifications.len())
+                .sum();
+            Ok((certifications as u32).encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let before = u32::decode(&mut &state[..]).map_err(|_| "pre-upgrade state does not decode")?;
+            ensure!(Pallet::<T>::on_chain_storage_version() == 2, "storage version was not set to 2");
+
+            let mut queued = 0;
+            for (facility_id, facility) in Facilities::<T>::iter() {
+                if facility.status == FacilityStatus::Revoked {
+                    continue;
+                }
+                for Certification { cert_id, .. } in facility.certifications {
+                    let (_, lapse_at) = ExpiryNotices::<T>::get(&facility_id, &cert_id)
+                        .ok_or("a certification has no expiry notices")?;
+                    // The lapse notice is never overwritten, unlike the warning
+                    let notice = CertificationsDue::<T>::get(lapse_at, (&facility_id, &cert_id));
+                    ensure!(notice == Some(ExpiryNotice::Lapsed), "a certification has no lapse notice");
+                    queued += 1;
+                }
+            }
+            ensure!(queued == before, "certifications were lost");
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,96 @@
+// Test runtime for the ELXR pallet
+//
+// Root is the registry origin. The bounds are kept small so the tests can
+// reach every one of them.
+use frame_support::{
+    construct_runtime,
+    pallet_prelude::*,
+    parameter_types,
+    traits::{ConstU32, ConstU64, Everything},
+};
+use frame_system::EnsureRoot;
+use sp_core::H256;
+use sp_runtime::{
+    traits::{BlakeTwo256, IdentityLookup},
+    BuildStorage,
+};
+
+use crate::pallet::{self as pallet_elxr, CertificationType};
+use crate::parachain::signature::PUBLIC_KEY_LENGTH;
+
+type Block = frame_system::mocking::MockBlock<Test>;
+
+construct_runtime!(
+    pub enum Test {
+        System: frame_system,
+        Elxr: pallet_elxr,
+    }
+);
+
+impl frame_system::Config for Test {
+    type BaseCallFilter = Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type RuntimeOrigin = RuntimeOrigin;
+    type RuntimeCall = RuntimeCall;
+    type Nonce = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Block = Block;
+    type RuntimeEvent = RuntimeEvent;
+    type BlockHashCount = ConstU64<250>;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = ();
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = ();
+    type OnSetCode = ();
+    type MaxConsumers = ConstU32<16>;
+}
+
+parameter_types! {
+    /// None unless a test sets them, so batches start without certifications.
+    pub storage RequiredCertifications: Vec<CertificationType> = Vec::new();
+}
+
+impl pallet_elxr::Config for Test {
+    type RuntimeEvent = RuntimeEvent;
+    type RegistryOrigin = EnsureRoot<u64>;
+    type MaxIdLength = ConstU32<16>;
+    type MaxDeviceIdLength = ConstU32<16>;
+    type MaxNameLength = ConstU32<32>;
+    type MaxPublicKeyLength = ConstU32<PUBLIC_KEY_LENGTH>;
+    type MaxCertifications = ConstU32<2>;
+    type MaxFacilitiesPerOwner = ConstU32<2>;
+    type MaxParentCultures = ConstU32<2>;
+    type MaxFlavorNotes = ConstU32<4>;
+    type MaxReadingsPerBatch = ConstU32<2>;
+    type MaxFindingsLength = ConstU32<64>;
+    type MaxFrameLength = ConstU32<1024>;
+    type AuditInterval = ConstU64<100>;
+    type ExpiryWarningPeriod = ConstU64<50>;
+    type RequiredCertifications = RequiredCertifications;
+    type WeightInfo = ();
+}
+
+pub const OWNER: u64 = 1;
+pub const AUDITOR: u64 = 2;
+pub const STRANGER: u64 = 3;
+
+/// `bytes` as a bounded ID, name or key. Panics if it is too long.
+pub fn bounded<S: Get<u32>>(bytes: &[u8]) -> BoundedVec<u8, S> {
+    bytes.to_vec().try_into().expect("test value fits its bound")
+}
+
+/// Externalities at block 1, so that events are recorded.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let storage = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+    let mut ext = sp_io::TestExternalities::new(storage);
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
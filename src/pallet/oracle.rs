@@ -347,30 +347,3 @@ impl ActorX {
         Ok(())
     }
 }
-
-// Error correction integrations
-mod error_correction {
-    // Classical error correction
-    pub mod classical {
-        pub fn correct_errors(data: &[u8]) -> Vec<u8> {
-            // Reed-Solomon implementation
-            data.to_vec()
-        }
-    }
-    
-    // Bridge error correction
-    pub mod bridge {
-        pub fn correct_interface_errors(data: &[u8]) -> Vec<u8> {
-            // Bridge protocol implementation
-            data.to_vec()
-        }
-    }
-    
-    // Quantum error correction
-    pub mod quantum {
-        pub fn correct_quantum_errors(data: &[u8]) -> Vec<u8> {
-            // Surface code implementation
-            data.to_vec()
-        }
-    }
-}
@@ -0,0 +1,1380 @@
+// Extrinsic, error and event coverage for the ELXR pallet
+use codec::Encode;
+use frame_support::traits::{GetStorageVersion, Hooks, StorageVersion};
+use frame_support::weights::Weight;
+use frame_support::dispatch::{DispatchResult, DispatchResultWithPostInfo};
+use frame_support::{assert_noop, assert_ok};
+use sp_runtime::{BuildStorage, DispatchError};
+
+use super::error_correction::{encode_comprehensive, CorrectionConfig, CorrectionLayer, FailureReason};
+use super::migrations::{v1::MigrateToV1, v2::MigrateToV2};
+use super::mock::*;
+use super::*;
+use crate::error::stats::LayerCounters;
+use crate::migrations::run_upgrade;
+use crate::parachain::signature::PUBLIC_KEY_LENGTH;
+use crate::runtime_api::Page;
+
+const FACILITY: &[u8] = b"FAC001";
+const OTHER_FACILITY: &[u8] = b"FAC002";
+const DEVICE: &[u8] = b"ELXR-01";
+const SCOBY: &[u8] = b"SCOBY-7";
+const BATCH: &[u8] = b"BATCH-12";
+
+fn id(bytes: &[u8]) -> IdOf<Test> {
+    bounded(bytes)
+}
+
+fn device_id(bytes: &[u8]) -> DeviceIdOf<Test> {
+    bounded(bytes)
+}
+
+fn name(bytes: &[u8]) -> NameOf<Test> {
+    bounded(bytes)
+}
+
+/// A key of Dilithium2 public key length.
+fn public_key() -> PublicKeyOf<Test> {
+    bounded(&[7; PUBLIC_KEY_LENGTH as usize])
+}
+
+fn register_facility(who: u64, facility: &[u8]) -> DispatchResult {
+    Elxr::register_facility(
+        RuntimeOrigin::signed(who),
+        id(facility),
+        bounded(b"Test Facility"),
+        (100, 200),
+        1000,
+    )
+}
+
+/// Registers `facility` for `OWNER` and has `AUDITOR` activate it.
+fn activate(facility: &[u8]) {
+    assert_ok!(register_facility(OWNER, facility));
+    if !Elxr::is_auditor(&AUDITOR) {
+        assert_ok!(Elxr::add_auditor(RuntimeOrigin::root(), AUDITOR));
+    }
+    assert_ok!(Elxr::update_facility_status(
+        RuntimeOrigin::signed(AUDITOR),
+        id(facility),
+        FacilityStatus::Active
+    ));
+}
+
+fn set_status(origin: RuntimeOrigin, facility: &[u8], status: FacilityStatus) -> DispatchResult {
+    Elxr::update_facility_status(origin, id(facility), status)
+}
+
+fn audit(facility: &[u8], outcome: AuditOutcome, follow_up_by: Option<u64>) -> DispatchResult {
+    Elxr::perform_audit(
+        RuntimeOrigin::signed(AUDITOR),
+        id(facility),
+        outcome,
+        bounded(b"Fermenters clean"),
+        follow_up_by,
+    )
+}
+
+fn certify(facility: &[u8], cert_id: &[u8], valid_until: u64) -> DispatchResultWithPostInfo {
+    Elxr::add_certification(
+        RuntimeOrigin::signed(AUDITOR),
+        id(facility),
+        CertificationType::HACCP,
+        bounded(cert_id),
+        valid_until,
+    )
+}
+
+fn request_renewal(who: u64, cert_id: &[u8], valid_until: u64) -> DispatchResultWithPostInfo {
+    Elxr::request_certification_renewal(RuntimeOrigin::signed(who), id(FACILITY), name(cert_id), valid_until)
+}
+
+fn renew(who: u64, cert_id: &[u8], valid_until: u64) -> DispatchResultWithPostInfo {
+    Elxr::renew_certification(RuntimeOrigin::signed(who), id(FACILITY), name(cert_id), valid_until)
+}
+
+/// Certification expiry events, oldest first.
+fn expiry_events() -> Vec<Event<Test>> {
+    System::events()
+        .into_iter()
+        .filter_map(|record| match record.event {
+            RuntimeEvent::Elxr(
+                event @ (Event::CertificationExpiring { .. } | Event::CertificationLapsed { .. }),
+            ) => Some(event),
+            _ => None,
+        })
+        .collect()
+}
+
+fn status(facility: &[u8]) -> FacilityStatus {
+    Facilities::<Test>::get(id(facility)).unwrap().status
+}
+
+fn register_device(device: &[u8], facility: &[u8]) -> DispatchResult {
+    Elxr::register_device(
+        RuntimeOrigin::signed(OWNER),
+        device_id(device),
+        id(facility),
+        public_key(),
+        bounded(b"1.4.2"),
+    )
+}
+
+fn characteristics() -> ScobyCharacteristics<Test> {
+    ScobyCharacteristics {
+        acidity: 6,
+        fermentation_speed: 4,
+        flavor_notes: vec![bounded(b"tart"), bounded(b"floral")].try_into().unwrap(),
+        thickness: 85,
+        avg_fermentation_days: 10,
+    }
+}
+
+fn register_scoby(scoby: &[u8], facility: &[u8], parents: &[&[u8]]) -> DispatchResult {
+    Elxr::register_scoby(
+        RuntimeOrigin::signed(OWNER),
+        id(scoby),
+        id(facility),
+        bounded(b"Mother"),
+        parents.iter().map(|parent| id(parent)).collect::<Vec<_>>().try_into().unwrap(),
+        bounded(b"Tbilisi"),
+        characteristics(),
+    )
+}
+
+fn start_batch(batch: &[u8], facility: &[u8], scoby: &[u8]) -> DispatchResult {
+    Elxr::start_batch(RuntimeOrigin::signed(OWNER), id(batch), id(facility), id(scoby))
+}
+
+/// An active facility with a device and a SCOBY culture, fermenting `BATCH`.
+fn fermenting_batch() {
+    activate(FACILITY);
+    assert_ok!(register_device(DEVICE, FACILITY));
+    assert_ok!(register_scoby(SCOBY, FACILITY, &[]));
+    assert_ok!(start_batch(BATCH, FACILITY, SCOBY));
+}
+
+fn reading() -> FermentationReading {
+    FermentationReading {
+        ph: 310,
+        temperature: 2450,
+        density: 1012,
+        co2: 6000,
+    }
+}
+
+fn record(who: u64, batch: &[u8], device: &[u8], reading: FermentationReading) -> DispatchResult {
+    Elxr::record_reading(RuntimeOrigin::signed(who), id(batch), device_id(device), reading)
+}
+
+fn parameters() -> CultivationParameters {
+    CultivationParameters {
+        ph_range: (280, 340),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn register_facility_works() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(register_facility(OWNER, FACILITY));
+
+        let facility = Facilities::<Test>::get(id(FACILITY)).unwrap();
+        assert_eq!(facility.name.to_vec(), b"Test Facility".to_vec());
+        assert_eq!(facility.status, FacilityStatus::Pending);
+        assert_eq!(facility.owner, OWNER);
+        assert_eq!(facility.registered_at, 1);
+        assert_eq!(facility.last_audit, None);
+        assert_eq!(FacilitiesByOwner::<Test>::get(OWNER).into_inner(), vec![id(FACILITY)]);
+        assert_eq!(Parameters::<Test>::get(id(FACILITY)), Some(CultivationParameters::default()));
+        assert_eq!(FacilityCount::<Test>::get(), 1);
+        System::assert_last_event(
+            Event::FacilityRegistered {
+                facility_id: id(FACILITY),
+                owner: OWNER,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn register_facility_rejects_duplicates_and_too_many() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(register_facility(OWNER, FACILITY));
+        assert_noop!(register_facility(STRANGER, FACILITY), Error::<Test>::FacilityAlreadyExists);
+
+        assert_ok!(register_facility(OWNER, OTHER_FACILITY));
+        assert_noop!(register_facility(OWNER, b"FAC003"), Error::<Test>::TooManyFacilities);
+    });
+}
+
+#[test]
+fn facility_lifecycle_is_enforced() {
+    use FacilityStatus::*;
+    new_test_ext().execute_with(|| {
+        activate(FACILITY);
+        System::assert_last_event(
+            Event::FacilityStatusChanged {
+                facility_id: id(FACILITY),
+                status: Active,
+            }
+            .into(),
+        );
+        assert_eq!(NextAudit::<Test>::get(id(FACILITY)), Some(101));
+
+        // Auditors suspend and reinstate, and nobody else does
+        let auditor = || RuntimeOrigin::signed(AUDITOR);
+        assert_noop!(set_status(RuntimeOrigin::root(), FACILITY, Suspended), DispatchError::BadOrigin);
+        assert_noop!(set_status(RuntimeOrigin::root(), FACILITY, Pending), DispatchError::BadOrigin);
+        let owner = RuntimeOrigin::signed(OWNER);
+        assert_noop!(set_status(owner, FACILITY, Suspended), Error::<Test>::NotAuditor);
+        assert_ok!(set_status(auditor(), FACILITY, Suspended));
+        assert_eq!(status(FACILITY), Suspended);
+        assert_eq!(NextAudit::<Test>::get(id(FACILITY)), None);
+        assert_eq!(AuditsDue::<Test>::get(101, id(FACILITY)), None);
+        assert_noop!(set_status(auditor(), FACILITY, Suspended), Error::<Test>::InvalidStatusTransition);
+        assert_noop!(set_status(auditor(), FACILITY, Pending), Error::<Test>::InvalidStatusTransition);
+        System::set_block_number(10);
+        assert_ok!(set_status(auditor(), FACILITY, Active));
+        assert_eq!(NextAudit::<Test>::get(id(FACILITY)), Some(110));
+
+        // Only governance revokes, and a revoked facility stays revoked
+        assert_noop!(set_status(auditor(), FACILITY, Revoked), DispatchError::BadOrigin);
+        assert_ok!(set_status(RuntimeOrigin::root(), FACILITY, Revoked));
+        assert_eq!(NextAudit::<Test>::get(id(FACILITY)), None);
+        assert_noop!(set_status(auditor(), FACILITY, Active), Error::<Test>::InvalidStatusTransition);
+        assert_noop!(
+            set_status(RuntimeOrigin::root(), FACILITY, Revoked),
+            Error::<Test>::InvalidStatusTransition
+        );
+
+        // Pending facilities can be revoked but not suspended
+        assert_ok!(register_facility(OWNER, OTHER_FACILITY));
+        assert_noop!(
+            set_status(auditor(), OTHER_FACILITY, Suspended),
+            Error::<Test>::InvalidStatusTransition
+        );
+        assert_ok!(set_status(RuntimeOrigin::root(), OTHER_FACILITY, Revoked));
+
+        assert_noop!(set_status(RuntimeOrigin::root(), b"FAC404", Revoked), Error::<Test>::FacilityNotFound);
+    });
+}
+
+#[test]
+fn register_device_works() {
+    new_test_ext().execute_with(|| {
+        activate(FACILITY);
+        System::set_block_number(3);
+        assert_ok!(register_device(DEVICE, FACILITY));
+
+        let device = Devices::<Test>::get(device_id(DEVICE)).unwrap();
+        assert_eq!(device.facility_id, id(FACILITY));
+        assert_eq!(device.status, DeviceStatus::Authorized);
+        assert_eq!((device.registered_at, device.last_active), (3, 3));
+        assert!(FacilityDevices::<Test>::contains_key(id(FACILITY), device_id(DEVICE)));
+        assert_eq!(DeviceCount::<Test>::get(), 1);
+        assert!(Elxr::is_device_authorized(&device_id(DEVICE), &id(FACILITY)));
+        System::assert_last_event(
+            Event::DeviceAuthorized {
+                device_id: device_id(DEVICE),
+                facility_id: id(FACILITY),
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn register_device_checks_the_facility() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(register_device(DEVICE, FACILITY), Error::<Test>::FacilityNotFound);
+
+        assert_ok!(register_facility(OWNER, FACILITY));
+        assert_noop!(register_device(DEVICE, FACILITY), Error::<Test>::FacilityNotActive);
+
+        assert_ok!(register_facility(STRANGER, OTHER_FACILITY));
+        assert_noop!(register_device(DEVICE, OTHER_FACILITY), Error::<Test>::NotFacilityOwner);
+
+        activate(b"FAC003");
+        assert_ok!(register_device(DEVICE, b"FAC003"));
+        assert_noop!(register_device(DEVICE, b"FAC003"), Error::<Test>::DeviceAlreadyExists);
+    });
+}
+
+#[test]
+fn register_device_rejects_keys_that_are_not_dilithium_keys() {
+    new_test_ext().execute_with(|| {
+        activate(FACILITY);
+        for len in [32, PUBLIC_KEY_LENGTH as usize - 1] {
+            assert_noop!(
+                Elxr::register_device(
+                    RuntimeOrigin::signed(OWNER),
+                    device_id(DEVICE),
+                    id(FACILITY),
+                    bounded(&vec![7; len]),
+                    bounded(b"1.4.2"),
+                ),
+                Error::<Test>::InvalidPublicKey
+            );
+        }
+    });
+}
+
+#[test]
+fn update_device_status_by_owner_or_governance() {
+    new_test_ext().execute_with(|| {
+        activate(FACILITY);
+        assert_ok!(register_device(DEVICE, FACILITY));
+
+        assert_ok!(Elxr::update_device_status(
+            RuntimeOrigin::signed(OWNER),
+            device_id(DEVICE),
+            DeviceStatus::Suspended
+        ));
+        System::assert_last_event(
+            Event::DeviceStatusChanged {
+                device_id: device_id(DEVICE),
+                status: DeviceStatus::Suspended,
+            }
+            .into(),
+        );
+        assert!(!Elxr::is_device_authorized(&device_id(DEVICE), &id(FACILITY)));
+
+        assert_ok!(Elxr::update_device_status(
+            RuntimeOrigin::root(),
+            device_id(DEVICE),
+            DeviceStatus::Revoked
+        ));
+        assert_eq!(Devices::<Test>::get(device_id(DEVICE)).unwrap().status, DeviceStatus::Revoked);
+
+        assert_noop!(
+            Elxr::update_device_status(
+                RuntimeOrigin::signed(STRANGER),
+                device_id(DEVICE),
+                DeviceStatus::Authorized
+            ),
+            Error::<Test>::NotFacilityOwner
+        );
+        // Revocation is final, even for governance
+        for origin in [RuntimeOrigin::signed(OWNER), RuntimeOrigin::root()] {
+            assert_noop!(
+                Elxr::update_device_status(origin, device_id(DEVICE), DeviceStatus::Authorized),
+                Error::<Test>::DeviceRevoked
+            );
+        }
+        assert_noop!(
+            Elxr::update_device_status(RuntimeOrigin::root(), device_id(b"ELXR-99"), DeviceStatus::Revoked),
+            Error::<Test>::DeviceNotFound
+        );
+    });
+}
+
+#[test]
+fn update_parameters_by_owner_or_auditor() {
+    new_test_ext().execute_with(|| {
+        activate(FACILITY);
+
+        assert_ok!(Elxr::update_parameters(RuntimeOrigin::signed(OWNER), id(FACILITY), parameters()));
+        assert_eq!(Parameters::<Test>::get(id(FACILITY)), Some(parameters()));
+        System::assert_last_event(Event::ParametersUpdated { facility_id: id(FACILITY) }.into());
+
+        let widened = CultivationParameters {
+            ph_range: (250, 400),
+            ..parameters()
+        };
+        assert_ok!(Elxr::update_parameters(RuntimeOrigin::signed(AUDITOR), id(FACILITY), widened));
+        assert_eq!(Parameters::<Test>::get(id(FACILITY)), Some(widened));
+
+        assert_noop!(
+            Elxr::update_parameters(RuntimeOrigin::signed(STRANGER), id(FACILITY), parameters()),
+            Error::<Test>::NotFacilityOwner
+        );
+        assert_noop!(
+            Elxr::update_parameters(RuntimeOrigin::signed(OWNER), id(b"FAC404"), parameters()),
+            Error::<Test>::FacilityNotFound
+        );
+    });
+}
+
+#[test]
+fn parameters_must_be_non_empty_and_within_limits() {
+    new_test_ext().execute_with(|| {
+        activate(FACILITY);
+        let inverted = CultivationParameters {
+            temp_range: (3000, 2000),
+            ..parameters()
+        };
+        let alkaline = CultivationParameters {
+            ph_range: (650, 900),
+            ..parameters()
+        };
+
+        for invalid in [inverted, alkaline] {
+            assert_noop!(
+                Elxr::update_parameters(RuntimeOrigin::signed(OWNER), id(FACILITY), invalid),
+                Error::<Test>::InvalidParameters
+            );
+            assert_noop!(
+                Elxr::update_default_parameters(RuntimeOrigin::root(), invalid),
+                Error::<Test>::InvalidParameters
+            );
+        }
+    });
+}
+
+#[test]
+fn add_certification_works() {
+    new_test_ext().execute_with(|| {
+        activate(FACILITY);
+        assert_ok!(Elxr::add_certification(
+            RuntimeOrigin::signed(AUDITOR),
+            id(FACILITY),
+            CertificationType::HACCP,
+            bounded(b"HACCP-2291"),
+            1000
+        ));
+
+        let certifications = Facilities::<Test>::get(id(FACILITY)).unwrap().certifications;
+        assert_eq!(certifications.len(), 1);
+        assert_eq!(certifications[0].cert_type, CertificationType::HACCP);
+        assert_eq!(certifications[0].issuer, AUDITOR);
+        assert_eq!(certifications[0].valid_until, 1000);
+        System::assert_last_event(
+            Event::CertificationAdded {
+                facility_id: id(FACILITY),
+                cert_type: CertificationType::HACCP,
+                valid_until: 1000,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn add_certification_errors() {
+    new_test_ext().execute_with(|| {
+        activate(FACILITY);
+        let certify = |who: u64, facility: &[u8], cert_id: &[u8], valid_until: u64| {
+            Elxr::add_certification(
+                RuntimeOrigin::signed(who),
+                id(facility),
+                CertificationType::Organic,
+                bounded(cert_id),
+                valid_until,
+            )
+        };
+
+        assert_noop!(certify(OWNER, FACILITY, b"ORG-1", 1000), Error::<Test>::NotAuditor);
+        assert_noop!(certify(AUDITOR, FACILITY, b"ORG-1", 1), Error::<Test>::CertificationExpired);
+        assert_noop!(certify(AUDITOR, b"FAC404", b"ORG-1", 1000), Error::<Test>::FacilityNotFound);
+
+        assert_ok!(certify(AUDITOR, FACILITY, b"ORG-1", 1000));
+        assert_noop!(certify(AUDITOR, FACILITY, b"ORG-1", 2000), Error::<Test>::CertificationAlreadyHeld);
+        assert_ok!(certify(AUDITOR, FACILITY, b"ORG-2", 2000));
+        assert_noop!(certify(AUDITOR, FACILITY, b"ORG-3", 3000), Error::<Test>::TooManyCertifications);
+    });
+}
+
+#[test]
+fn certifications_warn_before_expiring_and_report_lapses() {
+    new_test_ext().execute_with(|| {
+        activate(FACILITY);
+        assert_ok!(certify(FACILITY, b"HACCP-1", 200));
+        // Already inside the warning period, so warned about straight away
+        assert_ok!(certify(FACILITY, b"HACCP-2", 30));
+        let expiring = |cert_id: &[u8], valid_until: u64| Event::CertificationExpiring {
+            facility_id: id(FACILITY),
+            cert_type: CertificationType::HACCP,
+            cert_id: bounded(cert_id),
+            valid_until,
+        };
+        let lapsed = |cert_id: &[u8]| Event::CertificationLapsed {
+            facility_id: id(FACILITY),
+            cert_type: CertificationType::HACCP,
+            cert_id: bounded(cert_id),
+        };
+
+        Elxr::on_idle(1, Weight::MAX);
+        assert_eq!(expiry_events(), vec![expiring(b"HACCP-2", 30)]);
+        Elxr::on_idle(149, Weight::MAX);
+        assert_eq!(expiry_events(), vec![expiring(b"HACCP-2", 30), lapsed(b"HACCP-2")]);
+        Elxr::on_idle(150, Weight::MAX);
+        assert_eq!(expiry_events().last(), Some(&expiring(b"HACCP-1", 200)));
+
+        // A lapsed certification is removed, so a new one can take its place
+        let held = || -> Vec<_> {
+            let facility = Facilities::<Test>::get(id(FACILITY)).unwrap();
+            facility.certifications.into_iter().map(|held| held.cert_id).collect()
+        };
+        assert_eq!(held(), vec![name(b"HACCP-1")]);
+        assert_eq!(ExpiryNotices::<Test>::get(id(FACILITY), name(b"HACCP-2")), None);
+        assert_ok!(certify(FACILITY, b"HACCP-3", 1_000));
+
+        // Valid through `valid_until`, lapsed the block after
+        Elxr::on_idle(200, Weight::MAX);
+        assert_eq!(expiry_events().len(), 3);
+        Elxr::on_idle(201, Weight::MAX);
+        assert_eq!(expiry_events().last(), Some(&lapsed(b"HACCP-1")));
+        assert_eq!(held(), vec![name(b"HACCP-3")]);
+        assert_eq!(CertificationsDue::<Test>::iter().count(), 2);
+        assert_eq!(ExpiryNotices::<Test>::iter().count(), 1);
+    });
+}
+
+#[test]
+fn revoking_a_facility_drops_its_notices_and_renewal_requests() {
+    new_test_ext().execute_with(|| {
+        activate(FACILITY);
+        assert_ok!(certify(FACILITY, b"HACCP-1", 200));
+        assert_ok!(certify(FACILITY, b"HACCP-2", 300));
+        assert_ok!(request_renewal(OWNER, b"HACCP-1", 500));
+
+        assert_ok!(set_status(RuntimeOrigin::root(), FACILITY, FacilityStatus::Revoked));
+        assert_eq!(CertificationsDue::<Test>::iter().count(), 0);
+        assert_eq!(ExpiryNotices::<Test>::iter().count(), 0);
+        assert_eq!(RenewalRequests::<Test>::iter().count(), 0);
+        Elxr::on_idle(301, Weight::MAX);
+        assert_eq!(expiry_events(), vec![]);
+    });
+}
+
+#[test]
+fn renewals_drop_notices_queued_for_the_current_block() {
+    new_test_ext().execute_with(|| {
+        activate(FACILITY);
+        // Inside the warning period, so its warning is due straight away
+        assert_ok!(certify(FACILITY, b"HACCP-1", 30));
+        assert_eq!(ExpiryNotices::<Test>::get(id(FACILITY), name(b"HACCP-1")), Some((1, 31)));
+        assert_ok!(request_renewal(OWNER, b"HACCP-1", 500));
+        assert_ok!(renew(AUDITOR, b"HACCP-1", 500));
+
+        let mut due: Vec<_> = CertificationsDue::<Test>::iter().map(|(at, _, notice)| (at, notice)).collect();
+        due.sort_by_key(|(at, _)| *at);
+        assert_eq!(due, vec![(450, ExpiryNotice::Expiring), (501, ExpiryNotice::Lapsed)]);
+        assert_eq!(ExpiryNotices::<Test>::get(id(FACILITY), name(b"HACCP-1")), Some((450, 501)));
+    });
+}
+
+#[test]
+fn renewals_need_the_owner_and_the_issuing_auditor() {
+    new_test_ext().execute_with(|| {
+        activate(FACILITY);
+        assert_ok!(certify(FACILITY, b"HACCP-1", 200));
+
+        // The issuing auditor cannot extend it alone
+        assert_noop!(renew(AUDITOR, b"HACCP-1", 500), Error::<Test>::RenewalNotRequested);
+        assert_noop!(request_renewal(STRANGER, b"HACCP-1", 500), Error::<Test>::NotFacilityOwner);
+        assert_noop!(request_renewal(OWNER, b"HACCP-1", 1), Error::<Test>::CertificationExpired);
+        assert_noop!(request_renewal(OWNER, b"HACCP-9", 500), Error::<Test>::CertificationNotFound);
+        assert_ok!(request_renewal(OWNER, b"HACCP-1", 500));
+        System::assert_last_event(
+            Event::RenewalRequested {
+                facility_id: id(FACILITY),
+                cert_id: bounded(b"HACCP-1"),
+                valid_until: 500,
+            }
+            .into(),
+        );
+        assert_eq!(Facilities::<Test>::get(id(FACILITY)).unwrap().certifications[0].valid_until, 200);
+
+        // Nor can anyone but the issuer approve it, or on other terms
+        assert_noop!(renew(AUDITOR, b"HACCP-1", 600), Error::<Test>::RenewalNotRequested);
+        assert_noop!(renew(OWNER, b"HACCP-1", 500), Error::<Test>::NotAuditor);
+        assert_ok!(Elxr::add_auditor(RuntimeOrigin::root(), STRANGER));
+        assert_noop!(renew(STRANGER, b"HACCP-1", 500), Error::<Test>::NotCertificationIssuer);
+
+        assert_ok!(renew(AUDITOR, b"HACCP-1", 500));
+        System::assert_last_event(
+            Event::CertificationRenewed {
+                facility_id: id(FACILITY),
+                cert_id: bounded(b"HACCP-1"),
+                valid_until: 500,
+            }
+            .into(),
+        );
+        assert_eq!(Facilities::<Test>::get(id(FACILITY)).unwrap().certifications[0].valid_until, 500);
+        assert_eq!(RenewalRequests::<Test>::get(id(FACILITY), name(b"HACCP-1")), None);
+        assert_noop!(renew(AUDITOR, b"HACCP-1", 500), Error::<Test>::RenewalNotRequested);
+
+        // The notices follow the new expiry
+        Elxr::on_idle(449, Weight::MAX);
+        assert_eq!(expiry_events(), vec![]);
+        Elxr::on_idle(450, Weight::MAX);
+        assert_eq!(
+            expiry_events(),
+            vec![Event::CertificationExpiring {
+                facility_id: id(FACILITY),
+                cert_type: CertificationType::HACCP,
+                cert_id: bounded(b"HACCP-1"),
+                valid_until: 500,
+            }]
+        );
+    });
+}
+
+#[test]
+fn batches_need_the_required_certifications() {
+    new_test_ext().execute_with(|| {
+        RequiredCertifications::set(&vec![CertificationType::HACCP]);
+        activate(FACILITY);
+        assert_ok!(register_scoby(SCOBY, FACILITY, &[]));
+        assert_noop!(start_batch(BATCH, FACILITY, SCOBY), Error::<Test>::MissingRequiredCertification);
+
+        // Only a certification of the required type counts
+        assert_ok!(Elxr::add_certification(
+            RuntimeOrigin::signed(AUDITOR),
+            id(FACILITY),
+            CertificationType::Organic,
+            bounded(b"ORG-1"),
+            500
+        ));
+        assert_noop!(start_batch(BATCH, FACILITY, SCOBY), Error::<Test>::MissingRequiredCertification);
+        assert_ok!(certify(FACILITY, b"HACCP-1", 20));
+        assert_ok!(start_batch(BATCH, FACILITY, SCOBY));
+
+        // It holds through its last block, then blocks new batches until renewed
+        System::set_block_number(20);
+        assert_ok!(start_batch(b"BATCH-13", FACILITY, SCOBY));
+        System::set_block_number(21);
+        assert_noop!(start_batch(b"BATCH-14", FACILITY, SCOBY), Error::<Test>::MissingRequiredCertification);
+        assert_ok!(request_renewal(OWNER, b"HACCP-1", 500));
+        assert_ok!(renew(AUDITOR, b"HACCP-1", 500));
+        assert_ok!(start_batch(b"BATCH-14", FACILITY, SCOBY));
+    });
+}
+
+#[test]
+fn audits_record_findings_and_drive_the_status() {
+    use AuditOutcome::*;
+    new_test_ext().execute_with(|| {
+        assert_ok!(register_facility(OWNER, FACILITY));
+        assert_ok!(Elxr::add_auditor(RuntimeOrigin::root(), AUDITOR));
+        System::set_block_number(42);
+
+        // A pass activates a pending facility, due again by its follow-up
+        assert_ok!(audit(FACILITY, Pass, Some(60)));
+        assert_eq!(
+            Audits::<Test>::get(id(FACILITY)),
+            Some(Audit {
+                auditor: AUDITOR,
+                outcome: Pass,
+                findings: bounded(b"Fermenters clean"),
+                follow_up_by: Some(60),
+                audited_at: 42,
+            })
+        );
+        assert_eq!(Facilities::<Test>::get(id(FACILITY)).unwrap().last_audit, Some(42));
+        assert_eq!(status(FACILITY), FacilityStatus::Active);
+        assert_eq!(NextAudit::<Test>::get(id(FACILITY)), Some(60));
+        System::assert_has_event(
+            Event::AuditPerformed {
+                facility_id: id(FACILITY),
+                auditor: AUDITOR,
+                outcome: Pass,
+            }
+            .into(),
+        );
+
+        // A fail suspends it, and a later pass reinstates it for a full interval
+        assert_ok!(audit(FACILITY, Fail, None));
+        assert_eq!(status(FACILITY), FacilityStatus::Suspended);
+        assert_eq!(NextAudit::<Test>::get(id(FACILITY)), None);
+        assert_ok!(audit(FACILITY, Fail, None));
+        assert_eq!(status(FACILITY), FacilityStatus::Suspended);
+        System::set_block_number(50);
+        assert_ok!(audit(FACILITY, Pass, None));
+        assert_eq!(status(FACILITY), FacilityStatus::Active);
+        assert_eq!(NextAudit::<Test>::get(id(FACILITY)), Some(150));
+
+        // A follow-up later than the interval does not push the next audit back
+        assert_ok!(audit(FACILITY, Pass, Some(500)));
+        assert_eq!(NextAudit::<Test>::get(id(FACILITY)), Some(150));
+        assert_eq!(AuditsDue::<Test>::iter_keys().collect::<Vec<_>>(), vec![(150, id(FACILITY))]);
+
+        assert_noop!(audit(FACILITY, Pass, Some(50)), Error::<Test>::FollowUpInPast);
+        assert_noop!(
+            Elxr::perform_audit(RuntimeOrigin::signed(STRANGER), id(FACILITY), Pass, bounded(b""), None),
+            Error::<Test>::NotAuditor
+        );
+        assert_noop!(audit(b"FAC404", Pass, None), Error::<Test>::FacilityNotFound);
+
+        assert_ok!(set_status(RuntimeOrigin::root(), FACILITY, FacilityStatus::Revoked));
+        assert_noop!(audit(FACILITY, Pass, None), Error::<Test>::FacilityRevoked);
+    });
+}
+
+#[test]
+fn facilities_are_suspended_when_their_audit_lapses() {
+    new_test_ext().execute_with(|| {
+        activate(FACILITY);
+        activate(OTHER_FACILITY);
+        System::set_block_number(60);
+        assert_ok!(audit(OTHER_FACILITY, AuditOutcome::Pass, None));
+
+        Elxr::on_idle(100, Weight::MAX);
+        assert_eq!(status(FACILITY), FacilityStatus::Active);
+
+        Elxr::on_idle(101, Weight::MAX);
+        assert_eq!(status(FACILITY), FacilityStatus::Suspended);
+        assert_eq!(NextAudit::<Test>::get(id(FACILITY)), None);
+        System::assert_last_event(Event::AuditLapsed { facility_id: id(FACILITY) }.into());
+        assert_eq!(status(OTHER_FACILITY), FacilityStatus::Active);
+        assert_eq!(LapseCursor::<Test>::get(), 102);
+
+        // Lapses wait for a block with idle weight left
+        assert_eq!(Elxr::on_idle(200, Weight::zero()), Weight::zero());
+        assert_eq!(status(OTHER_FACILITY), FacilityStatus::Active);
+        assert_ne!(Elxr::on_idle(200, Weight::MAX), Weight::zero());
+        assert_eq!(status(OTHER_FACILITY), FacilityStatus::Suspended);
+    });
+}
+
+#[test]
+fn failed_facilities_are_reported_when_their_follow_up_lapses() {
+    use AuditOutcome::*;
+    new_test_ext().execute_with(|| {
+        activate(FACILITY);
+        activate(OTHER_FACILITY);
+        System::set_block_number(42);
+
+        // A fail with a follow-up makes the suspended facility due again by then
+        assert_ok!(audit(FACILITY, Fail, Some(60)));
+        assert_ok!(audit(OTHER_FACILITY, Fail, Some(70)));
+        assert_eq!(status(FACILITY), FacilityStatus::Suspended);
+        assert_eq!(NextAudit::<Test>::get(id(FACILITY)), Some(60));
+        assert_eq!(AuditsDue::<Test>::iter_prefix(101).count(), 0);
+
+        // Passing the follow-up in time reinstates the facility for a full interval
+        System::set_block_number(65);
+        assert_ok!(audit(OTHER_FACILITY, Pass, None));
+        assert_eq!(NextAudit::<Test>::get(id(OTHER_FACILITY)), Some(165));
+
+        Elxr::on_idle(59, Weight::MAX);
+        assert_eq!(NextAudit::<Test>::get(id(FACILITY)), Some(60));
+        Elxr::on_idle(70, Weight::MAX);
+        assert_eq!(status(FACILITY), FacilityStatus::Suspended);
+        assert_eq!(NextAudit::<Test>::get(id(FACILITY)), None);
+        System::assert_has_event(Event::FollowUpMissed { facility_id: id(FACILITY) }.into());
+        assert_eq!(status(OTHER_FACILITY), FacilityStatus::Active);
+        assert!(!System::events().iter().any(|record| {
+            record.event == Event::FollowUpMissed { facility_id: id(OTHER_FACILITY) }.into()
+        }));
+    });
+}
+
+#[test]
+fn lapses_are_found_a_step_at_a_time() {
+    new_test_ext().execute_with(|| {
+        activate(FACILITY);
+        activate(OTHER_FACILITY);
+
+        // Both are due at 101, so the third step finishes that block
+        assert_eq!(Elxr::suspend_lapsed(101, 101), 101);
+        assert_eq!(LapseCursor::<Test>::get(), 101);
+        assert_eq!(Elxr::suspend_lapsed(101, 1), 1);
+        assert_eq!(AuditsDue::<Test>::iter_prefix(101).count(), 1);
+        assert_eq!(Elxr::suspend_lapsed(101, 2), 2);
+        assert_eq!(LapseCursor::<Test>::get(), 102);
+        assert_eq!(status(FACILITY), FacilityStatus::Suspended);
+        assert_eq!(status(OTHER_FACILITY), FacilityStatus::Suspended);
+    });
+}
+
+/// A facility as stored before audits were scheduled; its layout is unchanged.
+fn v0_facility(
+    status: FacilityStatus,
+    registered_at: u64,
+    last_audit: Option<u64>,
+) -> CultivationFacility<Test> {
+    CultivationFacility {
+        name: bounded(b"Test Facility"),
+        location: (100, 200),
+        capacity: 1000,
+        certifications: Default::default(),
+        status,
+        owner: OWNER,
+        registered_at,
+        last_audit,
+    }
+}
+
+#[test]
+fn v0_active_facilities_are_scheduled_for_audit() {
+    new_test_ext().execute_with(|| {
+        StorageVersion::new(0).put::<Elxr>();
+        Facilities::<Test>::insert(id(FACILITY), v0_facility(FacilityStatus::Active, 1, Some(50)));
+        Facilities::<Test>::insert(id(OTHER_FACILITY), v0_facility(FacilityStatus::Active, 1, None));
+        Facilities::<Test>::insert(id(b"FAC003"), v0_facility(FacilityStatus::Pending, 1, None));
+        System::set_block_number(120);
+
+        run_upgrade::<MigrateToV1<Test>>();
+
+        assert_eq!(Elxr::on_chain_storage_version(), 1);
+        assert_eq!(NextAudit::<Test>::get(id(FACILITY)), Some(150));
+        // Already overdue, so due in the upgrade block
+        assert_eq!(NextAudit::<Test>::get(id(OTHER_FACILITY)), Some(120));
+        assert_eq!(NextAudit::<Test>::get(id(b"FAC003")), None);
+        assert_eq!(LapseCursor::<Test>::get(), 120);
+
+        Elxr::on_idle(120, Weight::MAX);
+        assert_eq!(status(OTHER_FACILITY), FacilityStatus::Suspended);
+        assert_eq!(status(FACILITY), FacilityStatus::Active);
+    });
+}
+
+fn certification(cert_id: &[u8], valid_until: u64) -> Certification<Test> {
+    Certification {
+        cert_type: CertificationType::HACCP,
+        cert_id: bounded(cert_id),
+        issuer: AUDITOR,
+        valid_until,
+    }
+}
+
+#[test]
+fn v1_certifications_are_queued_for_expiry_notices() {
+    new_test_ext().execute_with(|| {
+        StorageVersion::new(1).put::<Elxr>();
+        let mut facility = v0_facility(FacilityStatus::Active, 1, None);
+        facility.certifications =
+            vec![certification(b"HACCP-1", 100), certification(b"HACCP-2", 300)].try_into().unwrap();
+        Facilities::<Test>::insert(id(FACILITY), facility);
+        let mut revoked = v0_facility(FacilityStatus::Revoked, 1, None);
+        revoked.certifications = vec![certification(b"HACCP-3", 300)].try_into().unwrap();
+        Facilities::<Test>::insert(id(OTHER_FACILITY), revoked);
+        System::set_block_number(120);
+
+        run_upgrade::<MigrateToV2<Test>>();
+
+        assert_eq!(Elxr::on_chain_storage_version(), 2);
+        assert_eq!(ExpiryCursor::<Test>::get(), 120);
+        let due = |at: u64, cert_id: &[u8]| CertificationsDue::<Test>::get(at, (id(FACILITY), name(cert_id)));
+        // Already expired, so reported as lapsed in the upgrade block
+        assert_eq!(due(120, b"HACCP-1"), Some(ExpiryNotice::Lapsed));
+        assert_eq!(due(250, b"HACCP-2"), Some(ExpiryNotice::Expiring));
+        assert_eq!(due(301, b"HACCP-2"), Some(ExpiryNotice::Lapsed));
+        assert_eq!(CertificationsDue::<Test>::iter().count(), 3);
+
+        assert_eq!(ExpiryNotices::<Test>::get(id(FACILITY), name(b"HACCP-1")), Some((120, 120)));
+        assert_eq!(ExpiryNotices::<Test>::get(id(FACILITY), name(b"HACCP-2")), Some((250, 301)));
+
+        Elxr::on_idle(120, Weight::MAX);
+        assert_eq!(
+            expiry_events(),
+            vec![Event::CertificationLapsed {
+                facility_id: id(FACILITY),
+                cert_type: CertificationType::HACCP,
+                cert_id: bounded(b"HACCP-1"),
+            }]
+        );
+        let facility = Facilities::<Test>::get(id(FACILITY)).unwrap();
+        assert_eq!(facility.certifications.len(), 1);
+        assert_eq!(ExpiryNotices::<Test>::get(id(FACILITY), name(b"HACCP-1")), None);
+    });
+}
+
+#[test]
+fn migrations_leave_migrated_storage_alone() {
+    new_test_ext().execute_with(|| {
+        StorageVersion::new(2).put::<Elxr>();
+        let mut facility = v0_facility(FacilityStatus::Active, 1, None);
+        facility.certifications = vec![certification(b"HACCP-1", 100)].try_into().unwrap();
+        Facilities::<Test>::insert(id(FACILITY), facility);
+
+        run_upgrade::<MigrateToV1<Test>>();
+        run_upgrade::<MigrateToV2<Test>>();
+
+        assert_eq!(NextAudit::<Test>::get(id(FACILITY)), None);
+        assert_eq!(CertificationsDue::<Test>::iter().count(), 0);
+        assert_eq!(Elxr::on_chain_storage_version(), 2);
+    });
+}
+
+#[test]
+fn governance_manages_auditors() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Elxr::add_auditor(RuntimeOrigin::root(), AUDITOR));
+        assert!(Elxr::is_auditor(&AUDITOR));
+        System::assert_last_event(Event::AuditorAdded { auditor: AUDITOR }.into());
+        assert_noop!(
+            Elxr::add_auditor(RuntimeOrigin::root(), AUDITOR),
+            Error::<Test>::AuditorAlreadyExists
+        );
+
+        assert_ok!(Elxr::remove_auditor(RuntimeOrigin::root(), AUDITOR));
+        assert!(!Elxr::is_auditor(&AUDITOR));
+        System::assert_last_event(Event::AuditorRemoved { auditor: AUDITOR }.into());
+        assert_noop!(
+            Elxr::remove_auditor(RuntimeOrigin::root(), AUDITOR),
+            Error::<Test>::AuditorNotFound
+        );
+
+        assert_noop!(
+            Elxr::add_auditor(RuntimeOrigin::signed(OWNER), OWNER),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            Elxr::remove_auditor(RuntimeOrigin::signed(OWNER), AUDITOR),
+            DispatchError::BadOrigin
+        );
+    });
+}
+
+#[test]
+fn default_parameters_apply_to_new_facilities() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Elxr::update_default_parameters(RuntimeOrigin::signed(OWNER), parameters()),
+            DispatchError::BadOrigin
+        );
+
+        assert_ok!(Elxr::update_default_parameters(RuntimeOrigin::root(), parameters()));
+        assert_eq!(DefaultParameters::<Test>::get(), parameters());
+        System::assert_last_event(Event::DefaultParametersUpdated.into());
+
+        assert_ok!(register_facility(OWNER, FACILITY));
+        assert_eq!(Parameters::<Test>::get(id(FACILITY)), Some(parameters()));
+    });
+}
+
+#[test]
+fn scoby_generations_follow_lineage() {
+    new_test_ext().execute_with(|| {
+        activate(FACILITY);
+        assert_ok!(register_scoby(b"MOTHER-A", FACILITY, &[]));
+        assert_ok!(register_scoby(b"MOTHER-B", FACILITY, &[]));
+        assert_ok!(register_scoby(b"CHILD", FACILITY, &[b"MOTHER-A"]));
+        assert_ok!(register_scoby(SCOBY, FACILITY, &[b"CHILD", b"MOTHER-B"]));
+
+        let scoby = ScobyCultures::<Test>::get(id(SCOBY)).unwrap();
+        assert_eq!(scoby.generation, 2);
+        assert_eq!(scoby.parent_ids.into_inner(), vec![id(b"CHILD"), id(b"MOTHER-B")]);
+        assert_eq!(scoby.characteristics, characteristics());
+        assert_eq!(ScobyCount::<Test>::get(), 4);
+        System::assert_last_event(
+            Event::ScobyRegistered {
+                scoby_id: id(SCOBY),
+                facility_id: id(FACILITY),
+                generation: 2,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn register_scoby_errors() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(register_facility(OWNER, FACILITY));
+        assert_noop!(register_scoby(SCOBY, FACILITY, &[]), Error::<Test>::FacilityNotActive);
+
+        activate(OTHER_FACILITY);
+        assert_noop!(
+            register_scoby(SCOBY, OTHER_FACILITY, &[b"UNKNOWN"]),
+            Error::<Test>::ScobyNotFound
+        );
+        assert_ok!(register_scoby(SCOBY, OTHER_FACILITY, &[]));
+        assert_noop!(register_scoby(SCOBY, OTHER_FACILITY, &[]), Error::<Test>::ScobyAlreadyExists);
+    });
+}
+
+#[test]
+fn start_batch_works() {
+    new_test_ext().execute_with(|| {
+        fermenting_batch();
+
+        let batch = Batches::<Test>::get(id(BATCH)).unwrap();
+        assert_eq!(batch.facility_id, id(FACILITY));
+        assert_eq!(batch.scoby_id, id(SCOBY));
+        assert_eq!(batch.producer, OWNER);
+        assert_eq!(batch.status, BatchStatus::Fermenting);
+        assert_eq!(batch.fermentation_start, 1);
+        assert_eq!(BatchCount::<Test>::get(), 1);
+        System::assert_last_event(
+            Event::BatchStarted {
+                batch_id: id(BATCH),
+                facility_id: id(FACILITY),
+                scoby_id: id(SCOBY),
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn start_batch_errors() {
+    new_test_ext().execute_with(|| {
+        fermenting_batch();
+        activate(OTHER_FACILITY);
+
+        assert_noop!(start_batch(BATCH, FACILITY, SCOBY), Error::<Test>::BatchAlreadyExists);
+        assert_noop!(start_batch(b"BATCH-13", FACILITY, b"UNKNOWN"), Error::<Test>::ScobyNotFound);
+        assert_noop!(
+            start_batch(b"BATCH-13", OTHER_FACILITY, SCOBY),
+            Error::<Test>::ScobyFacilityMismatch
+        );
+        assert_noop!(
+            Elxr::start_batch(RuntimeOrigin::signed(STRANGER), id(b"BATCH-13"), id(FACILITY), id(SCOBY)),
+            Error::<Test>::NotFacilityOwner
+        );
+        assert_noop!(start_batch(b"BATCH-13", b"FAC404", SCOBY), Error::<Test>::FacilityNotFound);
+    });
+}
+
+#[test]
+fn record_reading_works() {
+    new_test_ext().execute_with(|| {
+        fermenting_batch();
+        System::set_block_number(5);
+        assert_ok!(record(OWNER, BATCH, DEVICE, reading()));
+        System::assert_last_event(
+            Event::ReadingRecorded {
+                batch_id: id(BATCH),
+                device_id: device_id(DEVICE),
+                index: 0,
+                within_parameters: true,
+            }
+            .into(),
+        );
+
+        let too_warm = FermentationReading {
+            temperature: 3400,
+            ..reading()
+        };
+        assert_ok!(record(OWNER, BATCH, DEVICE, too_warm));
+        System::assert_last_event(
+            Event::ReadingRecorded {
+                batch_id: id(BATCH),
+                device_id: device_id(DEVICE),
+                index: 1,
+                within_parameters: false,
+            }
+            .into(),
+        );
+
+        let stored = BatchReadings::<Test>::get(id(BATCH), 1).unwrap();
+        assert_eq!(stored.reading, too_warm);
+        assert_eq!(stored.recorded_at, 5);
+        assert!(!stored.within_parameters);
+        assert_eq!(Batches::<Test>::get(id(BATCH)).unwrap().readings, 2);
+        assert_eq!(Devices::<Test>::get(device_id(DEVICE)).unwrap().last_active, 5);
+
+        assert_noop!(record(OWNER, BATCH, DEVICE, reading()), Error::<Test>::TooManyReadings);
+    });
+}
+
+#[test]
+fn record_reading_checks_batch_and_device() {
+    new_test_ext().execute_with(|| {
+        fermenting_batch();
+        activate(OTHER_FACILITY);
+        assert_ok!(register_device(b"ELXR-02", OTHER_FACILITY));
+
+        assert_noop!(record(OWNER, b"BATCH-404", DEVICE, reading()), Error::<Test>::BatchNotFound);
+        assert_noop!(record(STRANGER, BATCH, DEVICE, reading()), Error::<Test>::NotFacilityOwner);
+        assert_noop!(record(OWNER, BATCH, b"ELXR-99", reading()), Error::<Test>::DeviceNotFound);
+        assert_noop!(
+            record(OWNER, BATCH, b"ELXR-02", reading()),
+            Error::<Test>::DeviceFacilityMismatch
+        );
+
+        assert_ok!(Elxr::update_device_status(
+            RuntimeOrigin::signed(OWNER),
+            device_id(DEVICE),
+            DeviceStatus::Suspended
+        ));
+        assert_noop!(record(OWNER, BATCH, DEVICE, reading()), Error::<Test>::DeviceNotAuthorized);
+
+        assert_ok!(set_status(RuntimeOrigin::signed(AUDITOR), FACILITY, FacilityStatus::Suspended));
+        assert_noop!(record(OWNER, BATCH, DEVICE, reading()), Error::<Test>::FacilityNotActive);
+    });
+}
+
+#[test]
+fn record_framed_reading_repairs_and_counts_frames() {
+    new_test_ext().execute_with(|| {
+        fermenting_batch();
+        System::set_block_number(5);
+        let source = CorrectionSource::<Test>::Device(device_id(DEVICE));
+        let submit = |who: u64, payload: &[u8], damage: bool| {
+            let mut frame = encode_comprehensive(&payload.to_vec(), &CorrectionConfig::default());
+            if damage {
+                frame[6 + 2] ^= 0x40;
+            }
+            Elxr::record_framed_reading(
+                RuntimeOrigin::signed(who),
+                id(BATCH),
+                device_id(DEVICE),
+                frame.try_into().unwrap(),
+            )
+        };
+
+        assert_ok!(submit(OWNER, &reading().encode(), true));
+        System::assert_has_event(
+            Event::FrameCorrected {
+                source: source.clone(),
+                layer: CorrectionLayer::Classical,
+                symbols_corrected: 1,
+            }
+            .into(),
+        );
+        System::assert_last_event(
+            Event::ReadingRecorded {
+                batch_id: id(BATCH),
+                device_id: device_id(DEVICE),
+                index: 0,
+                within_parameters: true,
+            }
+            .into(),
+        );
+        assert_eq!(BatchReadings::<Test>::get(id(BATCH), 0).unwrap().reading, reading());
+
+        // Nothing is recorded for a lost frame, but it is still counted
+        let lost = Elxr::record_framed_reading(
+            RuntimeOrigin::signed(OWNER),
+            id(BATCH),
+            device_id(DEVICE),
+            bounded(&[0; 8]),
+        );
+        assert_ok!(lost);
+        assert_eq!(Batches::<Test>::get(id(BATCH)).unwrap().readings, 1);
+        assert_eq!(
+            CorrectionCounters::<Test>::get(&source, CorrectionLayer::Classical),
+            LayerCounters {
+                frames: 2,
+                symbols_corrected: 1,
+                unrecoverable: 1,
+            }
+        );
+
+        assert_noop!(submit(OWNER, b"ph", false), Error::<Test>::MalformedReading);
+        assert_noop!(submit(STRANGER, &reading().encode(), false), Error::<Test>::NotFacilityOwner);
+    });
+}
+
+#[test]
+fn complete_batch_works() {
+    new_test_ext().execute_with(|| {
+        fermenting_batch();
+        System::set_block_number(20);
+        assert_ok!(Elxr::complete_batch(RuntimeOrigin::signed(OWNER), id(BATCH), Some(92)));
+
+        let batch = Batches::<Test>::get(id(BATCH)).unwrap();
+        assert_eq!(batch.status, BatchStatus::Completed);
+        assert_eq!(batch.fermentation_end, Some(20));
+        assert_eq!(batch.quality_score, Some(92));
+        System::assert_last_event(
+            Event::BatchCompleted {
+                batch_id: id(BATCH),
+                quality_score: Some(92),
+            }
+            .into(),
+        );
+
+        assert_noop!(
+            Elxr::complete_batch(RuntimeOrigin::signed(OWNER), id(BATCH), None),
+            Error::<Test>::BatchNotFermenting
+        );
+        assert_noop!(record(OWNER, BATCH, DEVICE, reading()), Error::<Test>::BatchNotFermenting);
+    });
+}
+
+#[test]
+fn complete_batch_errors() {
+    new_test_ext().execute_with(|| {
+        fermenting_batch();
+
+        assert_noop!(
+            Elxr::complete_batch(RuntimeOrigin::signed(OWNER), id(BATCH), Some(101)),
+            Error::<Test>::InvalidQualityScore
+        );
+        assert_noop!(
+            Elxr::complete_batch(RuntimeOrigin::signed(STRANGER), id(BATCH), None),
+            Error::<Test>::NotFacilityOwner
+        );
+        assert_noop!(
+            Elxr::complete_batch(RuntimeOrigin::signed(OWNER), id(b"BATCH-404"), None),
+            Error::<Test>::BatchNotFound
+        );
+    });
+}
+
+#[test]
+fn correct_frame_counts_and_reports_each_layer() {
+    new_test_ext().execute_with(|| {
+        let source = CorrectionSource::<Test>::Device(device_id(DEVICE));
+        let mut frame = encode_comprehensive(&b"{\"ph\":3.1}".to_vec(), &CorrectionConfig::default());
+        frame[6 + 2] ^= 0x40;
+
+        let outcome = Elxr::correct_frame(&source, &frame).unwrap();
+        assert_eq!(outcome.payload, b"{\"ph\":3.1}".to_vec());
+        System::assert_last_event(
+            Event::FrameCorrected {
+                source: source.clone(),
+                layer: CorrectionLayer::Classical,
+                symbols_corrected: 1,
+            }
+            .into(),
+        );
+
+        let failure = Elxr::correct_frame(&source, &[]).unwrap_err();
+        assert_eq!(failure.reason, FailureReason::EmptyFrame);
+        System::assert_last_event(
+            Event::FrameUnrecoverable {
+                source: source.clone(),
+                layer: CorrectionLayer::Classical,
+                reason: FailureReason::EmptyFrame,
+            }
+            .into(),
+        );
+
+        assert_eq!(
+            CorrectionCounters::<Test>::get(&source, CorrectionLayer::Classical),
+            LayerCounters {
+                frames: 2,
+                symbols_corrected: 1,
+                unrecoverable: 1,
+            }
+        );
+        assert_eq!(CorrectionCounters::<Test>::get(&source, CorrectionLayer::Quantum).frames, 1);
+        let chain = CorrectionSource::<Test>::Chain(2000);
+        assert_eq!(
+            CorrectionCounters::<Test>::get(chain, CorrectionLayer::Classical),
+            LayerCounters::default()
+        );
+    });
+}
+
+#[test]
+fn registry_queries_look_up_and_page_records() {
+    new_test_ext().execute_with(|| {
+        activate(FACILITY);
+        activate(OTHER_FACILITY);
+        assert_ok!(register_device(DEVICE, FACILITY));
+        assert_ok!(register_device(b"ELXR-02", FACILITY));
+        assert_ok!(register_scoby(b"MOTHER-A", FACILITY, &[]));
+        assert_ok!(register_scoby(b"MOTHER-B", FACILITY, &[]));
+        assert_ok!(register_scoby(b"CHILD", FACILITY, &[b"MOTHER-A"]));
+        assert_ok!(register_scoby(SCOBY, FACILITY, &[b"CHILD", b"MOTHER-B"]));
+        assert_ok!(start_batch(BATCH, FACILITY, SCOBY));
+
+        assert_eq!(Elxr::facility(FACILITY.to_vec()), Facilities::<Test>::get(id(FACILITY)));
+        assert_eq!(Elxr::facility(vec![b'F'; 17]), None);
+        let ids = |records: Vec<(Vec<u8>, _)>| records.into_iter().map(|(id, _)| id).collect::<Vec<_>>();
+        assert_eq!(
+            ids(Elxr::facilities_by_owner(OWNER, Page::default())),
+            vec![FACILITY.to_vec(), OTHER_FACILITY.to_vec()]
+        );
+        assert_eq!(
+            ids(Elxr::facilities_by_owner(OWNER, Page { offset: 1, limit: 1 })),
+            vec![OTHER_FACILITY.to_vec()]
+        );
+
+        assert_eq!(Elxr::facility_devices(FACILITY.to_vec(), Page::default()).len(), 2);
+        assert_eq!(Elxr::facility_devices(FACILITY.to_vec(), Page { offset: 0, limit: 1 }).len(), 1);
+        assert!(Elxr::facility_devices(OTHER_FACILITY.to_vec(), Page::default()).is_empty());
+
+        let lineage = |page| {
+            Elxr::scoby_lineage(SCOBY.to_vec(), page).into_iter().map(|(id, _)| id).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            lineage(Page::default()),
+            vec![SCOBY.to_vec(), b"CHILD".to_vec(), b"MOTHER-B".to_vec(), b"MOTHER-A".to_vec()]
+        );
+        assert_eq!(lineage(Page { offset: 2, limit: 5 }), vec![b"MOTHER-B".to_vec(), b"MOTHER-A".to_vec()]);
+
+        assert_eq!(Elxr::batch(BATCH.to_vec()).map(|batch| batch.status), Some(BatchStatus::Fermenting));
+        assert_eq!(Elxr::batch(b"UNKNOWN".to_vec()), None);
+    });
+}
+
+fn genesis() -> GenesisConfig<Test> {
+    GenesisConfig {
+        facilities: vec![(id(FACILITY), OWNER, bounded(b"Genesis Brewery"), (100, 200), 1000)],
+        auditors: vec![AUDITOR],
+        devices: vec![(device_id(DEVICE), id(FACILITY), public_key(), bounded(b"1.4.2"))],
+        default_parameters: CultivationParameters {
+            ph_range: (260, 340),
+            ..Default::default()
+        },
+        oracle_validators: vec![STRANGER],
+    }
+}
+
+fn genesis_ext(elxr: GenesisConfig<Test>) -> sp_io::TestExternalities {
+    let storage = RuntimeGenesisConfig {
+        system: Default::default(),
+        elxr,
+    }
+    .build_storage()
+    .unwrap();
+    let mut ext = sp_io::TestExternalities::new(storage);
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
+
+#[test]
+fn genesis_seeds_active_facilities_auditors_and_devices() {
+    genesis_ext(genesis()).execute_with(|| {
+        let parameters = genesis().default_parameters;
+        let facility = Facilities::<Test>::get(id(FACILITY)).unwrap();
+        assert_eq!((facility.owner, facility.status), (OWNER, FacilityStatus::Active));
+        assert_eq!(FacilitiesByOwner::<Test>::get(OWNER).into_inner(), vec![id(FACILITY)]);
+        assert_eq!(Parameters::<Test>::get(id(FACILITY)), Some(parameters));
+        assert_eq!(DefaultParameters::<Test>::get(), parameters);
+        assert!(Elxr::is_auditor(&AUDITOR));
+        assert!(Elxr::is_oracle_validator(&STRANGER));
+        assert!(!Elxr::is_oracle_validator(&OWNER));
+        assert!(Elxr::is_device_authorized(&device_id(DEVICE), &id(FACILITY)));
+        assert_eq!((FacilityCount::<Test>::get(), DeviceCount::<Test>::get()), (1, 1));
+
+        // Seeded records work with the calls straight away
+        assert_eq!(NextAudit::<Test>::get(id(FACILITY)), Some(100));
+        assert_ok!(audit(FACILITY, AuditOutcome::Pass, None));
+        assert_ok!(register_facility(OWNER, OTHER_FACILITY));
+        assert_eq!(Parameters::<Test>::get(id(OTHER_FACILITY)), Some(parameters));
+    });
+}
+
+#[test]
+#[should_panic(expected = "genesis devices need a facility")]
+fn genesis_devices_must_belong_to_a_genesis_facility() {
+    let mut genesis = genesis();
+    genesis.facilities.clear();
+    genesis_ext(genesis);
+}
+
+#[test]
+#[should_panic(expected = "genesis device keys must be Dilithium2 public keys")]
+fn genesis_devices_need_dilithium_keys() {
+    let mut genesis = genesis();
+    genesis.devices[0].2 = bounded(&[7; 32]);
+    genesis_ext(genesis);
+}
+
+#[test]
+fn genesis_config_loads_from_chain_spec_json() {
+    let json = r#"{
+        "facilities": [[[70, 65, 67, 48, 48, 49], 1, [66, 114, 101, 119], [100, 200], 1000]],
+        "auditors": [2],
+        "devices": [[[69, 76, 88, 82, 45, 48, 49], [70, 65, 67, 48, 48, 49], [7, 7, 7], [49]]],
+        "defaultParameters": {
+            "ph_range": [260, 340],
+            "temp_range": [2000, 3000],
+            "light_range": [0, 50000],
+            "density_range": [995, 1050],
+            "co2_range": [0, 20000]
+        },
+        "oracleValidators": [3]
+    }"#;
+    let config: GenesisConfig<Test> = serde_json::from_str(json).unwrap();
+    assert_eq!(config.facilities, vec![(id(FACILITY), OWNER, bounded(b"Brew"), (100, 200), 1000)]);
+    assert_eq!(config.devices[0].0, device_id(DEVICE));
+    assert_eq!(config.default_parameters, genesis().default_parameters);
+    assert_eq!((config.auditors, config.oracle_validators), (vec![AUDITOR], vec![STRANGER]));
+
+    // IDs longer than the runtime allows are rejected when the spec is loaded
+    let too_long = json.replace("[70, 65, 67, 48, 48, 49], 1", &format!("{:?}, 1", [b'F'; 17]));
+    assert!(serde_json::from_str::<GenesisConfig<Test>>(&too_long).is_err());
+}
@@ -0,0 +1,344 @@
+// Records kept by the ELXR pallet
+//
+// Ported from the ink! `KombuchaRegistry`: every unbounded `String` and `Vec`
+// there becomes a `BoundedVec` sized by a `Config` constant, and timestamps
+// become block numbers. IDs are the storage keys, so records don't repeat them.
+use frame_support::pallet_prelude::*;
+use frame_system::pallet_prelude::BlockNumberFor;
+use serde::{Deserialize, Serialize};
+
+use super::Config;
+
+/// Facility, SCOBY culture and batch IDs.
+pub type IdOf<T> = BoundedVec<u8, <T as Config>::MaxIdLength>;
+/// Telemetry device IDs, as the firmware reports them.
+pub type DeviceIdOf<T> = BoundedVec<u8, <T as Config>::MaxDeviceIdLength>;
+/// Names and other short free-form text.
+pub type NameOf<T> = BoundedVec<u8, <T as Config>::MaxNameLength>;
+/// A device's post-quantum public key.
+pub type PublicKeyOf<T> = BoundedVec<u8, <T as Config>::MaxPublicKeyLength>;
+/// An auditor's findings.
+pub type FindingsOf<T> = BoundedVec<u8, <T as Config>::MaxFindingsLength>;
+/// A device frame protected by every error-correction layer.
+pub type FrameOf<T> = BoundedVec<u8, <T as Config>::MaxFrameLength>;
+
+/// An inclusive `(min, max)` range in the units of the matching telemetry field.
+pub type Range = (u32, u32);
+
+/// A registered kombucha production facility.
+#[derive(
+    CloneNoBound, PartialEqNoBound, EqNoBound, RuntimeDebugNoBound, Encode, Decode, TypeInfo, MaxEncodedLen,
+)]
+#[scale_info(skip_type_params(T))]
+#[codec(mel_bound())]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize), serde(bound = ""))]
+pub struct CultivationFacility<T: Config> {
+    /// Public name of the facility
+    pub name: NameOf<T>,
+    /// Geographic coordinates, in millionths of a degree
+    pub location: (i32, i32),
+    /// Production capacity in liters per month
+    pub capacity: u32,
+    /// Certifications held by the facility
+    pub certifications: BoundedVec<Certification<T>, T::MaxCertifications>,
+    pub status: FacilityStatus,
+    pub owner: T::AccountId,
+    pub registered_at: BlockNumberFor<T>,
+    /// Block of the latest audit, if there has been one
+    pub last_audit: Option<BlockNumberFor<T>>,
+}
+
+/// A certification granted by an auditor.
+#[derive(
+    CloneNoBound, PartialEqNoBound, EqNoBound, RuntimeDebugNoBound, Encode, Decode, TypeInfo, MaxEncodedLen,
+)]
+#[scale_info(skip_type_params(T))]
+#[codec(mel_bound())]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize), serde(bound = ""))]
+pub struct Certification<T: Config> {
+    pub cert_type: CertificationType,
+    /// Identification number on the certificate
+    pub cert_id: NameOf<T>,
+    /// Auditor who recorded the certification
+    pub issuer: T::AccountId,
+    /// Last block at which the certification is valid
+    pub valid_until: BlockNumberFor<T>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug, Encode, Decode, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum CertificationType {
+    Organic,
+    FDA,
+    GMP,
+    HACCP,
+    ISO22000,
+    Vegan,
+    Kosher,
+    Halal,
+    Other,
+}
+
+/// Notice due for a certification at some block.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug, Encode, Decode, TypeInfo, MaxEncodedLen)]
+pub enum ExpiryNotice {
+    /// `Config::ExpiryWarningPeriod` blocks before the certification expires
+    Expiring,
+    /// The first block after the certification's `valid_until`
+    Lapsed,
+}
+
+/// Where a facility is in its lifecycle.
+///
+/// Auditors activate pending facilities and suspend or reinstate active
+/// ones; governance revokes, which is final.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug, Encode, Decode, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum FacilityStatus {
+    /// Registered, awaiting activation by an auditor
+    Pending,
+    Active,
+    /// Barred from production until an auditor reinstates it
+    Suspended,
+    Revoked,
+}
+
+impl FacilityStatus {
+    /// Whether a facility may move from this status to `next`.
+    pub fn can_become(self, next: FacilityStatus) -> bool {
+        use FacilityStatus::*;
+        matches!(
+            (self, next),
+            (Pending, Active)
+                | (Active, Suspended)
+                | (Suspended, Active)
+                | (Pending | Active | Suspended, Revoked)
+        )
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug, Encode, Decode, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum AuditOutcome {
+    Pass,
+    Fail,
+}
+
+/// The latest audit of a facility.
+#[derive(
+    CloneNoBound, PartialEqNoBound, EqNoBound, RuntimeDebugNoBound, Encode, Decode, TypeInfo, MaxEncodedLen,
+)]
+#[scale_info(skip_type_params(T))]
+#[codec(mel_bound())]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize), serde(bound = ""))]
+pub struct Audit<T: Config> {
+    pub auditor: T::AccountId,
+    pub outcome: AuditOutcome,
+    pub findings: FindingsOf<T>,
+    /// Block by which the facility must be audited again, if sooner than `Config::AuditInterval`
+    pub follow_up_by: Option<BlockNumberFor<T>>,
+    pub audited_at: BlockNumberFor<T>,
+}
+
+/// A telemetry device authorized to report for a facility.
+#[derive(
+    CloneNoBound, PartialEqNoBound, EqNoBound, RuntimeDebugNoBound, Encode, Decode, TypeInfo, MaxEncodedLen,
+)]
+#[scale_info(skip_type_params(T))]
+#[codec(mel_bound())]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize), serde(bound = ""))]
+pub struct TelemetryDevice<T: Config> {
+    pub facility_id: IdOf<T>,
+    /// Public key for quantum-resistant authentication
+    pub public_key: PublicKeyOf<T>,
+    pub status: DeviceStatus,
+    pub registered_at: BlockNumberFor<T>,
+    /// Block of the latest reading recorded from the device
+    pub last_active: BlockNumberFor<T>,
+    pub firmware_version: NameOf<T>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug, Encode, Decode, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum DeviceStatus {
+    Authorized,
+    Suspended,
+    Revoked,
+}
+
+/// Target fermentation conditions for a facility.
+///
+/// Serializable without `std` as well, since it is part of the genesis config.
+#[derive(
+    Clone, Copy, PartialEq, Eq, RuntimeDebug, Encode, Decode, TypeInfo, MaxEncodedLen, Serialize, Deserialize,
+)]
+pub struct CultivationParameters {
+    /// pH, scaled by 100
+    pub ph_range: Range,
+    /// Temperature in Celsius, scaled by 100
+    pub temp_range: Range,
+    /// Light in lux, scaled by 10
+    pub light_range: Range,
+    /// Specific gravity, scaled by 1000
+    pub density_range: Range,
+    /// Dissolved CO2 in mg/L, scaled by 10
+    pub co2_range: Range,
+}
+
+impl CultivationParameters {
+    /// Outer limits for any facility's ranges.
+    pub const LIMITS: CultivationParameters = CultivationParameters {
+        ph_range: (200, 700),
+        temp_range: (1500, 4500),
+        light_range: (0, 500_000),
+        density_range: (900, 1200),
+        co2_range: (0, 50_000),
+    };
+
+    /// Every range is non-empty and inside `LIMITS`.
+    pub fn is_valid(&self) -> bool {
+        self.ranges()
+            .iter()
+            .zip(Self::LIMITS.ranges())
+            .all(|(&(min, max), (low, high))| min < max && min >= low && max <= high)
+    }
+
+    /// Whether a reading lies inside every range it has a measurement for.
+    pub fn contains(&self, reading: &FermentationReading) -> bool {
+        let within = |(min, max): Range, value: u32| (min..=max).contains(&value);
+        within(self.ph_range, reading.ph)
+            && within(self.temp_range, reading.temperature)
+            && within(self.density_range, reading.density)
+            && within(self.co2_range, reading.co2)
+    }
+
+    fn ranges(&self) -> [Range; 5] {
+        [
+            self.ph_range,
+            self.temp_range,
+            self.light_range,
+            self.density_range,
+            self.co2_range,
+        ]
+    }
+}
+
+impl Default for CultivationParameters {
+    fn default() -> Self {
+        Self {
+            ph_range: (250, 350),       // pH 2.5 - 3.5
+            temp_range: (2000, 3000),   // 20.0°C - 30.0°C
+            light_range: (0, 50_000),   // 0 - 5000 lux
+            density_range: (995, 1050), // 0.995 - 1.050
+            co2_range: (0, 20_000),     // 0 - 2000 mg/L
+        }
+    }
+}
+
+/// A SCOBY culture and its lineage.
+#[derive(
+    CloneNoBound, PartialEqNoBound, EqNoBound, RuntimeDebugNoBound, Encode, Decode, TypeInfo, MaxEncodedLen,
+)]
+#[scale_info(skip_type_params(T))]
+#[codec(mel_bound())]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize), serde(bound = ""))]
+pub struct ScobyCulture<T: Config> {
+    /// Name or strain identifier
+    pub name: NameOf<T>,
+    /// Cultures this one was grown from
+    pub parent_ids: BoundedVec<IdOf<T>, T::MaxParentCultures>,
+    /// Facility that owns this culture
+    pub facility_id: IdOf<T>,
+    /// Origin description
+    pub origin: NameOf<T>,
+    /// One more than the highest parent generation, or 0 without parents
+    pub generation: u32,
+    pub registered_at: BlockNumberFor<T>,
+    pub characteristics: ScobyCharacteristics<T>,
+}
+
+#[derive(
+    CloneNoBound, PartialEqNoBound, EqNoBound, RuntimeDebugNoBound, Encode, Decode, TypeInfo, MaxEncodedLen,
+)]
+#[scale_info(skip_type_params(T))]
+#[codec(mel_bound())]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize), serde(bound = ""))]
+pub struct ScobyCharacteristics<T: Config> {
+    /// Acidity production (1-10 scale)
+    pub acidity: u8,
+    /// Fermentation speed (1-10 scale)
+    pub fermentation_speed: u8,
+    /// Flavor profile descriptors
+    pub flavor_notes: BoundedVec<NameOf<T>, T::MaxFlavorNotes>,
+    /// Thickness in mm, scaled by 10
+    pub thickness: u16,
+    /// Average fermentation time in days
+    pub avg_fermentation_days: u8,
+}
+
+/// A batch of kombucha fermenting from one SCOBY culture.
+#[derive(
+    CloneNoBound, PartialEqNoBound, EqNoBound, RuntimeDebugNoBound, Encode, Decode, TypeInfo, MaxEncodedLen,
+)]
+#[scale_info(skip_type_params(T))]
+#[codec(mel_bound())]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize), serde(bound = ""))]
+pub struct KombuchaBatch<T: Config> {
+    pub facility_id: IdOf<T>,
+    pub scoby_id: IdOf<T>,
+    pub producer: T::AccountId,
+    pub status: BatchStatus,
+    pub fermentation_start: BlockNumberFor<T>,
+    pub fermentation_end: Option<BlockNumberFor<T>>,
+    /// Quality score out of 100, given on completion
+    pub quality_score: Option<u8>,
+    /// Readings recorded so far, which are also the next reading's index
+    pub readings: u32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug, Encode, Decode, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum BatchStatus {
+    Fermenting,
+    Completed,
+}
+
+/// Measurements taken by a device during fermentation.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug, Encode, Decode, TypeInfo, MaxEncodedLen)]
+pub struct FermentationReading {
+    /// pH, scaled by 100
+    pub ph: u32,
+    /// Temperature in Celsius, scaled by 100
+    pub temperature: u32,
+    /// Specific gravity, scaled by 1000
+    pub density: u32,
+    /// Dissolved CO2 in mg/L, scaled by 10
+    pub co2: u32,
+}
+
+/// A reading recorded against a batch.
+#[derive(
+    CloneNoBound, PartialEqNoBound, EqNoBound, RuntimeDebugNoBound, Encode, Decode, TypeInfo, MaxEncodedLen,
+)]
+#[scale_info(skip_type_params(T))]
+#[codec(mel_bound())]
+pub struct TelemetryReading<T: Config> {
+    pub device_id: DeviceIdOf<T>,
+    pub recorded_at: BlockNumberFor<T>,
+    pub reading: FermentationReading,
+    /// Whether the reading was inside the facility's parameters when recorded
+    pub within_parameters: bool,
+}
+
+/// Where a frame handed to `correct_frame` came from.
+#[derive(
+    CloneNoBound, PartialEqNoBound, EqNoBound, RuntimeDebugNoBound, Encode, Decode, TypeInfo, MaxEncodedLen,
+)]
+#[scale_info(skip_type_params(T))]
+#[codec(mel_bound())]
+pub enum CorrectionSource<T: Config> {
+    /// A telemetry device, by device ID
+    Device(DeviceIdOf<T>),
+    /// Another chain, by para ID
+    Chain(u32),
+}
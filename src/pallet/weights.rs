@@ -0,0 +1,346 @@
+//! Weights for the ELXR pallet.
+//!
+//! Hand-written estimates, not benchmark results. Storage reads and writes are
+//! counted from each call's code path; execution times and proof sizes are
+//! conservative guesses for the default `Config` bounds. Replace this file
+//! with the output of `frame-benchmarking-cli` for the benchmarks in
+//! `src/pallet/benchmarking.rs` before a production runtime relies on it,
+//! from a node whose runtime is built with `runtime-benchmarks`:
+//!
+//! ```text
+//! <node> benchmark pallet --chain dev --pallet pallet_elxr --extrinsic '*' \
+//!     --steps 50 --repeat 20 --output src/pallet/weights.rs
+//! ```
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use core::marker::PhantomData;
+use frame_support::{
+    traits::Get,
+    weights::{constants::RocksDbWeight, Weight},
+};
+
+/// Weight functions needed for the ELXR pallet.
+pub trait WeightInfo {
+    fn register_facility() -> Weight;
+    fn update_facility_status(c: u32) -> Weight;
+    fn register_device() -> Weight;
+    fn update_device_status() -> Weight;
+    fn update_parameters() -> Weight;
+    fn add_certification(c: u32) -> Weight;
+    fn perform_audit() -> Weight;
+    fn add_auditor() -> Weight;
+    fn remove_auditor() -> Weight;
+    fn update_default_parameters() -> Weight;
+    fn register_scoby(p: u32) -> Weight;
+    fn start_batch() -> Weight;
+    fn record_reading() -> Weight;
+    fn record_framed_reading(f: u32) -> Weight;
+    fn complete_batch() -> Weight;
+    fn suspend_lapsed(n: u32) -> Weight;
+    fn request_certification_renewal(c: u32) -> Weight;
+    fn renew_certification(c: u32) -> Weight;
+    fn notify_expiries(n: u32) -> Weight;
+}
+
+/// Weights for the ELXR pallet using the runtime's database weights.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+    /// Storage: `Elxr::Facilities` (r:1 w:1)
+    /// Storage: `Elxr::FacilitiesByOwner` (r:1 w:1)
+    /// Storage: `Elxr::DefaultParameters` (r:1 w:0)
+    /// Storage: `Elxr::FacilityCount` (r:1 w:1)
+    /// Storage: `Elxr::Parameters` (r:0 w:1)
+    fn register_facility() -> Weight {
+        Weight::from_parts(32_000_000, 6_000)
+            .saturating_add(T::DbWeight::get().reads(4_u64))
+            .saturating_add(T::DbWeight::get().writes(4_u64))
+    }
+    /// Storage: `Elxr::Auditors` (r:1 w:0)
+    /// Storage: `Elxr::Facilities` (r:1 w:1)
+    /// Storage: `Elxr::NextAudit` (r:1 w:2)
+    /// Storage: `Elxr::AuditsDue` (r:0 w:2)
+    /// Storage: `Elxr::RenewalRequests` (r:0 w:c)
+    /// Storage: `Elxr::ExpiryNotices` (r:c w:c)
+    /// Storage: `Elxr::CertificationsDue` (r:0 w:2c)
+    /// The range of component `c` is `[0, T::MaxCertifications]`.
+    fn update_facility_status(c: u32) -> Weight {
+        Weight::from_parts(26_000_000, 5_000)
+            .saturating_add(Weight::from_parts(4_000_000, 100).saturating_mul(c.into()))
+            .saturating_add(T::DbWeight::get().reads(3_u64))
+            .saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(c.into())))
+            .saturating_add(T::DbWeight::get().writes(5_u64))
+            .saturating_add(T::DbWeight::get().writes((4_u64).saturating_mul(c.into())))
+    }
+    /// Storage: `Elxr::Facilities` (r:1 w:0)
+    /// Storage: `Elxr::Devices` (r:1 w:1)
+    /// Storage: `Elxr::DeviceCount` (r:1 w:1)
+    /// Storage: `Elxr::FacilityDevices` (r:0 w:1)
+    fn register_device() -> Weight {
+        Weight::from_parts(30_000_000, 7_000)
+            .saturating_add(T::DbWeight::get().reads(3_u64))
+            .saturating_add(T::DbWeight::get().writes(3_u64))
+    }
+    /// Storage: `Elxr::Devices` (r:1 w:1)
+    /// Storage: `Elxr::Facilities` (r:1 w:0)
+    fn update_device_status() -> Weight {
+        Weight::from_parts(22_000_000, 7_000)
+            .saturating_add(T::DbWeight::get().reads(2_u64))
+            .saturating_add(T::DbWeight::get().writes(1_u64))
+    }
+    /// Storage: `Elxr::Facilities` (r:1 w:0)
+    /// Storage: `Elxr::Auditors` (r:1 w:0)
+    /// Storage: `Elxr::Parameters` (r:0 w:1)
+    fn update_parameters() -> Weight {
+        Weight::from_parts(21_000_000, 4_500)
+            .saturating_add(T::DbWeight::get().reads(2_u64))
+            .saturating_add(T::DbWeight::get().writes(1_u64))
+    }
+    /// Storage: `Elxr::Auditors` (r:1 w:0)
+    /// Storage: `Elxr::Facilities` (r:1 w:1)
+    /// Storage: `Elxr::CertificationsDue` (r:0 w:2)
+    /// Storage: `Elxr::ExpiryNotices` (r:0 w:1)
+    /// The range of component `c` is `[0, T::MaxCertifications]`.
+    fn add_certification(c: u32) -> Weight {
+        Weight::from_parts(24_000_000, 4_500)
+            .saturating_add(Weight::from_parts(150_000, 100).saturating_mul(c.into()))
+            .saturating_add(T::DbWeight::get().reads(2_u64))
+            .saturating_add(T::DbWeight::get().writes(4_u64))
+    }
+    /// Storage: `Elxr::Auditors` (r:1 w:0)
+    /// Storage: `Elxr::Facilities` (r:2 w:2)
+    /// Storage: `Elxr::Audits` (r:0 w:1)
+    /// Storage: `Elxr::NextAudit` (r:2 w:4)
+    /// Storage: `Elxr::AuditsDue` (r:0 w:3)
+    fn perform_audit() -> Weight {
+        Weight::from_parts(40_000_000, 6_500)
+            .saturating_add(T::DbWeight::get().reads(5_u64))
+            .saturating_add(T::DbWeight::get().writes(10_u64))
+    }
+    /// Storage: `Elxr::Auditors` (r:1 w:1)
+    fn add_auditor() -> Weight {
+        Weight::from_parts(12_000_000, 3_500)
+            .saturating_add(T::DbWeight::get().reads(1_u64))
+            .saturating_add(T::DbWeight::get().writes(1_u64))
+    }
+    /// Storage: `Elxr::Auditors` (r:1 w:1)
+    fn remove_auditor() -> Weight {
+        Weight::from_parts(12_000_000, 3_500)
+            .saturating_add(T::DbWeight::get().reads(1_u64))
+            .saturating_add(T::DbWeight::get().writes(1_u64))
+    }
+    /// Storage: `Elxr::DefaultParameters` (r:0 w:1)
+    fn update_default_parameters() -> Weight {
+        Weight::from_parts(8_000_000, 0).saturating_add(T::DbWeight::get().writes(1_u64))
+    }
+    /// Storage: `Elxr::Facilities` (r:1 w:0)
+    /// Storage: `Elxr::ScobyCultures` (r:1 w:1), plus one read per parent
+    /// Storage: `Elxr::ScobyCount` (r:1 w:1)
+    /// The range of component `p` is `[0, T::MaxParentCultures]`.
+    fn register_scoby(p: u32) -> Weight {
+        Weight::from_parts(30_000_000, 6_000)
+            .saturating_add(Weight::from_parts(4_000_000, 3_000).saturating_mul(p.into()))
+            .saturating_add(T::DbWeight::get().reads(3_u64))
+            .saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(p.into())))
+            .saturating_add(T::DbWeight::get().writes(2_u64))
+    }
+    /// Storage: `Elxr::Facilities` (r:1 w:0)
+    /// Storage: `Elxr::ScobyCultures` (r:1 w:0)
+    /// Storage: `Elxr::Batches` (r:1 w:1)
+    /// Storage: `Elxr::BatchCount` (r:1 w:1)
+    fn start_batch() -> Weight {
+        Weight::from_parts(28_000_000, 9_000)
+            .saturating_add(T::DbWeight::get().reads(4_u64))
+            .saturating_add(T::DbWeight::get().writes(2_u64))
+    }
+    /// Storage: `Elxr::Batches` (r:1 w:1)
+    /// Storage: `Elxr::Facilities` (r:1 w:0)
+    /// Storage: `Elxr::Devices` (r:1 w:1)
+    /// Storage: `Elxr::Parameters` (r:1 w:0)
+    /// Storage: `Elxr::BatchReadings` (r:0 w:1)
+    fn record_reading() -> Weight {
+        Weight::from_parts(34_000_000, 12_000)
+            .saturating_add(T::DbWeight::get().reads(4_u64))
+            .saturating_add(T::DbWeight::get().writes(3_u64))
+    }
+    /// Storage: `Elxr::Batches` (r:1 w:1)
+    /// Storage: `Elxr::Facilities` (r:1 w:0)
+    /// Storage: `Elxr::Devices` (r:1 w:1)
+    /// Storage: `Elxr::CorrectionCounters` (r:3 w:3)
+    /// Storage: `Elxr::Parameters` (r:1 w:0)
+    /// Storage: `Elxr::BatchReadings` (r:0 w:1)
+    /// The range of component `f` is `[0, T::MaxFrameLength]`.
+    fn record_framed_reading(f: u32) -> Weight {
+        Weight::from_parts(40_000_000, 14_000)
+            .saturating_add(Weight::from_parts(200_000, 0).saturating_mul(f.into()))
+            .saturating_add(T::DbWeight::get().reads(7_u64))
+            .saturating_add(T::DbWeight::get().writes(6_u64))
+    }
+    /// Storage: `Elxr::Batches` (r:1 w:1)
+    fn complete_batch() -> Weight {
+        Weight::from_parts(16_000_000, 3_500)
+            .saturating_add(T::DbWeight::get().reads(1_u64))
+            .saturating_add(T::DbWeight::get().writes(1_u64))
+    }
+    /// Storage: `Elxr::LapseCursor` (r:1 w:1)
+    /// Storage: `Elxr::AuditsDue` (r:n w:2n)
+    /// Storage: `Elxr::NextAudit` (r:2n w:n)
+    /// Storage: `Elxr::Facilities` (r:2n w:n)
+    /// The range of component `n` is `[0, 100]`.
+    fn suspend_lapsed(n: u32) -> Weight {
+        Weight::from_parts(5_000_000, 1_500)
+            .saturating_add(Weight::from_parts(30_000_000, 4_500).saturating_mul(n.into()))
+            .saturating_add(T::DbWeight::get().reads(1_u64))
+            .saturating_add(T::DbWeight::get().reads((5_u64).saturating_mul(n.into())))
+            .saturating_add(T::DbWeight::get().writes(1_u64))
+            .saturating_add(T::DbWeight::get().writes((4_u64).saturating_mul(n.into())))
+    }
+    /// Storage: `Elxr::Facilities` (r:1 w:0)
+    /// Storage: `Elxr::RenewalRequests` (r:0 w:1)
+    /// The range of component `c` is `[0, T::MaxCertifications]`.
+    fn request_certification_renewal(c: u32) -> Weight {
+        Weight::from_parts(18_000_000, 4_500)
+            .saturating_add(Weight::from_parts(150_000, 100).saturating_mul(c.into()))
+            .saturating_add(T::DbWeight::get().reads(1_u64))
+            .saturating_add(T::DbWeight::get().writes(1_u64))
+    }
+    /// Storage: `Elxr::Auditors` (r:1 w:0)
+    /// Storage: `Elxr::RenewalRequests` (r:1 w:1)
+    /// Storage: `Elxr::Facilities` (r:1 w:1)
+    /// Storage: `Elxr::CertificationsDue` (r:0 w:4)
+    /// Storage: `Elxr::ExpiryNotices` (r:1 w:2)
+    /// The range of component `c` is `[0, T::MaxCertifications]`.
+    fn renew_certification(c: u32) -> Weight {
+        Weight::from_parts(30_000_000, 4_500)
+            .saturating_add(Weight::from_parts(150_000, 100).saturating_mul(c.into()))
+            .saturating_add(T::DbWeight::get().reads(4_u64))
+            .saturating_add(T::DbWeight::get().writes(7_u64))
+    }
+    /// Storage: `Elxr::ExpiryCursor` (r:1 w:1)
+    /// Storage: `Elxr::CertificationsDue` (r:n w:3n)
+    /// Storage: `Elxr::Facilities` (r:n w:n)
+    /// Storage: `Elxr::ExpiryNotices` (r:n w:n)
+    /// Storage: `Elxr::RenewalRequests` (r:0 w:n)
+    /// The range of component `n` is `[0, 100]`.
+    fn notify_expiries(n: u32) -> Weight {
+        Weight::from_parts(5_000_000, 1_500)
+            .saturating_add(Weight::from_parts(30_000_000, 4_500).saturating_mul(n.into()))
+            .saturating_add(T::DbWeight::get().reads(1_u64))
+            .saturating_add(T::DbWeight::get().reads((3_u64).saturating_mul(n.into())))
+            .saturating_add(T::DbWeight::get().writes(1_u64))
+            .saturating_add(T::DbWeight::get().writes((6_u64).saturating_mul(n.into())))
+    }
+}
+
+// For backwards compatibility and tests.
+impl WeightInfo for () {
+    fn register_facility() -> Weight {
+        Weight::from_parts(32_000_000, 6_000)
+            .saturating_add(RocksDbWeight::get().reads(4_u64))
+            .saturating_add(RocksDbWeight::get().writes(4_u64))
+    }
+    fn update_facility_status(c: u32) -> Weight {
+        Weight::from_parts(26_000_000, 5_000)
+            .saturating_add(Weight::from_parts(4_000_000, 100).saturating_mul(c.into()))
+            .saturating_add(RocksDbWeight::get().reads(3_u64))
+            .saturating_add(RocksDbWeight::get().reads((1_u64).saturating_mul(c.into())))
+            .saturating_add(RocksDbWeight::get().writes(5_u64))
+            .saturating_add(RocksDbWeight::get().writes((4_u64).saturating_mul(c.into())))
+    }
+    fn register_device() -> Weight {
+        Weight::from_parts(30_000_000, 7_000)
+            .saturating_add(RocksDbWeight::get().reads(3_u64))
+            .saturating_add(RocksDbWeight::get().writes(3_u64))
+    }
+    fn update_device_status() -> Weight {
+        Weight::from_parts(22_000_000, 7_000)
+            .saturating_add(RocksDbWeight::get().reads(2_u64))
+            .saturating_add(RocksDbWeight::get().writes(1_u64))
+    }
+    fn update_parameters() -> Weight {
+        Weight::from_parts(21_000_000, 4_500)
+            .saturating_add(RocksDbWeight::get().reads(2_u64))
+            .saturating_add(RocksDbWeight::get().writes(1_u64))
+    }
+    fn add_certification(c: u32) -> Weight {
+        Weight::from_parts(24_000_000, 4_500)
+            .saturating_add(Weight::from_parts(150_000, 100).saturating_mul(c.into()))
+            .saturating_add(RocksDbWeight::get().reads(2_u64))
+            .saturating_add(RocksDbWeight::get().writes(4_u64))
+    }
+    fn perform_audit() -> Weight {
+        Weight::from_parts(40_000_000, 6_500)
+            .saturating_add(RocksDbWeight::get().reads(5_u64))
+            .saturating_add(RocksDbWeight::get().writes(10_u64))
+    }
+    fn add_auditor() -> Weight {
+        Weight::from_parts(12_000_000, 3_500)
+            .saturating_add(RocksDbWeight::get().reads(1_u64))
+            .saturating_add(RocksDbWeight::get().writes(1_u64))
+    }
+    fn remove_auditor() -> Weight {
+        Weight::from_parts(12_000_000, 3_500)
+            .saturating_add(RocksDbWeight::get().reads(1_u64))
+            .saturating_add(RocksDbWeight::get().writes(1_u64))
+    }
+    fn update_default_parameters() -> Weight {
+        Weight::from_parts(8_000_000, 0).saturating_add(RocksDbWeight::get().writes(1_u64))
+    }
+    fn register_scoby(p: u32) -> Weight {
+        Weight::from_parts(30_000_000, 6_000)
+            .saturating_add(Weight::from_parts(4_000_000, 3_000).saturating_mul(p.into()))
+            .saturating_add(RocksDbWeight::get().reads(3_u64))
+            .saturating_add(RocksDbWeight::get().reads((1_u64).saturating_mul(p.into())))
+            .saturating_add(RocksDbWeight::get().writes(2_u64))
+    }
+    fn start_batch() -> Weight {
+        Weight::from_parts(28_000_000, 9_000)
+            .saturating_add(RocksDbWeight::get().reads(4_u64))
+            .saturating_add(RocksDbWeight::get().writes(2_u64))
+    }
+    fn record_reading() -> Weight {
+        Weight::from_parts(34_000_000, 12_000)
+            .saturating_add(RocksDbWeight::get().reads(4_u64))
+            .saturating_add(RocksDbWeight::get().writes(3_u64))
+    }
+    fn record_framed_reading(f: u32) -> Weight {
+        Weight::from_parts(40_000_000, 14_000)
+            .saturating_add(Weight::from_parts(200_000, 0).saturating_mul(f.into()))
+            .saturating_add(RocksDbWeight::get().reads(7_u64))
+            .saturating_add(RocksDbWeight::get().writes(6_u64))
+    }
+    fn complete_batch() -> Weight {
+        Weight::from_parts(16_000_000, 3_500)
+            .saturating_add(RocksDbWeight::get().reads(1_u64))
+            .saturating_add(RocksDbWeight::get().writes(1_u64))
+    }
+    fn suspend_lapsed(n: u32) -> Weight {
+        Weight::from_parts(5_000_000, 1_500)
+            .saturating_add(Weight::from_parts(30_000_000, 4_500).saturating_mul(n.into()))
+            .saturating_add(RocksDbWeight::get().reads(1_u64))
+            .saturating_add(RocksDbWeight::get().reads((5_u64).saturating_mul(n.into())))
+            .saturating_add(RocksDbWeight::get().writes(1_u64))
+            .saturating_add(RocksDbWeight::get().writes((4_u64).saturating_mul(n.into())))
+    }
+    fn request_certification_renewal(c: u32) -> Weight {
+        Weight::from_parts(18_000_000, 4_500)
+            .saturating_add(Weight::from_parts(150_000, 100).saturating_mul(c.into()))
+            .saturating_add(RocksDbWeight::get().reads(1_u64))
+            .saturating_add(RocksDbWeight::get().writes(1_u64))
+    }
+    fn renew_certification(c: u32) -> Weight {
+        Weight::from_parts(30_000_000, 4_500)
+            .saturating_add(Weight::from_parts(150_000, 100).saturating_mul(c.into()))
+            .saturating_add(RocksDbWeight::get().reads(4_u64))
+            .saturating_add(RocksDbWeight::get().writes(7_u64))
+    }
+    fn notify_expiries(n: u32) -> Weight {
+        Weight::from_parts(5_000_000, 1_500)
+            .saturating_add(Weight::from_parts(30_000_000, 4_500).saturating_mul(n.into()))
+            .saturating_add(RocksDbWeight::get().reads(1_u64))
+            .saturating_add(RocksDbWeight::get().reads((3_u64).saturating_mul(n.into())))
+            .saturating_add(RocksDbWeight::get().writes(1_u64))
+            .saturating_add(RocksDbWeight::get().writes((6_u64).saturating_mul(n.into())))
+    }
+}
@@ -0,0 +1,124 @@
+// Benchmarks for the telemetry pallet
+//
+// Readings are ELXR readings, which cost one write more than NRSH ones.
+// Their signatures come from `Config::BenchmarkHelper`, since the pallet
+// cannot make one. A Dilithium signature only verifies at its full length, so
+// a signature of any other length `s` is rejected before verification, and
+// that rejection is what such a call costs.
+use frame_benchmarking::v2::*;
+use frame_support::pallet_prelude::{DispatchResult, TransactionSource, ValidateUnsigned};
+use frame_support::sp_runtime::traits::Saturating;
+use frame_support::{traits::Get, BoundedVec};
+use frame_system::RawOrigin;
+use sp_std::prelude::*;
+
+use super::signature::{BenchmarkHelper, SIGNATURE_LENGTH};
+use super::*;
+
+/// `seed` followed by padding, as long as `S` allows.
+fn bytes<S: Get<u32>>(seed: u32) -> BoundedVec<u8, S> {
+    let mut bytes = seed.to_le_bytes().to_vec();
+    bytes.resize(S::get().max(4) as usize, b'x');
+    BoundedVec::truncate_from(bytes)
+}
+
+fn elxr_reading<T: Config>(timestamp: Moment, reporter: T::AccountId) -> ElxrTelemetry<T> {
+    ElxrTelemetry {
+        device_id: bytes(0),
+        timestamp,
+        nonce: 0,
+        batch_id: bytes(0),
+        ph: 310,
+        temperature: 2450,
+        light: 1200,
+        density: 1012,
+        co2: 6000,
+        fermentation: 420,
+        battery: 37,
+        reporter,
+        quantum_signature: BoundedVec::new(),
+    }
+}
+
+/// A reading from a newly registered device, with its signature cut or
+/// padded to `s` bytes.
+fn signed_report<T: Config>(reporter: T::AccountId, s: u32) -> Report<T> {
+    let mut reading = elxr_reading::<T>(1_700_000_000, reporter);
+    let mut signature =
+        T::BenchmarkHelper::register_and_sign(&reading.device_id, &reading.signing_payload());
+    signature.resize(s as usize, 0);
+    reading.quantum_signature = BoundedVec::truncate_from(signature);
+    Report::Elxr(reading)
+}
+
+/// Checks that the call went through if, and only if, the signature verifies.
+fn assert_stored_if_signed<T: Config>(device_id: &DeviceIdOf<T>, s: u32, result: DispatchResult) {
+    if s == SIGNATURE_LENGTH {
+        assert_eq!(result, Ok(()));
+        assert_eq!(DeviceNonces::<T>::get(device_id), 1);
+    } else {
+        assert!(result.is_err());
+    }
+}
+
+#[benchmarks]
+mod benchmarks {
+    use super::*;
+
+    #[benchmark]
+    fn submit_telemetry(s: Linear<0, { T::MaxSignatureLength::get() }>) {
+        let caller: T::AccountId = whitelisted_caller();
+        let report = signed_report::<T>(caller.clone(), s);
+        let device_id = report.device_id().clone();
+        let result;
+
+        #[block]
+        {
+            result = Pallet::<T>::submit_telemetry(RawOrigin::Signed(caller).into(), report);
+        }
+
+        assert_stored_if_signed::<T>(&device_id, s, result);
+    }
+
+    #[benchmark]
+    fn submit_unsigned_telemetry(s: Linear<0, { T::MaxSignatureLength::get() }>) {
+        let report = signed_report::<T>(account("relay", 0, 0), s);
+        let device_id = report.device_id().clone();
+        let call = Call::<T>::submit_unsigned_telemetry { report: report.clone() };
+        let result;
+
+        // The pool check is where the signature is verified, so it is part of the weight
+        #[block]
+        {
+            result = match Pallet::<T>::validate_unsigned(TransactionSource::External, &call) {
+                Ok(_) => Pallet::<T>::submit_unsigned_telemetry(RawOrigin::None.into(), report),
+                Err(_) => Err(Error::<T>::InvalidSignature.into()),
+            };
+        }
+
+        assert_stored_if_signed::<T>(&device_id, s, result);
+    }
+
+    #[benchmark]
+    fn prune(n: Linear<0, { T::MaxPrunedPerBlock::get() }>) {
+        let arrived = frame_system::Pallet::<T>::block_number();
+        for i in 0..n {
+            // A day apart, so every reading starts its own hourly and daily rollups
+            let reading = elxr_reading::<T>(1_700_000_000 + DAY * i as u64, account("relay", 0, 0));
+            let (device_id, timestamp) = (reading.device_id.clone(), reading.timestamp);
+            Arrivals::<T>::insert(arrived, (&device_id, timestamp), ());
+            ElxrReadings::<T>::insert(&device_id, timestamp, reading);
+        }
+        PruneCursor::<T>::put(arrived);
+        let now = arrived.saturating_add(T::RetentionPeriod::get());
+
+        #[block]
+        {
+            Pallet::<T>::prune(now, n);
+        }
+
+        assert_eq!(Arrivals::<T>::iter_prefix(arrived).count(), 0);
+    }
+
+    impl_benchmark_test_suite!(Pallet, crate::parachain::mock::new_test_ext(), crate::parachain::mock::Test);
+}
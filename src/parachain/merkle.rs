@@ -0,0 +1,27 @@
+// Merkle roots over pruned telemetry
+//
+// Each leaf is the hash of one SCALE-encoded reading. Pairs are hashed as
+// `hash(left ++ right)`; an unpaired node at the end of a level moves up
+// unchanged. The pallet sorts each block's pruned readings by device ID and
+// timestamp before hashing them, so an archive holding those readings can
+// recompute the root recorded on chain.
+use frame_support::sp_runtime::traits::Hash;
+use sp_std::prelude::*;
+
+/// Root of the tree over `leaves`, or the default hash if there are none.
+pub fn merkle_root<H: Hash>(mut leaves: Vec<H::Output>) -> H::Output {
+    if leaves.is_empty() {
+        return Default::default();
+    }
+    while leaves.len() > 1 {
+        leaves = leaves
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => H::hash(&[left.as_ref(), right.as_ref()].concat()),
+                [single] => *single,
+                _ => unreachable!("chunks of two"),
+            })
+            .collect();
+    }
+    leaves[0]
+}
@@ -0,0 +1,202 @@
+// Storage migrations for the telemetry pallet
+//
+// See `crate::migrations` for how these are written and tested.
+use frame_support::{
+    pallet_prelude::*,
+    storage_alias,
+    traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+};
+use sp_std::{marker::PhantomData, prelude::*};
+#[cfg(feature = "try-runtime")]
+use frame_support::sp_runtime::TryRuntimeError;
+
+use super::*;
+
+/// Version 1 added a `nonce` to every reading.
+///
+/// Readings stored before then are numbered per device in timestamp order,
+/// across both projects, and the device's next nonce is set to follow them.
+/// ELXR readings are also queued for pruning as if they arrived in the
+/// upgrade block, since they were stored before `Arrivals` was kept.
+pub mod v1 {
+    use super::*;
+
+    /// An NRSH reading in the version 0 layout.
+    #[derive(Encode, Decode)]
+    pub struct OldNrshTelemetry<T: Config> {
+        pub device_id: DeviceIdOf<T>,
+        pub timestamp: Moment,
+        pub batch_id: BatchIdOf<T>,
+        pub ph: u32,
+        pub temperature: u32,
+        pub light: u32,
+        pub density: u32,
+        pub dissolved_oxygen: u32,
+        pub nitrate: u32,
+        pub salinity: u32,
+        pub battery: u32,
+        pub overall_health: u32,
+        pub harvest_ready: bool,
+        pub reporter: T::AccountId,
+        pub quantum_signature: SignatureOf<T>,
+    }
+
+    impl<T: Config> OldNrshTelemetry<T> {
+        fn upgrade(self, nonce: u64) -> NrshTelemetry<T> {
+            NrshTelemetry {
+                device_id: self.device_id,
+                timestamp: self.timestamp,
+                nonce,
+                batch_id: self.batch_id,
+                ph: self.ph,
+                temperature: self.temperature,
+                light: self.light,
+                density: self.density,
+                dissolved_oxygen: self.dissolved_oxygen,
+                nitrate: self.nitrate,
+                salinity: self.salinity,
+                battery: self.battery,
+                overall_health: self.overall_health,
+                harvest_ready: self.harvest_ready,
+                reporter: self.reporter,
+                quantum_signature: self.quantum_signature,
+            }
+        }
+    }
+
+    /// An ELXR reading in the version 0 layout.
+    #[derive(Encode, Decode)]
+    pub struct OldElxrTelemetry<T: Config> {
+        pub device_id: DeviceIdOf<T>,
+        pub timestamp: Moment,
+        pub batch_id: BatchIdOf<T>,
+        pub ph: u32,
+        pub temperature: u32,
+        pub light: u32,
+        pub density: u32,
+        pub co2: u32,
+        pub fermentation: u32,
+        pub battery: u32,
+        pub reporter: T::AccountId,
+        pub quantum_signature: SignatureOf<T>,
+    }
+
+    impl<T: Config> OldElxrTelemetry<T> {
+        fn upgrade(self, nonce: u64) -> ElxrTelemetry<T> {
+            ElxrTelemetry {
+                device_id: self.device_id,
+                timestamp: self.timestamp,
+                nonce,
+                batch_id: self.batch_id,
+                ph: self.ph,
+                temperature: self.temperature,
+                light: self.light,
+                density: self.density,
+                co2: self.co2,
+                fermentation: self.fermentation,
+                battery: self.battery,
+                reporter: self.reporter,
+                quantum_signature: self.quantum_signature,
+            }
+        }
+    }
+
+    /// `NrshReadings` in the version 0 layout.
+    #[storage_alias]
+    pub type NrshReadings<T: Config> = StorageDoubleMap<
+        Pallet<T>,
+        Blake2_128Concat,
+        DeviceIdOf<T>,
+        Twox64Concat,
+        Moment,
+        OldNrshTelemetry<T>,
+    >;
+
+    /// `ElxrReadings` in the version 0 layout.
+    #[storage_alias]
+    pub type ElxrReadings<T: Config> = StorageDoubleMap<
+        Pallet<T>,
+        Blake2_128Concat,
+        DeviceIdOf<T>,
+        Twox64Concat,
+        Moment,
+        OldElxrTelemetry<T>,
+    >;
+
+    pub struct MigrateToV1<T>(PhantomData<T>);
+
+    impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+        fn on_runtime_upgrade() -> Weight {
+            if Pallet::<T>::on_chain_storage_version() != 0 {
+                return T::DbWeight::get().reads(1);
+            }
+
+            // Every stored reading, in the order its nonce is given
+            let mut keys: Vec<_> = NrshReadings::<T>::iter_keys()
+                .map(|(device_id, timestamp)| (device_id, timestamp, Project::Nrsh))
+                .chain(
+                    ElxrReadings::<T>::iter_keys()
+                        .map(|(device_id, timestamp)| (device_id, timestamp, Project::Elxr)),
+                )
+                .collect();
+            keys.sort_by(|a, b| (&a.0, a.1, a.2 as u8).cmp(&(&b.0, b.1, b.2 as u8)));
+
+            let now = frame_system::Pallet::<T>::block_number();
+            let readings = keys.len() as u64;
+            let mut devices = 0;
+            let mut nonce = 0;
+            for (index, (device_id, timestamp, project)) in keys.iter().enumerate() {
+                match project {
+                    Project::Nrsh => {
+                        if let Some(old) = NrshReadings::<T>::take(device_id, timestamp) {
+                            super::NrshReadings::<T>::insert(device_id, timestamp, old.upgrade(nonce));
+                        }
+                    }
+                    Project::Elxr => {
+                        if let Some(old) = ElxrReadings::<T>::take(device_id, timestamp) {
+                            super::ElxrReadings::<T>::insert(device_id, timestamp, old.upgrade(nonce));
+                            Arrivals::<T>::insert(now, (device_id, *timestamp), ());
+                        }
+                    }
+                }
+                nonce += 1;
+                if keys.get(index + 1).is_none_or(|next| next.0 != *device_id) {
+                    DeviceNonces::<T>::insert(device_id, nonce);
+                    devices += 1;
+                    nonce = 0;
+                }
+            }
+            StorageVersion::new(1).put::<Pallet<T>>();
+
+            // A key read and a value read per reading, then its value and arrival written
+            T::DbWeight::get().reads_writes(1 + 2 * readings, 1 + 2 * readings + devices)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+            let readings = NrshReadings::<T>::iter_keys().count() + ElxrReadings::<T>::iter_keys().count();
+            Ok((readings as u64).encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+            let before = u64::decode(&mut &state[..]).map_err(|_| "pre-upgrade state does not decode")?;
+            ensure!(Pallet::<T>::on_chain_storage_version() == 1, "storage version was not set to 1");
+
+            // Iterating decodes every value, so this also checks the new layout
+            let nrsh = super::NrshReadings::<T>::iter_values();
+            let elxr = super::ElxrReadings::<T>::iter_values();
+            let nonces = nrsh
+                .map(|reading| (reading.device_id, reading.nonce))
+                .chain(elxr.map(|reading| (reading.device_id, reading.nonce)));
+            let mut after = 0;
+            for (device_id, nonce) in nonces {
+                // The device's next nonce must follow every reading it has stored
+                ensure!(nonce < DeviceNonces::<T>::get(device_id), "a reading's nonce is still to come");
+                after += 1;
+            }
+            ensure!(after == before, "readings were lost");
+            Ok(())
+        }
+    }
+}
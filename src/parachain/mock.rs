@@ -0,0 +1,138 @@
+// Test runtime for the telemetry pallet
+//
+// Devices are looked up in the ELXR registry, which the tests fill directly
+// rather than going through facility registration.
+use frame_support::{
+    construct_runtime,
+    pallet_prelude::*,
+    traits::{ConstU32, ConstU64, Everything},
+};
+use frame_system::EnsureRoot;
+use pqc_dilithium::Keypair;
+use sp_core::H256;
+use sp_runtime::{
+    traits::{BlakeTwo256, IdentityLookup},
+    BuildStorage,
+};
+
+use crate::pallet as pallet_elxr;
+use crate::pallet::{DeviceStatus, TelemetryDevice};
+use crate::parachain as pallet_telemetry;
+use crate::parachain::signature::{PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH};
+
+type Block = frame_system::mocking::MockBlock<Test>;
+
+construct_runtime!(
+    pub enum Test {
+        System: frame_system,
+        Elxr: pallet_elxr,
+        Telemetry: pallet_telemetry,
+    }
+);
+
+impl frame_system::Config for Test {
+    type BaseCallFilter = Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type RuntimeOrigin = RuntimeOrigin;
+    type RuntimeCall = RuntimeCall;
+    type Nonce = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Block = Block;
+    type RuntimeEvent = RuntimeEvent;
+    type BlockHashCount = ConstU64<250>;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = ();
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = ();
+    type OnSetCode = ();
+    type MaxConsumers = ConstU32<16>;
+}
+
+impl pallet_elxr::Config for Test {
+    type RuntimeEvent = RuntimeEvent;
+    type RegistryOrigin = EnsureRoot<u64>;
+    type MaxIdLength = ConstU32<16>;
+    type MaxDeviceIdLength = ConstU32<16>;
+    type MaxNameLength = ConstU32<32>;
+    type MaxPublicKeyLength = ConstU32<PUBLIC_KEY_LENGTH>;
+    type MaxCertifications = ConstU32<2>;
+    type MaxFacilitiesPerOwner = ConstU32<2>;
+    type MaxParentCultures = ConstU32<2>;
+    type MaxFlavorNotes = ConstU32<4>;
+    type MaxReadingsPerBatch = ConstU32<2>;
+    type MaxFindingsLength = ConstU32<64>;
+    type MaxFrameLength = ConstU32<1024>;
+    type AuditInterval = ConstU64<100>;
+    type ExpiryWarningPeriod = ConstU64<50>;
+    type RequiredCertifications = ();
+    type WeightInfo = ();
+}
+
+impl pallet_telemetry::Config for Test {
+    type RuntimeEvent = RuntimeEvent;
+    type MaxDeviceIdLength = ConstU32<16>;
+    type MaxBatchIdLength = ConstU32<16>;
+    type MaxSignatureLength = ConstU32<SIGNATURE_LENGTH>;
+    type Devices = Elxr;
+    type UnsignedInterval = ConstU64<10>;
+    type UnsignedPriority = ConstU64<{ 1 << 20 }>;
+    type RetentionPeriod = ConstU64<100>;
+    type MaxPrunedPerBlock = ConstU32<2>;
+    type PrunedRootRetention = ConstU64<50>;
+    type WeightInfo = ();
+    #[cfg(feature = "runtime-benchmarks")]
+    type BenchmarkHelper = BenchmarkDevices;
+}
+
+pub const RELAY: u64 = 1;
+pub const STRANGER: u64 = 2;
+
+/// `bytes` as a bounded ID or signature. Panics if it is too long.
+pub fn bounded<S: Get<u32>>(bytes: &[u8]) -> BoundedVec<u8, S> {
+    bytes.to_vec().try_into().expect("test value fits its bound")
+}
+
+/// Registers `device_id` in the ELXR registry with a fresh key pair, and
+/// returns the key pair.
+pub fn register_device(device_id: &[u8], status: DeviceStatus) -> Keypair {
+    let keys = Keypair::generate();
+    pallet_elxr::Devices::<Test>::insert(
+        bounded::<<Test as pallet_elxr::Config>::MaxDeviceIdLength>(device_id),
+        TelemetryDevice {
+            facility_id: bounded(b"FAC-1"),
+            public_key: bounded(&keys.public),
+            status,
+            registered_at: 1,
+            last_active: 1,
+            firmware_version: bounded(b"1.0.0"),
+        },
+    );
+    keys
+}
+
+/// Registers benchmark devices through [`register_device`].
+#[cfg(feature = "runtime-benchmarks")]
+pub struct BenchmarkDevices;
+
+#[cfg(feature = "runtime-benchmarks")]
+impl pallet_telemetry::signature::BenchmarkHelper for BenchmarkDevices {
+    fn register_and_sign(device_id: &[u8], message: &[u8]) -> Vec<u8> {
+        register_device(device_id, DeviceStatus::Authorized).sign(message).to_vec()
+    }
+}
+
+/// Externalities at block 1, so that events are recorded.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let storage = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+    let mut ext = sp_io::TestExternalities::new(storage);
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
@@ -1,143 +1,505 @@
 // Parachain Integration for Elixir Chain
 // Adapted from parachain-integration.rs
-
-use frame_support::{decl_module, decl_storage, decl_event, ensure};
-use frame_system::{self as system, ensure_signed};
-use sp_std::prelude::*;
-use permaweb_lib::profile::{Profile, Zone, Wallet};
-
-// Adapted for ELXR parachain
+//
 // NRSH and ELXR Parachain Integration Module
 // Connects Arduino telemetry devices to Polkadot-based parachains
 // Target: Rococo testnet for initial demonstration
 // Copyright © 2025 NRSH/ELXR
+//
+// Devices report through `submit_telemetry`. ELXR readings and, for the NRSH
+// sister chain, spirulina readings are stored keyed by device ID and device
+// timestamp, so a device can report at most once per second. A reading is
+// only accepted if it carries a valid Dilithium signature by the key its
+// device is registered with, and the device is still authorized. Each
+// device numbers its readings, and a reading must carry the next nonce in
+// that sequence, so an old reading cannot be replayed.
+//
+// Devices have no funds, so a relay may also forward their readings as
+// unsigned transactions through `submit_unsigned_telemetry`. The device's
+// signature takes the place of fees: the pool only admits readings that
+// verify against a registered, authorized device and carry its next nonce,
+// or the one after, which waits behind it. A reading only goes in a block
+// `UnsignedInterval` blocks after the device's previous unsigned reading, and
+// devices registered longer ago are served first.
+//
+// ELXR readings are kept for `RetentionPeriod` blocks after they arrive. In
+// `on_idle` the expired ones are folded into per-device, per-batch hourly and
+// daily rollups and then removed, and the Merkle root of the removed readings
+// is kept in `PrunedRoots` for `PrunedRootRetention` blocks so an off-chain
+// archive of them stays provable.
+// NRSH readings are kept until the sister chain takes them over.
+//
+// `device_telemetry` and `batch_telemetry` back `runtime_api::TelemetryApi`.
+//
+// Readings stored before nonces were added are migrated by
+// `migrations::v1::MigrateToV1`.
 
-use clap::Parser;
-use codec::{Decode, Encode};
-use frame_support::{
-    decl_event, decl_module, decl_storage, dispatch::DispatchResult,
-    ensure, traits::Get,
-};
-use frame_system::{self as system, ensure_signed};
-use serde::{Deserialize, Serialize};
-use sp_core::{crypto::AccountId32, H256};
-use sp_runtime::{traits::Hash, RuntimeDebug};
-use sp_std::prelude::*;
-
-// Command-line interface for integration testing
-#[derive(Parser, Debug)]
-#[clap(author, version, about, long_about = None)]
-struct Args {
-    /// Rococo testnet endpoint
-    #[clap(short, long, default_value = "wss://rococo-rpc.polkadot.io")]
-    endpoint: String,
-
-    /// Serial port for Arduino connection
-    #[clap(short, long)]
-    serial_port: Option<String>,
-
-    /// Use simulated data instead of real device
-    #[clap(short, long)]
-    simulate: bool,
-
-    /// Project selection (nrsh or elxr)
-    #[clap(short, long, default_value = "nrsh")]
-    project: String,
-}
+pub use pallet::*;
+pub use signature::{DeviceRegistry, RegisteredDevice};
+pub use types::*;
+pub use weights::WeightInfo;
 
-// Data structures for telemetry data
-#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, Serialize, Deserialize)]
-pub struct NrshTelemetry<AccountId, Moment> {
-    pub device_id: Vec<u8>,
-    pub timestamp: Moment,
-    pub batch_id: Vec<u8>,
-    pub ph: u32,           // scaled by 100
-    pub temperature: u32,  // scaled by 100
-    pub light: u32,        // scaled by 10
-    pub density: u32,      // scaled by 1000
-    pub dissolved_oxygen: u32, // scaled by 100
-    pub nitrate: u32,      // scaled by 10
-    pub salinity: u32,     // scaled by 10
-    pub battery: u32,      // scaled by 10
-    pub overall_health: u32, // scaled by 10
-    pub harvest_ready: bool,
-    pub reporter: AccountId,
-    pub quantum_signature: Vec<u8>,
-}
+pub mod merkle;
+pub mod migrations;
+pub mod signature;
+pub mod types;
+pub mod weights;
 
-#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, Serialize, Deserialize)]
-pub struct ElxrTelemetry<AccountId, Moment> {
-    pub device_id: Vec<u8>,
-    pub timestamp: Moment,
-    pub ph: u32,           // scaled by 100
-    pub temperature: u32,  // scaled by 100
-    pub light: u32,        // scaled by 10
-    pub density: u32,      // scaled by 1000
-    pub co2: u32,          // scaled by 10
-    pub fermentation: u32, // scaled by 1000
-    pub battery: u32,      // scaled by 10
-    pub reporter: AccountId,
-    pub quantum_signature: Vec<u8>,
-}
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
 
-// Pallet definition for NRSH telemetry
-pub trait Config: system::Config {
-    type Event: From<Event<Self>> + Into<<Self as system::Config>::Event>;
-    type TelemetryId: Member + Parameter + Default + Copy;
-    type MaxDeviceIdLength: Get<u32>;
-    type MaxBatchIdLength: Get<u32>;
-    type MaxSignatureLength: Get<u32>;
-}
+#[frame_support::pallet]
+pub mod pallet {
+    use frame_support::pallet_prelude::*;
+    use frame_support::sp_runtime::traits::{Hash, Saturating};
+    use frame_support::sp_runtime::SaturatedConversion;
+    use sp_std::prelude::*;
+    use frame_system::pallet_prelude::*;
 
-decl_storage! {
-    trait Store for Module<T: Config> as NrshTelemetry {
-        // Storage for NRSH spirulina telemetry
-        pub SpirulinaTelemetry
+    use super::merkle::merkle_root;
+    use super::signature::{self, DeviceRegistry, RegisteredDevice};
+    use super::types::*;
+    use super::WeightInfo;
+    use crate::pallet::DeviceStatus;
+    use crate::runtime_api::Page;
 
-// ActorX implementation with permaweb integration
-pub struct ActorX {
-    profile: Profile,
-    zone: Zone,
-    wallet: Wallet,
-}
+    /// Layout of the pallet's storage, see [`crate::migrations`].
+    /// Version 1 added nonces to readings.
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
+    #[pallet::pallet]
+    #[pallet::storage_version(STORAGE_VERSION)]
+    pub struct Pallet<T>(_);
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        /// The overarching event type.
+        type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+        /// Longest device ID.
+        #[pallet::constant]
+        type MaxDeviceIdLength: Get<u32>;
+
+        /// Longest batch ID.
+        #[pallet::constant]
+        type MaxBatchIdLength: Get<u32>;
+
+        /// Longest quantum signature.
+        #[pallet::constant]
+        type MaxSignatureLength: Get<u32>;
+
+        /// Registry holding each device's status, public key and registration block.
+        type Devices: DeviceRegistry<BlockNumberFor<Self>>;
+
+        /// Fewest blocks between two unsigned readings from one device. Unsigned
+        /// readings stay valid in the pool for this long, or twice as long while
+        /// they wait behind the device's previous reading.
+        #[pallet::constant]
+        type UnsignedInterval: Get<BlockNumberFor<Self>>;
+
+        /// Priority of unsigned readings from a device registered in the current
+        /// block. Each block a device has been registered for adds one.
+        #[pallet::constant]
+        type UnsignedPriority: Get<TransactionPriority>;
+
+        /// Blocks an ELXR reading is kept for before it is rolled up and pruned.
+        #[pallet::constant]
+        type RetentionPeriod: Get<BlockNumberFor<Self>>;
+
+        /// Most readings pruned in one block.
+        #[pallet::constant]
+        type MaxPrunedPerBlock: Get<u32>;
+
+        /// Blocks the Merkle root of pruned readings is kept for.
+        #[pallet::constant]
+        type PrunedRootRetention: Get<BlockNumberFor<Self>>;
+
+        /// Weight information for extrinsics in this pallet.
+        type WeightInfo: WeightInfo;
+
+        /// Registers devices and signs readings for the benchmarks.
+        #[cfg(feature = "runtime-benchmarks")]
+        type BenchmarkHelper: signature::BenchmarkHelper;
+    }
+
+    /// Kombucha fermentation readings by device and device timestamp.
+    #[pallet::storage]
+    pub type ElxrReadings<T: Config> =
+        StorageDoubleMap<_, Blake2_128Concat, DeviceIdOf<T>, Twox64Concat, Moment, ElxrTelemetry<T>>;
+
+    /// Spirulina cultivation readings for the NRSH sister chain, by device and device timestamp.
+    #[pallet::storage]
+    pub type NrshReadings<T: Config> =
+        StorageDoubleMap<_, Blake2_128Concat, DeviceIdOf<T>, Twox64Concat, Moment, NrshTelemetry<T>>;
+
+    /// Nonce the next reading from each device must carry.
+    #[pallet::storage]
+    pub type DeviceNonces<T: Config> = StorageMap<_, Blake2_128Concat, DeviceIdOf<T>, u64, ValueQuery>;
+
+    /// Block of each device's latest unsigned reading.
+    #[pallet::storage]
+    pub type LastUnsignedAt<T: Config> = StorageMap<_, Blake2_128Concat, DeviceIdOf<T>, BlockNumberFor<T>>;
+
+    /// ELXR readings by the block they arrived in, for pruning.
+    #[pallet::storage]
+    pub type Arrivals<T: Config> =
+        StorageDoubleMap<_, Twox64Concat, BlockNumberFor<T>, Blake2_128Concat, (DeviceIdOf<T>, Moment), ()>;
+
+    /// Oldest arrival block that may still have readings to prune.
+    #[pallet::storage]
+    pub type PruneCursor<T: Config> = StorageValue<_, BlockNumberFor<T>, ValueQuery>;
+
+    /// Rollups of pruned readings by device, batch and Unix hour (`timestamp / HOUR`).
+    #[pallet::storage]
+    pub type HourlyRollups<T: Config> = StorageNMap<
+        _,
+        (
+            NMapKey<Blake2_128Concat, DeviceIdOf<T>>,
+            NMapKey<Blake2_128Concat, BatchIdOf<T>>,
+            NMapKey<Twox64Concat, u64>,
+        ),
+        Rollup,
+    >;
+
+    /// Rollups of pruned readings by device, batch and Unix day (`timestamp / DAY`).
+    #[pallet::storage]
+    pub type DailyRollups<T: Config> = StorageNMap<
+        _,
+        (
+            NMapKey<Blake2_128Concat, DeviceIdOf<T>>,
+            NMapKey<Blake2_128Concat, BatchIdOf<T>>,
+            NMapKey<Twox64Concat, u64>,
+        ),
+        Rollup,
+    >;
+
+    /// Merkle root of the readings pruned in each block, see [`merkle_root`].
+    #[pallet::storage]
+    pub type PrunedRoots<T: Config> = StorageMap<_, Twox64Concat, BlockNumberFor<T>, T::Hash>;
+
+    /// Oldest block that may still have a root in `PrunedRoots`, from the
+    /// first block any readings were pruned in.
+    #[pallet::storage]
+    pub type PrunedRootsCursor<T: Config> = StorageValue<_, BlockNumberFor<T>>;
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        TelemetryStored {
+            project: Project,
+            device_id: DeviceIdOf<T>,
+            timestamp: Moment,
+            reporter: T::AccountId,
+        },
+        /// Expired readings were rolled up and removed
+        ReadingsPruned { count: u32, root: T::Hash },
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// The reading names someone other than the caller as its reporter
+        ReporterMismatch,
+        /// The device already has a reading at this timestamp
+        DuplicateReading,
+        /// The device is not in the registry
+        UnknownDevice,
+        /// The device is suspended
+        DeviceSuspended,
+        /// The device has been revoked
+        DeviceRevoked,
+        /// The quantum signature does not match the reading and the device's key
+        InvalidSignature,
+        /// The reading does not carry the device's next nonce
+        BadNonce,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_idle(now: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+            let base = T::WeightInfo::prune(0);
+            if remaining_weight.any_lt(base) {
+                return Weight::zero();
+            }
+            let per_step = T::WeightInfo::prune(1).saturating_sub(base);
+            let limit = remaining_weight
+                .saturating_sub(base)
+                .checked_div_per_component(&per_step)
+                .unwrap_or(u64::MAX)
+                .min(T::MaxPrunedPerBlock::get().into()) as u32;
 
-impl ActorX {
-    pub fn new() -> Self {
-        let profile = Profile::new("ELXR-Parachain");
-        let zone = Zone::new(&profile);
-        let wallet = Wallet::new(&profile);
-        
-        Self {
-            profile,
-            zone,
-            wallet,
+            T::WeightInfo::prune(Self::prune(now, limit))
         }
     }
-}
 
-// Error correction integrations
-mod error_correction {
-    // Classical error correction
-    pub mod classical {
-        pub fn correct_errors(data: &[u8]) -> Vec<u8> {
-            // Reed-Solomon implementation
-            data.to_vec()
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Stores a reading reported by the caller and signed by its device.
+        #[pallet::call_index(0)]
+        #[pallet::weight(T::WeightInfo::submit_telemetry(report.quantum_signature().len() as u32))]
+        pub fn submit_telemetry(origin: OriginFor<T>, report: Report<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(*report.reporter() == who, Error::<T>::ReporterMismatch);
+            Self::verify_signature(&report)?;
+            ensure!(
+                report.nonce() == DeviceNonces::<T>::get(report.device_id()),
+                Error::<T>::BadNonce
+            );
+
+            Self::store(report)
+        }
+
+        /// Stores a reading signed by its device and forwarded without an
+        /// account. Only valid as an unsigned transaction, see
+        /// [`Pallet::validate_unsigned`].
+        #[pallet::call_index(1)]
+        #[pallet::weight(T::WeightInfo::submit_unsigned_telemetry(report.quantum_signature().len() as u32))]
+        pub fn submit_unsigned_telemetry(origin: OriginFor<T>, report: Report<T>) -> DispatchResult {
+            ensure_none(origin)?;
+
+            let now = frame_system::Pallet::<T>::block_number();
+            LastUnsignedAt::<T>::insert(report.device_id(), now);
+            Self::store(report)
         }
     }
-    
-    // Bridge error correction
-    pub mod bridge {
-        pub fn correct_interface_errors(data: &[u8]) -> Vec<u8> {
-            // Bridge protocol implementation
-            data.to_vec()
+
+    #[pallet::validate_unsigned]
+    impl<T: Config> ValidateUnsigned for Pallet<T> {
+        type Call = Call<T>;
+
+        /// Admits an unsigned reading signed by its device if it is the
+        /// device's next one, or queues it behind that one if it is the one
+        /// after. Older devices are served first.
+        ///
+        /// The rate limit is left to `pre_dispatch`, so a reading waiting for
+        /// it stays in the pool until the device's interval has passed.
+        fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+            let Call::submit_unsigned_telemetry { report } = call else {
+                return InvalidTransaction::Call.into();
+            };
+            let device_id = report.device_id();
+
+            let nonce = DeviceNonces::<T>::get(device_id);
+            ensure!(report.nonce() >= nonce, InvalidTransaction::Stale);
+            ensure!(report.nonce() <= nonce.saturating_add(1), InvalidTransaction::Future);
+            ensure!(!Self::is_duplicate(report), InvalidTransaction::Stale);
+
+            let device = Self::verify_signature(report).map_err(|error| match error {
+                Error::<T>::InvalidSignature => InvalidTransaction::BadProof,
+                _ => InvalidTransaction::BadSigner,
+            })?;
+
+            let now = frame_system::Pallet::<T>::block_number();
+            let age: TransactionPriority = now.saturating_sub(device.registered_at).saturated_into();
+            let interval: u64 = T::UnsignedInterval::get().saturated_into::<u64>().max(1);
+            let mut validity = ValidTransaction::with_tag_prefix("ElxrTelemetry")
+                .priority(T::UnsignedPriority::get().saturating_add(age))
+                .and_provides((device_id, report.nonce()))
+                .longevity(interval)
+                .propagate(true);
+            if report.nonce() > nonce {
+                validity = validity.and_requires((device_id, nonce)).longevity(interval.saturating_mul(2));
+            }
+            validity.build()
+        }
+
+        /// Only the device's next reading goes in a block, and only once
+        /// `UnsignedInterval` blocks have passed since its previous unsigned one.
+        fn pre_dispatch(call: &Self::Call) -> Result<(), TransactionValidityError> {
+            let Call::submit_unsigned_telemetry { report } = call else {
+                return Err(InvalidTransaction::Call.into());
+            };
+            let device_id = report.device_id();
+            ensure!(
+                report.nonce() <= DeviceNonces::<T>::get(device_id),
+                InvalidTransaction::Future
+            );
+
+            let now = frame_system::Pallet::<T>::block_number();
+            let interval = T::UnsignedInterval::get();
+            ensure!(
+                LastUnsignedAt::<T>::get(device_id).is_none_or(|last| now >= last.saturating_add(interval)),
+                InvalidTransaction::ExhaustsResources
+            );
+            Self::validate_unsigned(TransactionSource::InBlock, call).map(|_| ())
         }
     }
-    
-    // Quantum error correction
-    pub mod quantum {
-        pub fn correct_quantum_errors(data: &[u8]) -> Vec<u8> {
-            // Surface code implementation
-            data.to_vec()
+
+    impl<T: Config> Pallet<T> {
+        /// Stored readings from `device_id` with timestamps in `from..=to`, in
+        /// storage order. Only the readings up to the end of `page` are read.
+        pub fn device_telemetry(
+            device_id: Vec<u8>,
+            from: Moment,
+            to: Moment,
+            page: Page,
+        ) -> Vec<ElxrTelemetry<T>> {
+            let Ok(device_id) = DeviceIdOf::<T>::try_from(device_id) else {
+                return Vec::new();
+            };
+            page.of(
+                ElxrReadings::<T>::iter_prefix_values(device_id)
+                    .filter(|reading| (from..=to).contains(&reading.timestamp)),
+            )
+        }
+
+        /// Stored readings for `batch_id` from every device, with timestamps in
+        /// `from..=to`, oldest first. Scans every stored ELXR reading.
+        pub fn batch_telemetry(
+            batch_id: Vec<u8>,
+            from: Moment,
+            to: Moment,
+            page: Page,
+        ) -> Vec<ElxrTelemetry<T>> {
+            let mut readings: Vec<_> = ElxrReadings::<T>::iter_values()
+                .filter(|reading| {
+                    reading.batch_id[..] == batch_id[..] && (from..=to).contains(&reading.timestamp)
+                })
+                .collect();
+            readings.sort_by(|a, b| (a.timestamp, &a.device_id).cmp(&(b.timestamp, &b.device_id)));
+            page.of(readings.into_iter())
+        }
+
+        /// Checks that the reading's device is authorized and that it signed
+        /// the reading, and returns the device's registry record.
+        fn verify_signature(report: &Report<T>) -> Result<RegisteredDevice<BlockNumberFor<T>>, Error<T>> {
+            let device = T::Devices::device(report.device_id()).ok_or(Error::<T>::UnknownDevice)?;
+            match device.status {
+                DeviceStatus::Authorized => {}
+                DeviceStatus::Suspended => return Err(Error::<T>::DeviceSuspended),
+                DeviceStatus::Revoked => return Err(Error::<T>::DeviceRevoked),
+            }
+            ensure!(
+                signature::verify(&device.public_key, &report.signing_payload(), report.quantum_signature()),
+                Error::<T>::InvalidSignature
+            );
+            Ok(device)
+        }
+
+        /// Whether the device already has a reading at the report's timestamp.
+        fn is_duplicate(report: &Report<T>) -> bool {
+            let (device_id, timestamp) = (report.device_id(), report.timestamp());
+            match report {
+                Report::Elxr(_) => ElxrReadings::<T>::contains_key(device_id, timestamp),
+                Report::Nrsh(_) => NrshReadings::<T>::contains_key(device_id, timestamp),
+            }
+        }
+
+        /// Stores a verified reading and advances its device's nonce.
+        fn store(report: Report<T>) -> DispatchResult {
+            ensure!(!Self::is_duplicate(&report), Error::<T>::DuplicateReading);
+
+            let project = report.project();
+            let device_id = report.device_id().clone();
+            let timestamp = report.timestamp();
+            let reporter = report.reporter().clone();
+            match report {
+                Report::Elxr(reading) => {
+                    ElxrReadings::<T>::insert(&device_id, timestamp, reading);
+                    let now = frame_system::Pallet::<T>::block_number();
+                    Arrivals::<T>::insert(now, (&device_id, timestamp), ());
+                }
+                Report::Nrsh(reading) => NrshReadings::<T>::insert(&device_id, timestamp, reading),
+            }
+            DeviceNonces::<T>::mutate(&device_id, |nonce| *nonce = nonce.saturating_add(1));
+
+            Self::deposit_event(Event::TelemetryStored {
+                project,
+                device_id,
+                timestamp,
+                reporter,
+            });
+            Ok(())
+        }
+
+        /// Rolls up and removes ELXR readings that arrived at least
+        /// `RetentionPeriod` blocks before `now`, then drops roots recorded
+        /// `PrunedRootRetention` blocks before `now`, taking at most `limit`
+        /// steps. A step is one pruned reading, one arrival block found empty
+        /// or one block whose root was dropped. Returns the number of steps taken.
+        pub(crate) fn prune(now: BlockNumberFor<T>, limit: u32) -> u32 {
+            let steps = Self::prune_readings(now, limit);
+            steps + Self::prune_roots(now, limit - steps)
+        }
+
+        /// Pruning of the readings for [`Self::prune`].
+        fn prune_readings(now: BlockNumberFor<T>, limit: u32) -> u32 {
+            let retention = T::RetentionPeriod::get();
+            if now < retention {
+                return 0;
+            }
+            let horizon = now - retention;
+
+            let mut cursor = PruneCursor::<T>::get();
+            let mut steps = 0;
+            let mut pruned = Vec::new();
+            while cursor <= horizon && steps < limit {
+                let budget = (limit - steps) as usize;
+                let keys: Vec<_> = Arrivals::<T>::iter_key_prefix(cursor).take(budget).collect();
+                let exhausted = keys.len() < budget;
+                steps += keys.len() as u32;
+
+                for (device_id, timestamp) in keys {
+                    Arrivals::<T>::remove(cursor, (&device_id, timestamp));
+                    if let Some(reading) = ElxrReadings::<T>::take(&device_id, timestamp) {
+                        Self::roll_up(&reading);
+                        pruned.push(reading);
+                    }
+                }
+                if exhausted {
+                    cursor.saturating_inc();
+                    steps += 1;
+                }
+            }
+            PruneCursor::<T>::put(cursor);
+
+            if !pruned.is_empty() {
+                // Storage order follows key hashes, so sort for a root an archive can recompute
+                pruned.sort_by(|a, b| (&a.device_id, a.timestamp).cmp(&(&b.device_id, b.timestamp)));
+                let count = pruned.len() as u32;
+                let root = merkle_root::<T::Hashing>(pruned.iter().map(T::Hashing::hash_of).collect());
+                PrunedRoots::<T>::insert(now, root);
+                PrunedRootsCursor::<T>::mutate(|cursor| {
+                    cursor.get_or_insert(now);
+                });
+                Self::deposit_event(Event::ReadingsPruned { count, root });
+            }
+            steps
+        }
+
+        /// Dropping of expired roots for [`Self::prune`].
+        fn prune_roots(now: BlockNumberFor<T>, limit: u32) -> u32 {
+            let retention = T::PrunedRootRetention::get();
+            let Some(mut cursor) = PrunedRootsCursor::<T>::get() else {
+                return 0;
+            };
+            if now < retention {
+                return 0;
+            }
+            let horizon = now - retention;
+
+            let mut steps = 0;
+            while cursor <= horizon && steps < limit {
+                PrunedRoots::<T>::remove(cursor);
+                cursor.saturating_inc();
+                steps += 1;
+            }
+            PrunedRootsCursor::<T>::put(cursor);
+            steps
+        }
+
+        /// Folds `reading` into its hourly and daily rollups.
+        fn roll_up(reading: &ElxrTelemetry<T>) {
+            let record = |rollup: &mut Option<Rollup>| match rollup {
+                Some(rollup) => rollup.record(reading),
+                None => *rollup = Some(Rollup::new(reading)),
+            };
+            let (device_id, batch_id) = (&reading.device_id, &reading.batch_id);
+            HourlyRollups::<T>::mutate((device_id, batch_id, reading.timestamp / HOUR), record);
+            DailyRollups::<T>::mutate((device_id, batch_id, reading.timestamp / DAY), record);
         }
     }
 }
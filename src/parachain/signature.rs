@@ -0,0 +1,56 @@
+// Post-quantum signatures on telemetry readings
+//
+// Devices sign each reading with Dilithium2 (ML-DSA-44 parameters) using the
+// key they were registered with in the ELXR registry. The signed message is
+// the reading's signing payload, its SCALE encoding without the signature.
+use frame_system::pallet_prelude::BlockNumberFor;
+use sp_std::prelude::*;
+
+use crate::pallet::{self as registry, DeviceStatus};
+
+/// Length of a Dilithium2 public key.
+pub const PUBLIC_KEY_LENGTH: u32 = pqc_dilithium::PUBLICKEYBYTES as u32;
+/// Length of a Dilithium2 signature.
+pub const SIGNATURE_LENGTH: u32 = pqc_dilithium::SIGNBYTES as u32;
+
+/// What the telemetry pallet knows about a device from its registry.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RegisteredDevice<BlockNumber> {
+    pub status: DeviceStatus,
+    pub public_key: Vec<u8>,
+    /// Block the device was registered in
+    pub registered_at: BlockNumber,
+}
+
+/// Where the telemetry pallet looks up the devices allowed to report.
+pub trait DeviceRegistry<BlockNumber> {
+    /// Registry record of `device_id`, or `None` if it was never registered.
+    fn device(device_id: &[u8]) -> Option<RegisteredDevice<BlockNumber>>;
+}
+
+impl<T: registry::Config> DeviceRegistry<BlockNumberFor<T>> for registry::Pallet<T> {
+    fn device(device_id: &[u8]) -> Option<RegisteredDevice<BlockNumberFor<T>>> {
+        // An ID longer than the registry allows cannot have been registered
+        let device_id = registry::DeviceIdOf::<T>::try_from(device_id.to_vec()).ok()?;
+        registry::Devices::<T>::get(device_id).map(|device| RegisteredDevice {
+            status: device.status,
+            public_key: device.public_key.into_inner(),
+            registered_at: device.registered_at,
+        })
+    }
+}
+
+/// Sets up devices that can sign readings, for benchmarks.
+#[cfg(feature = "runtime-benchmarks")]
+pub trait BenchmarkHelper {
+    /// Registers `device_id` as authorized with a new key pair, and returns
+    /// the signature of `message` by it.
+    fn register_and_sign(device_id: &[u8], message: &[u8]) -> Vec<u8>;
+}
+
+/// Whether `signature` is a valid Dilithium2 signature of `message` by
+/// `public_key`. Keys of any other length never verify.
+pub fn verify(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    public_key.len() == PUBLIC_KEY_LENGTH as usize
+        && pqc_dilithium::verify(signature, message, public_key).is_ok()
+}
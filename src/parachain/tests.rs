@@ -0,0 +1,540 @@
+// Telemetry submission for both chains
+use codec::{Decode, Encode};
+use frame_support::pallet_prelude::{InvalidTransaction, TransactionSource, ValidateUnsigned};
+use frame_support::traits::{GetStorageVersion, Hooks, StorageVersion};
+use frame_support::weights::Weight;
+use frame_support::{assert_noop, assert_ok};
+use sp_core::H256;
+use sp_runtime::traits::{BlakeTwo256, Hash};
+use sp_runtime::DispatchError;
+use pqc_dilithium::Keypair;
+
+use super::migrations::v1::{self, MigrateToV1};
+use super::mock::*;
+use super::*;
+use crate::migrations::run_upgrade;
+use crate::pallet::DeviceStatus;
+use crate::runtime_api::Page;
+
+const DEVICE: &[u8] = b"ELXR-01";
+
+fn device_id() -> DeviceIdOf<Test> {
+    bounded(DEVICE)
+}
+
+fn elxr_reading(timestamp: Moment, nonce: u64) -> ElxrTelemetry<Test> {
+    ElxrTelemetry {
+        device_id: bounded(DEVICE),
+        timestamp,
+        nonce,
+        batch_id: bounded(b"BATCH-12"),
+        ph: 310,
+        temperature: 2450,
+        light: 1200,
+        density: 1012,
+        co2: 6000,
+        fermentation: 420,
+        battery: 37,
+        reporter: RELAY,
+        quantum_signature: Default::default(),
+    }
+}
+
+fn signed_elxr(timestamp: Moment, nonce: u64, keys: &Keypair) -> ElxrTelemetry<Test> {
+    let mut reading = elxr_reading(timestamp, nonce);
+    reading.quantum_signature = bounded(&keys.sign(&reading.signing_payload()));
+    reading
+}
+
+fn nrsh_reading(timestamp: Moment) -> NrshTelemetry<Test> {
+    NrshTelemetry {
+        device_id: bounded(b"NRSH-01"),
+        timestamp,
+        nonce: 0,
+        batch_id: bounded(b"POND-3"),
+        ph: 950,
+        temperature: 3300,
+        light: 40_000,
+        density: 1500,
+        dissolved_oxygen: 720,
+        nitrate: 180,
+        salinity: 150,
+        battery: 39,
+        overall_health: 88,
+        harvest_ready: false,
+        reporter: RELAY,
+        quantum_signature: Default::default(),
+    }
+}
+
+fn old_elxr_reading(timestamp: Moment) -> v1::OldElxrTelemetry<Test> {
+    let reading = elxr_reading(timestamp, 0);
+    v1::OldElxrTelemetry {
+        device_id: reading.device_id,
+        timestamp,
+        batch_id: reading.batch_id,
+        ph: reading.ph,
+        temperature: reading.temperature,
+        light: reading.light,
+        density: reading.density,
+        co2: reading.co2,
+        fermentation: reading.fermentation,
+        battery: reading.battery,
+        reporter: reading.reporter,
+        quantum_signature: bounded(b"signed by ELXR-01"),
+    }
+}
+
+fn old_nrsh_reading(timestamp: Moment) -> v1::OldNrshTelemetry<Test> {
+    let reading = nrsh_reading(timestamp);
+    v1::OldNrshTelemetry {
+        device_id: reading.device_id,
+        timestamp,
+        batch_id: reading.batch_id,
+        ph: reading.ph,
+        temperature: reading.temperature,
+        light: reading.light,
+        density: reading.density,
+        dissolved_oxygen: reading.dissolved_oxygen,
+        nitrate: reading.nitrate,
+        salinity: reading.salinity,
+        battery: reading.battery,
+        overall_health: reading.overall_health,
+        harvest_ready: reading.harvest_ready,
+        reporter: reading.reporter,
+        quantum_signature: bounded(b"signed by NRSH-01"),
+    }
+}
+
+#[test]
+fn elxr_readings_are_stored_by_device_and_timestamp() {
+    new_test_ext().execute_with(|| {
+        let keys = register_device(DEVICE, DeviceStatus::Authorized);
+        let reading = signed_elxr(1_700_000_000, 0, &keys);
+        assert_ok!(Telemetry::submit_telemetry(
+            RuntimeOrigin::signed(RELAY),
+            Report::Elxr(reading.clone())
+        ));
+
+        assert_eq!(ElxrReadings::<Test>::get(&reading.device_id, 1_700_000_000), Some(reading.clone()));
+        System::assert_last_event(
+            Event::TelemetryStored {
+                project: Project::Elxr,
+                device_id: reading.device_id,
+                timestamp: 1_700_000_000,
+                reporter: RELAY,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn nrsh_readings_are_kept_apart() {
+    new_test_ext().execute_with(|| {
+        let keys = register_device(b"NRSH-01", DeviceStatus::Authorized);
+        let mut reading = nrsh_reading(1_700_000_000);
+        reading.quantum_signature = bounded(&keys.sign(&reading.signing_payload()));
+        assert_ok!(Telemetry::submit_telemetry(
+            RuntimeOrigin::signed(RELAY),
+            Report::Nrsh(reading.clone())
+        ));
+
+        assert_eq!(NrshReadings::<Test>::get(&reading.device_id, 1_700_000_000), Some(reading));
+        assert_eq!(ElxrReadings::<Test>::iter().count(), 0);
+    });
+}
+
+#[test]
+fn duplicate_timestamps_and_foreign_reporters_are_rejected() {
+    new_test_ext().execute_with(|| {
+        let keys = register_device(DEVICE, DeviceStatus::Authorized);
+        assert_ok!(Telemetry::submit_telemetry(
+            RuntimeOrigin::signed(RELAY),
+            Report::Elxr(signed_elxr(1_700_000_000, 0, &keys))
+        ));
+        assert_noop!(
+            Telemetry::submit_telemetry(
+                RuntimeOrigin::signed(RELAY),
+                Report::Elxr(signed_elxr(1_700_000_000, 1, &keys))
+            ),
+            Error::<Test>::DuplicateReading
+        );
+        assert_noop!(
+            Telemetry::submit_telemetry(
+                RuntimeOrigin::signed(STRANGER),
+                Report::Elxr(signed_elxr(1_700_000_300, 1, &keys))
+            ),
+            Error::<Test>::ReporterMismatch
+        );
+    });
+}
+
+#[test]
+fn readings_must_carry_the_next_nonce() {
+    new_test_ext().execute_with(|| {
+        let keys = register_device(DEVICE, DeviceStatus::Authorized);
+        assert_ok!(Telemetry::submit_telemetry(
+            RuntimeOrigin::signed(RELAY),
+            Report::Elxr(signed_elxr(1_700_000_000, 0, &keys))
+        ));
+        assert_eq!(DeviceNonces::<Test>::get(device_id()), 1);
+
+        for nonce in [0, 2] {
+            assert_noop!(
+                Telemetry::submit_telemetry(
+                    RuntimeOrigin::signed(RELAY),
+                    Report::Elxr(signed_elxr(1_700_000_300, nonce, &keys))
+                ),
+                Error::<Test>::BadNonce
+            );
+        }
+    });
+}
+
+#[test]
+fn readings_must_be_signed_by_the_registered_key() {
+    new_test_ext().execute_with(|| {
+        register_device(DEVICE, DeviceStatus::Authorized);
+        let impostor = Keypair::generate();
+        assert_noop!(
+            Telemetry::submit_telemetry(
+                RuntimeOrigin::signed(RELAY),
+                Report::Elxr(signed_elxr(1_700_000_000, 0, &impostor))
+            ),
+            Error::<Test>::InvalidSignature
+        );
+
+        let keys = register_device(DEVICE, DeviceStatus::Authorized);
+        let mut tampered = signed_elxr(1_700_000_000, 0, &keys);
+        tampered.ph += 1;
+        assert_noop!(
+            Telemetry::submit_telemetry(RuntimeOrigin::signed(RELAY), Report::Elxr(tampered)),
+            Error::<Test>::InvalidSignature
+        );
+    });
+}
+
+#[test]
+fn devices_with_keys_of_the_wrong_length_cannot_report() {
+    new_test_ext().execute_with(|| {
+        let keys = register_device(DEVICE, DeviceStatus::Authorized);
+        // As if registered before the registry checked key lengths
+        let registry_id: crate::pallet::DeviceIdOf<Test> = bounded(DEVICE);
+        crate::pallet::Devices::<Test>::mutate(registry_id, |device| {
+            device.as_mut().unwrap().public_key = bounded(&[7; 32]);
+        });
+
+        let reading = signed_elxr(1_700_000_000, 0, &keys);
+        assert!(!signature::verify(&[7; 32], &reading.signing_payload(), &reading.quantum_signature));
+        assert_noop!(
+            Telemetry::submit_telemetry(RuntimeOrigin::signed(RELAY), Report::Elxr(reading)),
+            Error::<Test>::InvalidSignature
+        );
+    });
+}
+
+#[test]
+fn unknown_suspended_and_revoked_devices_are_rejected() {
+    new_test_ext().execute_with(|| {
+        let keys = Keypair::generate();
+        assert_noop!(
+            Telemetry::submit_telemetry(
+                RuntimeOrigin::signed(RELAY),
+                Report::Elxr(signed_elxr(1_700_000_000, 0, &keys))
+            ),
+            Error::<Test>::UnknownDevice
+        );
+
+        for (status, error) in [
+            (DeviceStatus::Suspended, Error::<Test>::DeviceSuspended),
+            (DeviceStatus::Revoked, Error::<Test>::DeviceRevoked),
+        ] {
+            let keys = register_device(DEVICE, status);
+            assert_noop!(
+                Telemetry::submit_telemetry(
+                    RuntimeOrigin::signed(RELAY),
+                    Report::Elxr(signed_elxr(1_700_000_000, 0, &keys))
+                ),
+                error
+            );
+        }
+    });
+}
+
+#[test]
+fn unsigned_readings_are_admitted_and_stored() {
+    new_test_ext().execute_with(|| {
+        let keys = register_device(DEVICE, DeviceStatus::Authorized);
+        let report = Report::Elxr(signed_elxr(1_700_000_000, 0, &keys));
+        let call = Call::submit_unsigned_telemetry { report: report.clone() };
+
+        let valid = Telemetry::validate_unsigned(TransactionSource::External, &call).unwrap();
+        assert_eq!(valid.priority, 1 << 20);
+        assert_eq!(valid.longevity, 10);
+        assert_eq!(valid.provides.len(), 1);
+        assert!(valid.requires.is_empty());
+
+        // Devices registered longer ago go first
+        System::set_block_number(5);
+        let valid = Telemetry::validate_unsigned(TransactionSource::External, &call).unwrap();
+        assert_eq!(valid.priority, (1 << 20) + 4);
+
+        assert_noop!(
+            Telemetry::submit_unsigned_telemetry(RuntimeOrigin::signed(RELAY), report.clone()),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(Telemetry::submit_unsigned_telemetry(RuntimeOrigin::none(), report));
+        assert_eq!(DeviceNonces::<Test>::get(device_id()), 1);
+        assert_eq!(LastUnsignedAt::<Test>::get(device_id()), Some(1));
+    });
+}
+
+#[test]
+fn unsigned_readings_are_queued_and_rate_limited() {
+    new_test_ext().execute_with(|| {
+        let keys = register_device(DEVICE, DeviceStatus::Authorized);
+        let call = |reading| Call::submit_unsigned_telemetry { report: Report::Elxr(reading) };
+        let validate = |reading| Telemetry::validate_unsigned(TransactionSource::External, &call(reading));
+
+        // The reading after the next one waits in the pool behind it
+        let next = validate(signed_elxr(1_700_000_000, 0, &keys)).unwrap();
+        let queued = validate(signed_elxr(1_700_000_300, 1, &keys)).unwrap();
+        assert_eq!(queued.requires, next.provides);
+        assert_eq!(queued.longevity, 20);
+        assert_eq!(
+            validate(signed_elxr(1_700_000_600, 2, &keys)),
+            Err(InvalidTransaction::Future.into())
+        );
+        assert_eq!(
+            Telemetry::pre_dispatch(&call(signed_elxr(1_700_000_300, 1, &keys))),
+            Err(InvalidTransaction::Future.into())
+        );
+
+        assert_ok!(Telemetry::pre_dispatch(&call(signed_elxr(1_700_000_000, 0, &keys))));
+        assert_ok!(Telemetry::submit_unsigned_telemetry(
+            RuntimeOrigin::none(),
+            Report::Elxr(signed_elxr(1_700_000_000, 0, &keys))
+        ));
+        assert_eq!(
+            validate(signed_elxr(1_700_000_300, 0, &keys)),
+            Err(InvalidTransaction::Stale.into())
+        );
+
+        // Revalidation keeps the reading in the pool until the interval has passed
+        System::set_block_number(10);
+        assert!(validate(signed_elxr(1_700_000_300, 1, &keys)).unwrap().requires.is_empty());
+        assert_eq!(
+            Telemetry::pre_dispatch(&call(signed_elxr(1_700_000_300, 1, &keys))),
+            Err(InvalidTransaction::ExhaustsResources.into())
+        );
+        System::set_block_number(11);
+        assert_ok!(Telemetry::pre_dispatch(&call(signed_elxr(1_700_000_300, 1, &keys))));
+    });
+}
+
+#[test]
+fn unsigned_readings_are_checked_in_the_pool() {
+    new_test_ext().execute_with(|| {
+        let keys = register_device(DEVICE, DeviceStatus::Authorized);
+        let validate = |reading| {
+            let call = Call::submit_unsigned_telemetry { report: Report::Elxr(reading) };
+            Telemetry::validate_unsigned(TransactionSource::External, &call)
+        };
+
+        assert_eq!(
+            validate(signed_elxr(1_700_000_000, 0, &Keypair::generate())),
+            Err(InvalidTransaction::BadProof.into())
+        );
+        register_device(DEVICE, DeviceStatus::Suspended);
+        assert_eq!(
+            validate(signed_elxr(1_700_000_000, 0, &keys)),
+            Err(InvalidTransaction::BadSigner.into())
+        );
+    });
+}
+
+#[test]
+fn expired_readings_are_rolled_up_and_pruned() {
+    new_test_ext().execute_with(|| {
+        let keys = register_device(DEVICE, DeviceStatus::Authorized);
+        // Two readings in one hour and a third in the next, all on one day
+        for (nonce, (timestamp, ph)) in [(1_700_000_000, 300), (1_700_000_300, 320), (1_700_003_600, 340)]
+            .into_iter()
+            .enumerate()
+        {
+            let mut reading = elxr_reading(timestamp, nonce as u64);
+            reading.ph = ph;
+            reading.quantum_signature = bounded(&keys.sign(&reading.signing_payload()));
+            assert_ok!(Telemetry::submit_telemetry(RuntimeOrigin::signed(RELAY), Report::Elxr(reading)));
+        }
+        let leaf = |(device_id, timestamp): &(DeviceIdOf<Test>, Moment)| -> H256 {
+            BlakeTwo256::hash_of(&ElxrReadings::<Test>::get(device_id, timestamp).unwrap())
+        };
+        // Readings are pruned in storage order, but hashed in device and timestamp order
+        let arrived: Vec<_> = Arrivals::<Test>::iter_key_prefix(1).collect();
+        let mut first = arrived[..2].to_vec();
+        first.sort();
+        let first_root = merkle::merkle_root::<BlakeTwo256>(first.iter().map(leaf).collect());
+        let last_leaf = leaf(&arrived[2]);
+
+        // Nothing has expired yet
+        Telemetry::on_idle(100, Weight::MAX);
+        assert_eq!(ElxrReadings::<Test>::iter().count(), 3);
+
+        // `MaxPrunedPerBlock` spreads the three readings over two blocks
+        Telemetry::on_idle(101, Weight::MAX);
+        assert_eq!(ElxrReadings::<Test>::iter().count(), 1);
+        assert_eq!(PrunedRoots::<Test>::get(101), Some(first_root));
+        System::assert_last_event(Event::ReadingsPruned { count: 2, root: first_root }.into());
+        Telemetry::on_idle(102, Weight::MAX);
+        assert_eq!(ElxrReadings::<Test>::iter().count(), 0);
+        assert_eq!(Arrivals::<Test>::iter().count(), 0);
+        assert_eq!(PrunedRoots::<Test>::get(102), Some(last_leaf));
+
+        // Roots are dropped `PrunedRootRetention` blocks after they were recorded
+        Telemetry::prune(151, 100);
+        assert_eq!(PrunedRoots::<Test>::get(101), None);
+        assert_eq!(PrunedRoots::<Test>::get(102), Some(last_leaf));
+        assert_eq!(PrunedRootsCursor::<Test>::get(), Some(102));
+
+        let batch_id = bounded(b"BATCH-12");
+        let first_hour =
+            HourlyRollups::<Test>::get((device_id(), &batch_id, 1_700_000_000 / HOUR)).unwrap();
+        assert_eq!(first_hour.count, 2);
+        assert_eq!((first_hour.ph.min, first_hour.ph.max), (300, 320));
+        assert_eq!(first_hour.ph.mean(first_hour.count), 310);
+        let second_hour =
+            HourlyRollups::<Test>::get((device_id(), &batch_id, 1_700_003_600 / HOUR)).unwrap();
+        assert_eq!(second_hour.count, 1);
+        let day = DailyRollups::<Test>::get((device_id(), &batch_id, 1_700_000_000 / DAY)).unwrap();
+        assert_eq!(day.count, 3);
+        assert_eq!((day.ph.min, day.ph.max, day.ph.mean(day.count)), (300, 340, 320));
+        assert_eq!((day.co2.min, day.co2.max, day.co2.sum), (6000, 6000, 18_000));
+    });
+}
+
+#[test]
+fn pruning_waits_for_idle_weight() {
+    new_test_ext().execute_with(|| {
+        let keys = register_device(DEVICE, DeviceStatus::Authorized);
+        assert_ok!(Telemetry::submit_telemetry(
+            RuntimeOrigin::signed(RELAY),
+            Report::Elxr(signed_elxr(1_700_000_000, 0, &keys))
+        ));
+
+        assert_eq!(Telemetry::on_idle(200, Weight::zero()), Weight::zero());
+        assert_eq!(ElxrReadings::<Test>::iter().count(), 1);
+        assert_ne!(Telemetry::on_idle(200, Weight::MAX), Weight::zero());
+        assert_eq!(ElxrReadings::<Test>::iter().count(), 0);
+    });
+}
+
+#[test]
+fn merkle_root_pairs_leaves_and_promotes_the_odd_one() {
+    let [a, b, c] = [1u8, 2, 3].map(|leaf| BlakeTwo256::hash(&[leaf]));
+    let pair = |left: H256, right: H256| BlakeTwo256::hash(&[left.as_ref(), right.as_ref()].concat());
+
+    assert_eq!(merkle::merkle_root::<BlakeTwo256>(vec![]), H256::zero());
+    assert_eq!(merkle::merkle_root::<BlakeTwo256>(vec![a]), a);
+    assert_eq!(merkle::merkle_root::<BlakeTwo256>(vec![a, b, c]), pair(pair(a, b), c));
+}
+
+#[test]
+fn telemetry_queries_filter_and_page() {
+    new_test_ext().execute_with(|| {
+        let keys = register_device(DEVICE, DeviceStatus::Authorized);
+        for (nonce, timestamp) in [1_700_000_600, 1_700_000_000, 1_700_000_300].into_iter().enumerate() {
+            assert_ok!(Telemetry::submit_telemetry(
+                RuntimeOrigin::signed(RELAY),
+                Report::Elxr(signed_elxr(timestamp, nonce as u64, &keys))
+            ));
+        }
+        let other_keys = register_device(b"ELXR-02", DeviceStatus::Authorized);
+        let mut other = elxr_reading(1_700_000_100, 0);
+        other.device_id = bounded(b"ELXR-02");
+        other.quantum_signature = bounded(&other_keys.sign(&other.signing_payload()));
+        assert_ok!(Telemetry::submit_telemetry(RuntimeOrigin::signed(RELAY), Report::Elxr(other)));
+
+        let timestamps = |readings: Vec<ElxrTelemetry<Test>>| {
+            readings.into_iter().map(|reading| reading.timestamp).collect::<Vec<_>>()
+        };
+        let sorted = |mut timestamps: Vec<Moment>| {
+            timestamps.sort();
+            timestamps
+        };
+        // A device's readings come in storage order, the same on every call
+        assert_eq!(
+            sorted(timestamps(Telemetry::device_telemetry(DEVICE.to_vec(), 0, u64::MAX, Page::default()))),
+            vec![1_700_000_000, 1_700_000_300, 1_700_000_600]
+        );
+        let in_range = |page| {
+            timestamps(Telemetry::device_telemetry(DEVICE.to_vec(), 1_700_000_100, 1_700_000_600, page))
+        };
+        let all = in_range(Page::default());
+        assert_eq!(sorted(all.clone()), vec![1_700_000_300, 1_700_000_600]);
+        assert_eq!(in_range(Page { offset: 1, limit: 1 }), all[1..]);
+        assert_eq!(
+            timestamps(Telemetry::batch_telemetry(b"BATCH-12".to_vec(), 0, 1_700_000_300, Page::default())),
+            vec![1_700_000_000, 1_700_000_100, 1_700_000_300]
+        );
+        assert!(Telemetry::batch_telemetry(b"BATCH-99".to_vec(), 0, u64::MAX, Page::default()).is_empty());
+    });
+}
+
+#[test]
+fn bounds_are_enforced_when_decoding() {
+    let mut reading = elxr_reading(1_700_000_000, 0).encode();
+    // Device ID length prefix claims more bytes than `MaxDeviceIdLength`
+    reading[0] = 17 << 2;
+    assert!(ElxrTelemetry::<Test>::decode(&mut &reading[..]).is_err());
+}
+
+#[test]
+fn v0_readings_are_numbered_per_device_and_queued_for_pruning() {
+    new_test_ext().execute_with(|| {
+        // Storage as a chain left it before nonces were added
+        StorageVersion::new(0).put::<Telemetry>();
+        for timestamp in [1_700_000_600, 1_700_000_000, 1_700_000_300] {
+            v1::ElxrReadings::<Test>::insert(device_id(), timestamp, old_elxr_reading(timestamp));
+        }
+        let nrsh_device: DeviceIdOf<Test> = bounded(b"NRSH-01");
+        v1::NrshReadings::<Test>::insert(&nrsh_device, 1_700_000_100, old_nrsh_reading(1_700_000_100));
+
+        run_upgrade::<MigrateToV1<Test>>();
+
+        assert_eq!(Telemetry::on_chain_storage_version(), 1);
+        for (nonce, timestamp) in [1_700_000_000, 1_700_000_300, 1_700_000_600].into_iter().enumerate() {
+            let reading = ElxrReadings::<Test>::get(device_id(), timestamp).unwrap();
+            assert_eq!(reading.nonce, nonce as u64);
+            assert_eq!(reading.quantum_signature, bounded(b"signed by ELXR-01"));
+            assert!(Arrivals::<Test>::contains_key(1, (device_id(), timestamp)));
+        }
+        assert_eq!(NrshReadings::<Test>::get(&nrsh_device, 1_700_000_100).unwrap().nonce, 0);
+        assert_eq!(DeviceNonces::<Test>::get(device_id()), 3);
+        assert_eq!(DeviceNonces::<Test>::get(&nrsh_device), 1);
+
+        // The device carries on from the nonce after its migrated readings
+        let keys = register_device(DEVICE, DeviceStatus::Authorized);
+        assert_ok!(Telemetry::submit_telemetry(
+            RuntimeOrigin::signed(RELAY),
+            Report::Elxr(signed_elxr(1_700_000_900, 3, &keys))
+        ));
+    });
+}
+
+#[test]
+fn migration_to_v1_leaves_migrated_storage_alone() {
+    new_test_ext().execute_with(|| {
+        StorageVersion::new(1).put::<Telemetry>();
+        let reading = elxr_reading(1_700_000_000, 5);
+        ElxrReadings::<Test>::insert(device_id(), 1_700_000_000, reading.clone());
+        DeviceNonces::<Test>::insert(device_id(), 6);
+
+        run_upgrade::<MigrateToV1<Test>>();
+
+        assert_eq!(ElxrReadings::<Test>::get(device_id(), 1_700_000_000), Some(reading));
+        assert_eq!(DeviceNonces::<Test>::get(device_id()), 6);
+        assert_eq!(Arrivals::<Test>::iter().count(), 0);
+    });
+}
@@ -0,0 +1,257 @@
+// Telemetry records reported by NRSH and ELXR devices
+//
+// Readings are stored as the devices report them: fixed-point integers with
+// the scale noted on each field, and a Unix timestamp from the device clock.
+use frame_support::pallet_prelude::*;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_std::prelude::*;
+
+use super::Config;
+
+/// Unix time in seconds, as reported by the device.
+pub type Moment = u64;
+
+pub type DeviceIdOf<T> = BoundedVec<u8, <T as Config>::MaxDeviceIdLength>;
+pub type BatchIdOf<T> = BoundedVec<u8, <T as Config>::MaxBatchIdLength>;
+pub type SignatureOf<T> = BoundedVec<u8, <T as Config>::MaxSignatureLength>;
+
+/// The chain a reading is for.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug, Encode, Decode, TypeInfo, MaxEncodedLen)]
+pub enum Project {
+    /// Nourish Chain, spirulina cultivation
+    Nrsh,
+    /// Elixir Chain, kombucha fermentation
+    Elxr,
+}
+
+/// A spirulina cultivation reading for the NRSH sister chain.
+#[derive(
+    CloneNoBound, PartialEqNoBound, EqNoBound, RuntimeDebugNoBound, Encode, Decode, TypeInfo, MaxEncodedLen,
+)]
+#[scale_info(skip_type_params(T))]
+#[codec(mel_bound())]
+pub struct NrshTelemetry<T: Config> {
+    pub device_id: DeviceIdOf<T>,
+    pub timestamp: Moment,
+    /// Position of the reading in the device's sequence, starting from 0
+    pub nonce: u64,
+    pub batch_id: BatchIdOf<T>,
+    pub ph: u32,               // scaled by 100
+    pub temperature: u32,      // scaled by 100
+    pub light: u32,            // scaled by 10
+    pub density: u32,          // scaled by 1000
+    pub dissolved_oxygen: u32, // scaled by 100
+    pub nitrate: u32,          // scaled by 10
+    pub salinity: u32,         // scaled by 10
+    pub battery: u32,          // scaled by 10
+    pub overall_health: u32,   // scaled by 10
+    pub harvest_ready: bool,
+    pub reporter: T::AccountId,
+    pub quantum_signature: SignatureOf<T>,
+}
+
+impl<T: Config> NrshTelemetry<T> {
+    /// The bytes the device signs: the project, then every field but the
+    /// signature in declaration order, SCALE encoded.
+    pub fn signing_payload(&self) -> Vec<u8> {
+        (
+            Project::Nrsh,
+            &self.device_id,
+            self.timestamp,
+            self.nonce,
+            &self.batch_id,
+            self.ph,
+            self.temperature,
+            self.light,
+            self.density,
+            self.dissolved_oxygen,
+            self.nitrate,
+            self.salinity,
+            self.battery,
+            self.overall_health,
+            self.harvest_ready,
+            &self.reporter,
+        )
+            .encode()
+    }
+}
+
+/// A kombucha fermentation reading.
+#[derive(
+    CloneNoBound, PartialEqNoBound, EqNoBound, RuntimeDebugNoBound, Encode, Decode, TypeInfo, MaxEncodedLen,
+)]
+#[scale_info(skip_type_params(T))]
+#[codec(mel_bound())]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize), serde(bound = ""))]
+pub struct ElxrTelemetry<T: Config> {
+    pub device_id: DeviceIdOf<T>,
+    pub timestamp: Moment,
+    /// Position of the reading in the device's sequence, starting from 0
+    pub nonce: u64,
+    /// Fermentation batch the device is monitoring
+    pub batch_id: BatchIdOf<T>,
+    pub ph: u32,           // scaled by 100
+    pub temperature: u32,  // scaled by 100
+    pub light: u32,        // scaled by 10
+    pub density: u32,      // scaled by 1000
+    pub co2: u32,          // scaled by 10
+    pub fermentation: u32, // scaled by 1000
+    pub battery: u32,      // scaled by 10
+    pub reporter: T::AccountId,
+    pub quantum_signature: SignatureOf<T>,
+}
+
+impl<T: Config> ElxrTelemetry<T> {
+    /// The bytes the device signs: the project, then every field but the
+    /// signature in declaration order, SCALE encoded.
+    pub fn signing_payload(&self) -> Vec<u8> {
+        (
+            Project::Elxr,
+            &self.device_id,
+            self.timestamp,
+            self.nonce,
+            &self.batch_id,
+            self.ph,
+            self.temperature,
+            self.light,
+            self.density,
+            self.co2,
+            self.fermentation,
+            self.battery,
+            &self.reporter,
+        )
+            .encode()
+    }
+}
+
+/// A reading for either chain, as accepted by `submit_telemetry`.
+#[derive(
+    CloneNoBound, PartialEqNoBound, EqNoBound, RuntimeDebugNoBound, Encode, Decode, TypeInfo, MaxEncodedLen,
+)]
+#[scale_info(skip_type_params(T))]
+#[codec(mel_bound())]
+pub enum Report<T: Config> {
+    Nrsh(NrshTelemetry<T>),
+    Elxr(ElxrTelemetry<T>),
+}
+
+impl<T: Config> Report<T> {
+    pub fn project(&self) -> Project {
+        match self {
+            Report::Nrsh(_) => Project::Nrsh,
+            Report::Elxr(_) => Project::Elxr,
+        }
+    }
+
+    pub fn device_id(&self) -> &DeviceIdOf<T> {
+        match self {
+            Report::Nrsh(reading) => &reading.device_id,
+            Report::Elxr(reading) => &reading.device_id,
+        }
+    }
+
+    pub fn timestamp(&self) -> Moment {
+        match self {
+            Report::Nrsh(reading) => reading.timestamp,
+            Report::Elxr(reading) => reading.timestamp,
+        }
+    }
+
+    pub fn nonce(&self) -> u64 {
+        match self {
+            Report::Nrsh(reading) => reading.nonce,
+            Report::Elxr(reading) => reading.nonce,
+        }
+    }
+
+    pub fn reporter(&self) -> &T::AccountId {
+        match self {
+            Report::Nrsh(reading) => &reading.reporter,
+            Report::Elxr(reading) => &reading.reporter,
+        }
+    }
+
+    pub fn quantum_signature(&self) -> &SignatureOf<T> {
+        match self {
+            Report::Nrsh(reading) => &reading.quantum_signature,
+            Report::Elxr(reading) => &reading.quantum_signature,
+        }
+    }
+
+    pub fn signing_payload(&self) -> Vec<u8> {
+        match self {
+            Report::Nrsh(reading) => reading.signing_payload(),
+            Report::Elxr(reading) => reading.signing_payload(),
+        }
+    }
+}
+
+/// Length of an hourly rollup period, in seconds of device time.
+pub const HOUR: Moment = 3_600;
+/// Length of a daily rollup period, in seconds of device time.
+pub const DAY: Moment = 86_400;
+
+/// Minimum, maximum and sum of one quantity over a rollup period.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug, Encode, Decode, TypeInfo, MaxEncodedLen)]
+pub struct Aggregate {
+    pub min: u32,
+    pub max: u32,
+    pub sum: u64,
+}
+
+impl Aggregate {
+    fn new(value: u32) -> Self {
+        Aggregate {
+            min: value,
+            max: value,
+            sum: value.into(),
+        }
+    }
+
+    fn record(&mut self, value: u32) {
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.sum = self.sum.saturating_add(value.into());
+    }
+
+    /// Mean over `count` readings, rounded down.
+    pub fn mean(&self, count: u32) -> u32 {
+        (self.sum / u64::from(count.max(1))) as u32
+    }
+}
+
+/// Summary of a device's ELXR readings for one batch over one hour or day.
+///
+/// Values keep the scale of the reading fields they summarize.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug, Encode, Decode, TypeInfo, MaxEncodedLen)]
+pub struct Rollup {
+    pub count: u32,
+    pub ph: Aggregate,
+    pub temperature: Aggregate,
+    pub density: Aggregate,
+    pub co2: Aggregate,
+    pub fermentation: Aggregate,
+}
+
+impl Rollup {
+    pub fn new<T: Config>(reading: &ElxrTelemetry<T>) -> Self {
+        Rollup {
+            count: 1,
+            ph: Aggregate::new(reading.ph),
+            temperature: Aggregate::new(reading.temperature),
+            density: Aggregate::new(reading.density),
+            co2: Aggregate::new(reading.co2),
+            fermentation: Aggregate::new(reading.fermentation),
+        }
+    }
+
+    pub fn record<T: Config>(&mut self, reading: &ElxrTelemetry<T>) {
+        self.count = self.count.saturating_add(1);
+        self.ph.record(reading.ph);
+        self.temperature.record(reading.temperature);
+        self.density.record(reading.density);
+        self.co2.record(reading.co2);
+        self.fermentation.record(reading.fermentation);
+    }
+}
@@ -0,0 +1,97 @@
+//! Weights for the telemetry pallet.
+//!
+//! Hand-written estimates, not benchmark results. Storage reads and writes are
+//! counted from each call's code path; execution times and proof sizes are
+//! conservative guesses for the default `Config` bounds. Replace this file
+//! with the output of `frame-benchmarking-cli` for the benchmarks in
+//! `src/parachain/benchmarking.rs` before a production runtime relies on it,
+//! from a node whose runtime is built with `runtime-benchmarks`:
+//!
+//! ```text
+//! <node> benchmark pallet --chain dev --pallet pallet_telemetry --extrinsic '*' \
+//!     --steps 50 --repeat 20 --output src/parachain/weights.rs
+//! ```
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use core::marker::PhantomData;
+use frame_support::{
+    traits::Get,
+    weights::{constants::RocksDbWeight, Weight},
+};
+
+/// Weight functions needed for the telemetry pallet.
+pub trait WeightInfo {
+    fn submit_telemetry(s: u32) -> Weight;
+    fn submit_unsigned_telemetry(s: u32) -> Weight;
+    fn prune(n: u32) -> Weight;
+}
+
+/// Weights for the telemetry pallet using the runtime's database weights.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+    /// Storage: `Elxr::Devices` (r:1 w:0)
+    /// Storage: `Telemetry::DeviceNonces` (r:1 w:1)
+    /// Storage: `Telemetry::ElxrReadings` or `Telemetry::NrshReadings` (r:1 w:1)
+    /// Storage: `Telemetry::Arrivals` (r:0 w:1)
+    /// The range of component `s` is `[0, T::MaxSignatureLength]`.
+    fn submit_telemetry(s: u32) -> Weight {
+        Weight::from_parts(310_000_000, 6_000)
+            .saturating_add(Weight::from_parts(2_000, 1).saturating_mul(s.into()))
+            .saturating_add(T::DbWeight::get().reads(3_u64))
+            .saturating_add(T::DbWeight::get().writes(3_u64))
+    }
+    /// Storage: `Elxr::Devices` (r:1 w:0)
+    /// Storage: `Telemetry::DeviceNonces` (r:1 w:1)
+    /// Storage: `Telemetry::LastUnsignedAt` (r:1 w:1)
+    /// Storage: `Telemetry::ElxrReadings` or `Telemetry::NrshReadings` (r:1 w:1)
+    /// Storage: `Telemetry::Arrivals` (r:0 w:1)
+    /// The range of component `s` is `[0, T::MaxSignatureLength]`.
+    fn submit_unsigned_telemetry(s: u32) -> Weight {
+        Weight::from_parts(310_000_000, 6_000)
+            .saturating_add(Weight::from_parts(2_000, 1).saturating_mul(s.into()))
+            .saturating_add(T::DbWeight::get().reads(4_u64))
+            .saturating_add(T::DbWeight::get().writes(4_u64))
+    }
+    /// Storage: `Telemetry::PruneCursor` (r:1 w:1)
+    /// Storage: `Telemetry::Arrivals` (r:n w:n)
+    /// Storage: `Telemetry::ElxrReadings` (r:n w:n)
+    /// Storage: `Telemetry::HourlyRollups` (r:n w:n)
+    /// Storage: `Telemetry::DailyRollups` (r:n w:n)
+    /// Storage: `Telemetry::PrunedRoots` (r:0 w:n)
+    /// Storage: `Telemetry::PrunedRootsCursor` (r:1 w:1)
+    /// The range of component `n` is `[0, T::MaxPrunedPerBlock]`.
+    fn prune(n: u32) -> Weight {
+        Weight::from_parts(5_000_000, 1_500)
+            .saturating_add(Weight::from_parts(40_000_000, 3_500).saturating_mul(n.into()))
+            .saturating_add(T::DbWeight::get().reads(2_u64))
+            .saturating_add(T::DbWeight::get().reads((4_u64).saturating_mul(n.into())))
+            .saturating_add(T::DbWeight::get().writes(3_u64))
+            .saturating_add(T::DbWeight::get().writes((4_u64).saturating_mul(n.into())))
+    }
+}
+
+// For backwards compatibility and tests.
+impl WeightInfo for () {
+    fn submit_telemetry(s: u32) -> Weight {
+        Weight::from_parts(310_000_000, 6_000)
+            .saturating_add(Weight::from_parts(2_000, 1).saturating_mul(s.into()))
+            .saturating_add(RocksDbWeight::get().reads(3_u64))
+            .saturating_add(RocksDbWeight::get().writes(3_u64))
+    }
+    fn submit_unsigned_telemetry(s: u32) -> Weight {
+        Weight::from_parts(310_000_000, 6_000)
+            .saturating_add(Weight::from_parts(2_000, 1).saturating_mul(s.into()))
+            .saturating_add(RocksDbWeight::get().reads(4_u64))
+            .saturating_add(RocksDbWeight::get().writes(4_u64))
+    }
+    fn prune(n: u32) -> Weight {
+        Weight::from_parts(5_000_000, 1_500)
+            .saturating_add(Weight::from_parts(40_000_000, 3_500).saturating_mul(n.into()))
+            .saturating_add(RocksDbWeight::get().reads(2_u64))
+            .saturating_add(RocksDbWeight::get().reads((4_u64).saturating_mul(n.into())))
+            .saturating_add(RocksDbWeight::get().writes(3_u64))
+            .saturating_add(RocksDbWeight::get().writes((4_u64).saturating_mul(n.into())))
+    }
+}
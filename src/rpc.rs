@@ -0,0 +1,200 @@
+//! JSON-RPC methods over [`crate::runtime_api`], for nodes to merge into
+//! their RPC module.
+//!
+//! Every method takes an optional block hash and queries the best block
+//! without one. IDs are hex-encoded bytes; records are returned as JSON.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use codec::Codec;
+use jsonrpsee::{
+    core::{Error as JsonRpseeError, RpcResult},
+    proc_macros::rpc,
+    types::error::{CallError, ErrorObject},
+};
+use serde::{de::DeserializeOwned, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_runtime::traits::Block as BlockT;
+
+use crate::runtime_api::{Page, RegistryApi, TelemetryApi};
+
+#[rpc(client, server)]
+pub trait ElxrApi<BlockHash, AccountId, Facility, Device, Scoby, Batch, Reading> {
+    #[method(name = "elxr_facility")]
+    fn facility(&self, facility_id: Bytes, at: Option<BlockHash>) -> RpcResult<Option<Facility>>;
+
+    #[method(name = "elxr_facilitiesByOwner")]
+    fn facilities_by_owner(
+        &self,
+        owner: AccountId,
+        page: Option<Page>,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Vec<(Bytes, Facility)>>;
+
+    #[method(name = "elxr_facilityDevices")]
+    fn facility_devices(
+        &self,
+        facility_id: Bytes,
+        page: Option<Page>,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Vec<(Bytes, Device)>>;
+
+    #[method(name = "elxr_scobyLineage")]
+    fn scoby_lineage(
+        &self,
+        scoby_id: Bytes,
+        page: Option<Page>,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Vec<(Bytes, Scoby)>>;
+
+    #[method(name = "elxr_batch")]
+    fn batch(&self, batch_id: Bytes, at: Option<BlockHash>) -> RpcResult<Option<Batch>>;
+
+    #[method(name = "elxr_deviceTelemetry")]
+    fn device_telemetry(
+        &self,
+        device_id: Bytes,
+        from: u64,
+        to: u64,
+        page: Option<Page>,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Vec<Reading>>;
+
+    #[method(name = "elxr_batchTelemetry")]
+    fn batch_telemetry(
+        &self,
+        batch_id: Bytes,
+        from: u64,
+        to: u64,
+        page: Option<Page>,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Vec<Reading>>;
+}
+
+/// Error code for a failed runtime API call.
+pub const RUNTIME_ERROR: i32 = 1;
+
+fn runtime_error(error: impl ToString) -> JsonRpseeError {
+    let error = ErrorObject::owned(RUNTIME_ERROR, "Runtime API call failed", Some(error.to_string()));
+    CallError::Custom(error).into()
+}
+
+/// Serves [`ElxrApiServer`] from a client's runtime.
+pub struct Elxr<C, Block> {
+    client: Arc<C>,
+    _marker: PhantomData<Block>,
+}
+
+impl<C, Block> Elxr<C, Block> {
+    pub fn new(client: Arc<C>) -> Self {
+        Elxr {
+            client,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<C, Block> Elxr<C, Block>
+where
+    Block: BlockT,
+    C: HeaderBackend<Block>,
+{
+    fn at(&self, at: Option<Block::Hash>) -> Block::Hash {
+        at.unwrap_or_else(|| self.client.info().best_hash)
+    }
+}
+
+fn keyed<T>(items: Vec<(Vec<u8>, T)>) -> Vec<(Bytes, T)> {
+    items.into_iter().map(|(id, item)| (id.into(), item)).collect()
+}
+
+impl<C, Block, AccountId, Facility, Device, Scoby, Batch, Reading>
+    ElxrApiServer<Block::Hash, AccountId, Facility, Device, Scoby, Batch, Reading> for Elxr<C, Block>
+where
+    Block: BlockT,
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+    C::Api: RegistryApi<Block, AccountId, Facility, Device, Scoby, Batch> + TelemetryApi<Block, Reading>,
+    AccountId: Codec + DeserializeOwned + Send + Sync + 'static,
+    Facility: Codec + Serialize + Send + Sync + 'static,
+    Device: Codec + Serialize + Send + Sync + 'static,
+    Scoby: Codec + Serialize + Send + Sync + 'static,
+    Batch: Codec + Serialize + Send + Sync + 'static,
+    Reading: Codec + Serialize + Send + Sync + 'static,
+{
+    fn facility(&self, facility_id: Bytes, at: Option<Block::Hash>) -> RpcResult<Option<Facility>> {
+        self.client.runtime_api().facility(self.at(at), facility_id.to_vec()).map_err(runtime_error)
+    }
+
+    fn facilities_by_owner(
+        &self,
+        owner: AccountId,
+        page: Option<Page>,
+        at: Option<Block::Hash>,
+    ) -> RpcResult<Vec<(Bytes, Facility)>> {
+        self.client
+            .runtime_api()
+            .facilities_by_owner(self.at(at), owner, page.unwrap_or_default())
+            .map(keyed)
+            .map_err(runtime_error)
+    }
+
+    fn facility_devices(
+        &self,
+        facility_id: Bytes,
+        page: Option<Page>,
+        at: Option<Block::Hash>,
+    ) -> RpcResult<Vec<(Bytes, Device)>> {
+        self.client
+            .runtime_api()
+            .facility_devices(self.at(at), facility_id.to_vec(), page.unwrap_or_default())
+            .map(keyed)
+            .map_err(runtime_error)
+    }
+
+    fn scoby_lineage(
+        &self,
+        scoby_id: Bytes,
+        page: Option<Page>,
+        at: Option<Block::Hash>,
+    ) -> RpcResult<Vec<(Bytes, Scoby)>> {
+        self.client
+            .runtime_api()
+            .scoby_lineage(self.at(at), scoby_id.to_vec(), page.unwrap_or_default())
+            .map(keyed)
+            .map_err(runtime_error)
+    }
+
+    fn batch(&self, batch_id: Bytes, at: Option<Block::Hash>) -> RpcResult<Option<Batch>> {
+        self.client.runtime_api().batch(self.at(at), batch_id.to_vec()).map_err(runtime_error)
+    }
+
+    fn device_telemetry(
+        &self,
+        device_id: Bytes,
+        from: u64,
+        to: u64,
+        page: Option<Page>,
+        at: Option<Block::Hash>,
+    ) -> RpcResult<Vec<Reading>> {
+        self.client
+            .runtime_api()
+            .device_telemetry(self.at(at), device_id.to_vec(), from, to, page.unwrap_or_default())
+            .map_err(runtime_error)
+    }
+
+    fn batch_telemetry(
+        &self,
+        batch_id: Bytes,
+        from: u64,
+        to: u64,
+        page: Option<Page>,
+        at: Option<Block::Hash>,
+    ) -> RpcResult<Vec<Reading>> {
+        self.client
+            .runtime_api()
+            .batch_telemetry(self.at(at), batch_id.to_vec(), from, to, page.unwrap_or_default())
+            .map_err(runtime_error)
+    }
+}
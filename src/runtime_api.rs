@@ -0,0 +1,84 @@
+//! Runtime APIs for reading ELXR registry and telemetry state.
+//!
+//! The APIs are generic over the records they return, so a runtime
+//! implements them with the pallet types for its own `Config`, each method
+//! forwarding to the query of the same name on [`crate::pallet::Pallet`] or
+//! [`crate::parachain::Pallet`]. IDs go in and come out as plain bytes.
+//! Lists are paged with [`Page`].
+
+use codec::{Codec, Decode, Encode};
+use scale_info::TypeInfo;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_std::prelude::*;
+
+/// A window into a list result.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Encode, Decode, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct Page {
+    /// Items to skip
+    pub offset: u32,
+    /// Most items to return, capped at [`Page::MAX_LIMIT`]
+    pub limit: u32,
+}
+
+impl Page {
+    /// Most items returned by one call.
+    pub const MAX_LIMIT: u32 = 100;
+
+    /// The items of `items` inside this page.
+    pub fn of<I: Iterator>(self, items: I) -> Vec<I::Item> {
+        items.skip(self.offset as usize).take(self.limit.min(Self::MAX_LIMIT) as usize).collect()
+    }
+}
+
+impl Default for Page {
+    fn default() -> Self {
+        Page {
+            offset: 0,
+            limit: Self::MAX_LIMIT,
+        }
+    }
+}
+
+sp_api::decl_runtime_apis! {
+    /// Facilities, devices, SCOBY cultures and batches in the kombucha registry.
+    pub trait RegistryApi<AccountId, Facility, Device, Scoby, Batch>
+    where
+        AccountId: Codec,
+        Facility: Codec,
+        Device: Codec,
+        Scoby: Codec,
+        Batch: Codec,
+    {
+        /// The facility registered under `facility_id`.
+        fn facility(facility_id: Vec<u8>) -> Option<Facility>;
+
+        /// The facilities registered by `owner`, in registration order.
+        fn facilities_by_owner(owner: AccountId, page: Page) -> Vec<(Vec<u8>, Facility)>;
+
+        /// The devices registered for `facility_id`.
+        fn facility_devices(facility_id: Vec<u8>, page: Page) -> Vec<(Vec<u8>, Device)>;
+
+        /// The culture `scoby_id` followed by its ancestors breadth first: its
+        /// parents, then their parents, and so on, each culture once.
+        fn scoby_lineage(scoby_id: Vec<u8>, page: Page) -> Vec<(Vec<u8>, Scoby)>;
+
+        /// The batch registered under `batch_id`, including its status.
+        fn batch(batch_id: Vec<u8>) -> Option<Batch>;
+    }
+
+    /// Stored ELXR telemetry readings.
+    pub trait TelemetryApi<Reading>
+    where
+        Reading: Codec,
+    {
+        /// Readings from `device_id` with device timestamps in `from..=to`, in
+        /// storage order, which is the same from one call to the next until the
+        /// device's readings change. Callers sort a page by timestamp themselves.
+        fn device_telemetry(device_id: Vec<u8>, from: u64, to: u64, page: Page) -> Vec<Reading>;
+
+        /// Readings for `batch_id` with device timestamps in `from..=to`, oldest first.
+        fn batch_telemetry(batch_id: Vec<u8>, from: u64, to: u64, page: Page) -> Vec<Reading>;
+    }
+}
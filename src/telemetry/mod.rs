@@ -7,6 +7,11 @@
 #![no_main]
 
 use arduino_hal::prelude::*;
+// Depends on `elxr` with `default-features = false`, which builds only the
+// heapless Reed-Solomon framing and needs no allocator
+use elxr::error::classical::{
+    ClassicalErrorCorrection, SERIAL_DATA_SHARDS, SERIAL_INTERLEAVE_DEPTH, SERIAL_PARITY_SHARDS,
+};
 use arduino_hal::{adc, delay_ms};
 use embedded_hal::digital::v2::OutputPin;
 use heapless::String;
@@ -95,6 +100,10 @@ const OPTIMAL_SALINITY_MAX: f32 = 20.0; // g/L
 // Battery monitoring
 const BATTERY_LEVEL_PIN: u8 = 7;  // A7
 
+// Reed-Solomon protected serial frame: a full 512-byte JSON payload plus its
+// 4-byte length header fills 33 RS(24, 16) codewords
+const SERIAL_FRAME_CAPACITY: usize = 33 * (SERIAL_DATA_SHARDS + SERIAL_PARITY_SHARDS);
+
 // Rococo testnet endpoint (to be updated with actual endpoint)
 const ROCOCO_ENDPOINT: &str = "wss://rococo-rpc.polkadot.io";
 
@@ -111,6 +120,9 @@ fn main() -> ! {
     
     // Generate quantum-resistant keys
     let keys = quantum_crypto::generate_keys();
+
+    // Same codec the pallet decodes with; encoding never touches the heap
+    let serial_codec = ClassicalErrorCorrection::new(SERIAL_DATA_SHARDS, SERIAL_PARITY_SHARDS);
     
     // Store last measurement time to handle timing
     let mut last_measurement_time: u32 = 0;
@@ -190,11 +202,15 @@ fn main() -> ! {
         // Append signature hash to JSON (simplified)
         write!(json_data, ",\"qsig\":\"{}\"", signature.signature[0]).unwrap();
         
-        // Send data to serial (for debugging and transmission)
-        for byte in json_data.as_bytes() {
+        // Reed-Solomon encode the frame so line noise can be repaired on-chain
+        let frame: Vec<u8, SERIAL_FRAME_CAPACITY> = serial_codec
+            .encode_interleaved_heapless(json_data.as_bytes(), SERIAL_INTERLEAVE_DEPTH)
+            .unwrap();
+
+        // Send to serial with a length prefix, since the frame is binary
+        for byte in (frame.len() as u16).to_le_bytes().iter().chain(frame.iter()) {
             block!(serial.write(*byte)).unwrap();
         }
-        block!(serial.write(b'\n')).unwrap();
         
         // Battery level handling
         if battery_percentage < 15.0 {